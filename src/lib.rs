@@ -8,6 +8,10 @@ pub mod allocator;
 pub mod macros;
 
 pub mod nvim_funcs;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod task;
+mod uv;
 
 #[global_allocator]
 static GLOBAL_ALLOCATOR: NvAllocator = NvAllocator::new(true);
@@ -22,6 +22,21 @@ pub(crate) unsafe fn assign_field<T>(
     field.write(value);
 }
 
+/// Returns a reference to the field if a value was assigned to it
+///
+/// # Safety
+///
+/// `field` must be the field at `idx` of the struct that `mask` belongs to.
+pub(crate) unsafe fn masked_field<'a, T>(
+    idx: usize,
+    mask: u64,
+    mask_offsets: &'static [u64],
+    field: &'a MaybeUninit<T>,
+) -> Option<&'a T> {
+    let bit: u64 = 1 << mask_offsets[idx];
+    (mask & bit == bit).then(|| unsafe { field.assume_init_ref() })
+}
+
 /// Create a struct with masked fields
 ///
 /// # The mask
@@ -35,6 +50,11 @@ pub(crate) unsafe fn assign_field<T>(
 /// the structs mask we are required to use setters instead. These setters handle the mask and drop logic of
 /// the arguments so from a user perspective its just a function with an argument.
 ///
+/// # RPC
+///
+/// With the `rpc` feature the struct implements `RpcArg` which converts the fields that are set
+/// in to a dictionary keyed by the field names (or their renames).
+///
 /// # Builder module
 ///
 /// When a struct with masked fields is created a builder module is defined.
@@ -48,6 +68,13 @@ macro_rules! masked_builder {
         $struct_vis struct $struct_name {
             mask: u64,
         }
+
+        #[cfg(feature = "rpc")]
+        impl $crate::rpc::RpcArg for $struct_name {
+            fn to_object(&self) -> Result<$crate::nvim_types::Object, $crate::rpc::RpcError> {
+                Ok($crate::nvim_types::Object::Dict($crate::nvim_types::Dict::default()))
+            }
+        }
     };
     ($(#[$struct_attr:meta])* $struct_vis:vis struct $struct_name:ident $(<$lf:lifetime>)? {
         $(
@@ -92,6 +119,29 @@ macro_rules! masked_builder {
         impl $(<$lf>)? $struct_name $(<$lf>)? {
             $crate::macros::masked_builder::gen_funcs!(@IDX=0; $($( #[$( $builder_attr )+] )? $field_name: $field_ty,)*);
         }
+
+        #[cfg(feature = "rpc")]
+        impl $(<$lf>)? $crate::rpc::RpcArg for $struct_name $(<$lf>)? {
+            #[allow(unused_assignments)]
+            fn to_object(&self) -> Result<$crate::nvim_types::Object, $crate::rpc::RpcError> {
+                use $crate::nvim_types::{Dict, Object, OwnedThinString};
+
+                let mut dict = Dict::default();
+                let mut idx = 0;
+                $(
+                    let field = unsafe {
+                        $crate::macros::masked_builder::masked_field(
+                            idx, self.mask, &builder::MASK_OFFSETS, &self.$field_name
+                        )
+                    };
+                    if let Some(field) = field {
+                        dict.insert(OwnedThinString::from(builder::FIELDS[idx]), field.to_object()?);
+                    }
+                    idx += 1;
+                )+
+                Ok(Object::Dict(dict))
+            }
+        }
     };
 }
 pub(crate) use masked_builder;
@@ -6,8 +6,10 @@ pub(crate) mod hash_face;
 pub(crate) mod masked_builder;
 pub(crate) mod nv_enum;
 pub(crate) mod nvim_values;
+pub(crate) mod obj_ref_setters;
 pub(crate) mod one_of_objects;
-pub(crate) mod thinstring;
+#[doc(hidden)]
+pub mod thinstring;
 pub(crate) mod tri;
 pub(crate) mod zeroed_default;
 pub(crate) mod utils;
@@ -1,3 +1,21 @@
+/// Declares an enum that is represented by a string in Neovim
+///
+/// The enum must derive [`Clone`] and [`Copy`]. The enum is read from its string value in a
+/// plugin configuration, see [`FromConfig`](crate::plugin::config::FromConfig).
+///
+/// # Example
+/// ```no_run
+/// use nvimium::nv_str_enum;
+///
+/// nv_str_enum!(
+///     #[derive(Clone, Copy, Debug)]
+///     pub enum Position {
+///         Left = "left",
+///         Right = "right",
+///     }
+/// );
+/// ```
+#[macro_export]
 macro_rules! nv_str_enum {
     (
         $(#[$($enum_attr:tt)+])*
@@ -11,6 +29,8 @@ macro_rules! nv_str_enum {
 
         impl $enum_name {
             const LOOKUP: &[$crate::nvim_types::ThinString<'static>] = &[$($crate::th!($enum_str_val)),*];
+            #[allow(dead_code)]
+            const VARIANTS: &[Self] = &[$(Self::$enum_variant),*];
         }
 
         unsafe impl $crate::nvim_types::AsThinString for $enum_name {
@@ -24,6 +44,24 @@ macro_rules! nv_str_enum {
             pub(crate) const fn as_enum_str(&self) -> $crate::nvim_types::ThinString<'static> {
                 Self::LOOKUP[*self as usize]
             }
+
+            /// Returns the variant represented by the provided string if any.
+            #[allow(dead_code)]
+            #[inline]
+            pub(crate) fn from_enum_str(s: $crate::nvim_types::ThinString<'_>) -> Option<Self> {
+                Self::LOOKUP
+                    .iter()
+                    .position(|v| v.as_slice() == s.as_slice())
+                    .map(|i| Self::VARIANTS[i])
+            }
+        }
+
+        impl $crate::plugin::config::FromConfig for $enum_name {
+            fn from_config(
+                value: $crate::plugin::config::ConfigValue<'_>,
+            ) -> ::core::result::Result<Self, $crate::plugin::config::ConfigError> {
+                $crate::plugin::config::from_enum_str(value, Self::LOOKUP, Self::from_enum_str)
+            }
         }
     };
 }
@@ -56,7 +94,7 @@ pub(crate) use nv_obj_ref_enum;
 
 #[cfg(test)]
 mod tests {
-    use crate::nvim_types::AsThinString;
+    use crate::{nvim_types::AsThinString, th};
 
     nv_str_enum!(
         #[derive(Clone, Copy)]
@@ -81,4 +119,13 @@ mod tests {
         assert_eq!(MyEnum::B.as_enum_str(), "b");
         assert_eq!(MyEnum::C.as_enum_str(), "c");
     }
+
+    #[test]
+    fn my_enum_from_str() {
+        assert!(matches!(MyEnum::from_enum_str(th!("a")), Some(MyEnum::A)));
+        assert!(matches!(MyEnum::from_enum_str(th!("b")), Some(MyEnum::B)));
+        assert!(matches!(MyEnum::from_enum_str(th!("c")), Some(MyEnum::C)));
+        assert!(MyEnum::from_enum_str(th!("d")).is_none());
+        assert!(MyEnum::from_enum_str(th!("")).is_none());
+    }
 }
@@ -0,0 +1,56 @@
+/// Generates setters for union fields stored as an [`ObjectRef`]
+///
+/// Every listed field gets a setter accepting a string, the optional group field gets a setter
+/// for its id and its name. The indexes are the same as the ones used for
+/// [`masked_builder`](super::masked_builder::masked_builder) setters.
+///
+/// [`ObjectRef`]: crate::nvim_types::object::ObjectRef
+macro_rules! obj_ref_setters {
+    ($($idx:literal => $field:ident),* $(; group = $group_idx:literal)?) => {
+        $(
+            pub fn $field<TH: ?Sized + $crate::nvim_types::AsThinString>(&mut self, $field: &'a TH) -> &mut Self {
+                unsafe {
+                    $crate::macros::masked_builder::assign_field(
+                        $idx,
+                        &mut self.mask,
+                        &builder::MASK_OFFSETS,
+                        &mut self.$field,
+                        $crate::nvim_types::object::ObjectRef::new_th($field.as_thinstr()),
+                    )
+                };
+                self
+            }
+        )*
+
+        $(
+            /// Set the group via its id
+            pub fn group(&mut self, id: $crate::nvim_types::Integer) -> &mut Self {
+                unsafe {
+                    $crate::macros::masked_builder::assign_field(
+                        $group_idx,
+                        &mut self.mask,
+                        &builder::MASK_OFFSETS,
+                        &mut self.group,
+                        $crate::nvim_types::object::ObjectRef::new_int(id),
+                    )
+                };
+                self
+            }
+
+            /// Set the group via its name
+            pub fn group_name<TH: ?Sized + $crate::nvim_types::AsThinString>(&mut self, name: &'a TH) -> &mut Self {
+                unsafe {
+                    $crate::macros::masked_builder::assign_field(
+                        $group_idx,
+                        &mut self.mask,
+                        &builder::MASK_OFFSETS,
+                        &mut self.group,
+                        $crate::nvim_types::object::ObjectRef::new_th(name.as_thinstr()),
+                    )
+                };
+                self
+            }
+        )?
+    };
+}
+pub(crate) use obj_ref_setters;
@@ -10,6 +10,7 @@ macro_rules! th {
     }
 }
 
-pub(crate) const fn th_f<'a>(s: &'a str) -> ThinString<'a> {
+#[doc(hidden)]
+pub const fn th_f<'a>(s: &'a str) -> ThinString<'a> {
     ThinString::from_null_terminated(s.as_bytes())
 }
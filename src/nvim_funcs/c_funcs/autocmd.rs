@@ -0,0 +1,46 @@
+use std::mem::MaybeUninit;
+
+use crate::nvim_types::{
+    Arena, Array, Channel, Error, Integer, Object, ThinString,
+    borrowed::Borrowed,
+    opts::{
+        clear_autocmds::ClearAutocmdsOpts, create_augroup::CreateAugroupOpts,
+        create_autocmd::CreateAutocmdOpts, exec_autocmds::ExecAutocmdsOpts,
+        get_autocmds::GetAutocmdsOpts,
+    },
+};
+
+unsafe extern "C" {
+    pub fn nvim_clear_autocmds(
+        opts: *const ClearAutocmdsOpts<'_>,
+        arena: *mut Arena,
+        err: *mut Error,
+    );
+    pub fn nvim_create_augroup(
+        chan: Channel,
+        name: ThinString<'_>,
+        opts: *const CreateAugroupOpts,
+        err: *mut Error,
+    ) -> MaybeUninit<Integer>;
+    pub fn nvim_create_autocmd(
+        chan: Channel,
+        event: Borrowed<'_, Object>,
+        opts: *const CreateAutocmdOpts<'_>,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<Integer>;
+    pub fn nvim_del_augroup_by_id(id: Integer, err: *mut Error);
+    pub fn nvim_del_augroup_by_name(name: ThinString<'_>, err: *mut Error);
+    pub fn nvim_del_autocmd(id: Integer, err: *mut Error);
+    pub fn nvim_exec_autocmds(
+        event: Borrowed<'_, Object>,
+        opts: *const ExecAutocmdsOpts<'_>,
+        arena: *mut Arena,
+        err: *mut Error,
+    );
+    pub fn nvim_get_autocmds(
+        opts: *const GetAutocmdsOpts<'_>,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<Array>;
+}
@@ -23,6 +23,13 @@ unsafe extern "C" {
     ) -> MaybeUninit<Boolean>;
 
     pub fn nvim_buf_call(buf: Buffer, f: LuaRef, err: *mut Error) -> MaybeUninit<Object>;
+    pub fn nvim_buf_del_keymap(
+        chan: Channel,
+        buf: Buffer,
+        mode: KeyMapMode,
+        lhs: ThinString<'_>,
+        err: *mut Error,
+    );
     pub fn nvim_buf_del_mark(
         buf: Buffer,
         name: ThinString<'_>,
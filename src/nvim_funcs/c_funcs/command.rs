@@ -1,9 +1,12 @@
 use std::mem::MaybeUninit;
 
 use crate::nvim_types::{
-    Arena, Buffer, Channel, Dict, Error, ThinString,
+    Arena, Buffer, Channel, Dict, Error, OwnedThinString, ThinString,
     func_types::create_user_command::UserCommand,
-    opts::{create_user_command::CreateUserCommandOpts, get_commands::GetCommandOpts},
+    opts::{
+        cmd::Cmd, cmd_opts::CmdOpts, create_user_command::CreateUserCommandOpts,
+        get_commands::GetCommandOpts, parse_cmd::ParseCmdOpts,
+    },
 };
 
 unsafe extern "C" {
@@ -24,7 +27,13 @@ unsafe extern "C" {
         err: *mut Error,
     ) -> MaybeUninit<Dict>;
 
-    // TODO: nvim_cmd
+    pub fn nvim_cmd(
+        chan: Channel,
+        cmd: *const Cmd<'_>,
+        opts: *const CmdOpts,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<OwnedThinString>;
 
     pub fn nvim_create_user_command(
         chan: Channel,
@@ -40,4 +49,11 @@ unsafe extern "C" {
         arena: *mut Arena,
         err: *mut Error,
     ) -> MaybeUninit<Dict>;
+
+    pub fn nvim_parse_cmd(
+        s: ThinString<'_>,
+        opts: *const ParseCmdOpts,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<Cmd<'static>>;
 }
@@ -1,7 +1,13 @@
-use std::mem::MaybeUninit;
+use std::mem::{ManuallyDrop, MaybeUninit};
 
 use crate::nvim_types::{
-    Arena, Array, Boolean, Buffer, Error, Integer, NameSpace, extmark::ExtMark,
+    Arena, Array, Boolean, Buffer, Dict, Error, Integer, NameSpace, Object, ThinString,
+    borrowed::Borrowed,
+    extmark::ExtMark,
+    opts::{
+        get_extmark::GetExtmarkOpts, get_extmarks::GetExtmarksOpts,
+        set_decoration_provider::DecorationProviderOpts, set_extmark::SetExtmarkOpts,
+    },
 };
 
 unsafe extern "C" {
@@ -24,9 +30,37 @@ unsafe extern "C" {
         buf: Buffer,
         ns: NameSpace,
         ex: ExtMark,
-        // TODO
-        opts: *mut (),
+        opts: *const GetExtmarkOpts,
         arena: *mut Arena,
         err: *mut Error,
     ) -> MaybeUninit<Array>;
+
+    pub fn nvim_buf_get_extmarks(
+        buf: Buffer,
+        ns: NameSpace,
+        start: Borrowed<'_, Object>,
+        end: Borrowed<'_, Object>,
+        opts: *const GetExtmarksOpts,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<Array>;
+
+    pub fn nvim_buf_set_extmark(
+        buf: Buffer,
+        ns: NameSpace,
+        line: Integer,
+        col: Integer,
+        opts: *const SetExtmarkOpts<'_>,
+        err: *mut Error,
+    ) -> MaybeUninit<ExtMark>;
+
+    pub fn nvim_create_namespace(name: ThinString<'_>) -> NameSpace;
+
+    pub fn nvim_get_namespaces(arena: *mut Arena) -> ManuallyDrop<Dict>;
+
+    pub fn nvim_set_decoration_provider(
+        ns: NameSpace,
+        opts: *mut DecorationProviderOpts,
+        err: *mut Error,
+    );
 }
@@ -1,3 +1,4 @@
+pub mod autocmd;
 pub mod buffer;
 pub mod command;
 pub mod extmark;
@@ -6,3 +7,4 @@ pub mod options;
 pub mod vimscript;
 pub mod tabpage;
 pub mod win_config;
+pub mod window;
@@ -6,7 +6,7 @@ unsafe extern "C" {
     pub fn nvim_open_win(
         buf: Buffer,
         enter: Boolean,
-        config: *const WinConfig,
+        config: *const WinConfig<'_>,
         err: *mut Error,
     ) -> MaybeUninit<Window>;
 }
@@ -0,0 +1,61 @@
+use std::mem::MaybeUninit;
+
+use crate::nvim_types::{
+    Arena, Array, Boolean, Buffer, Dict, Error, Integer, LuaRef, Object, TabPage, ThinString,
+    Window,
+    borrowed::Borrowed,
+    opts::{win_opts::WinConfig, win_text_height::WinTextHeightOpts},
+    returns::utils::ArrayOf,
+};
+
+unsafe extern "C" {
+    pub fn nvim_win_call(win: Window, fun: LuaRef, err: *mut Error) -> MaybeUninit<Object>;
+    pub fn nvim_win_close(win: Window, force: Boolean, err: *mut Error);
+    pub fn nvim_win_del_var(win: Window, name: ThinString<'_>, err: *mut Error);
+    pub fn nvim_win_get_buf(win: Window, err: *mut Error) -> MaybeUninit<Buffer>;
+    pub fn nvim_win_get_config(
+        win: Window,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<WinConfig<'static>>;
+    pub fn nvim_win_get_cursor(
+        win: Window,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<ArrayOf<Integer>>;
+    pub fn nvim_win_get_height(win: Window, err: *mut Error) -> MaybeUninit<Integer>;
+    pub fn nvim_win_get_number(win: Window, err: *mut Error) -> MaybeUninit<Integer>;
+    pub fn nvim_win_get_position(
+        win: Window,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<ArrayOf<Integer>>;
+    pub fn nvim_win_get_tabpage(win: Window, err: *mut Error) -> MaybeUninit<TabPage>;
+    pub fn nvim_win_get_var(
+        win: Window,
+        name: ThinString<'_>,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<Object>;
+    pub fn nvim_win_get_width(win: Window, err: *mut Error) -> MaybeUninit<Integer>;
+    pub fn nvim_win_hide(win: Window, err: *mut Error);
+    pub fn nvim_win_is_valid(win: Window) -> Boolean;
+    pub fn nvim_win_set_buf(win: Window, buf: Buffer, err: *mut Error);
+    pub fn nvim_win_set_config(win: Window, config: *const WinConfig<'_>, err: *mut Error);
+    pub fn nvim_win_set_cursor(win: Window, pos: Borrowed<'_, Array>, err: *mut Error);
+    pub fn nvim_win_set_height(win: Window, height: Integer, err: *mut Error);
+    pub fn nvim_win_set_hl_ns(win: Window, ns_id: Integer, err: *mut Error);
+    pub fn nvim_win_set_var(
+        win: Window,
+        name: ThinString<'_>,
+        value: Borrowed<'_, Object>,
+        err: *mut Error,
+    );
+    pub fn nvim_win_set_width(win: Window, width: Integer, err: *mut Error);
+    pub fn nvim_win_text_height(
+        win: Window,
+        opts: *const WinTextHeightOpts,
+        arena: *mut Arena,
+        err: *mut Error,
+    ) -> MaybeUninit<Dict>;
+}
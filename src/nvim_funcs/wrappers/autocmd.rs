@@ -0,0 +1,259 @@
+use thread_lock::call_check;
+
+use crate::{
+    macros::tri::{tri_ez, tri_nc, tri_ret},
+    nvim_funcs::c_funcs::autocmd::{
+        nvim_clear_autocmds, nvim_create_augroup, nvim_create_autocmd, nvim_del_augroup_by_id,
+        nvim_del_augroup_by_name, nvim_del_autocmd, nvim_exec_autocmds, nvim_get_autocmds,
+    },
+    nvim_types::{
+        Array, AsThinString, Channel, Error, Integer, KVec, Object, OwnedThinString,
+        call_with_arena,
+        opts::{
+            clear_autocmds::ClearAutocmdsOpts, create_augroup::CreateAugroupOpts,
+            create_autocmd::CreateAutocmdOpts, exec_autocmds::ExecAutocmdsOpts,
+            get_autocmds::GetAutocmdsOpts,
+        },
+        returns::autocmd::AutocmdInfo,
+    },
+};
+
+fn events_to_obj<TH: AsThinString>(events: &[TH]) -> Object {
+    let mut arr = Array::default();
+    arr.reserve_exact(events.len());
+    arr.extend(
+        events
+            .iter()
+            .map(|e| Object::String(OwnedThinString::from(e.as_thinstr()))),
+    );
+    Object::Array(arr)
+}
+
+/// Clears the autocommands matching the provided options
+pub fn clear_autocmds(opts: &ClearAutocmdsOpts) -> Result<(), Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ez! {
+                err;
+                nvim_clear_autocmds(opts, arena, &raw mut err);
+            }
+        })
+    }
+}
+
+/// Creates or gets an autocommand group
+///
+/// Returns the id of the group.
+pub fn create_augroup<TH: AsThinString>(
+    name: TH,
+    opts: &CreateAugroupOpts,
+) -> Result<Integer, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_create_augroup(Channel::LUA_INTERNAL_CALL, name.as_thinstr(), opts, &raw mut err) };
+    }
+}
+
+/// Creates an autocommand for the provided events
+///
+/// Returns the id of the autocommand.
+pub fn create_autocmd<TH: AsThinString>(
+    events: &[TH],
+    opts: &CreateAutocmdOpts,
+) -> Result<Integer, Error> {
+    call_check();
+
+    let events = events_to_obj(events);
+    unsafe {
+        call_with_arena(|arena| {
+            tri_nc! {
+                err;
+                nvim_create_autocmd(Channel::LUA_INTERNAL_CALL, (&events).into(), opts, arena, &raw mut err);
+            }
+        })
+    }
+}
+
+/// Deletes an autocommand group and the autocommands it contains by its id
+pub fn del_augroup_by_id(id: Integer) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_del_augroup_by_id(id, &raw mut err) };
+    }
+}
+
+/// Deletes an autocommand group and the autocommands it contains by its name
+pub fn del_augroup_by_name<TH: AsThinString>(name: TH) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_del_augroup_by_name(name.as_thinstr(), &raw mut err) };
+    }
+}
+
+pub fn del_autocmd(id: Integer) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_del_autocmd(id, &raw mut err) };
+    }
+}
+
+/// Executes the autocommands matching the events and options
+pub fn exec_autocmds<TH: AsThinString>(
+    events: &[TH],
+    opts: &ExecAutocmdsOpts,
+) -> Result<(), Error> {
+    call_check();
+
+    let events = events_to_obj(events);
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ez! {
+                err;
+                nvim_exec_autocmds((&events).into(), opts, arena, &raw mut err);
+            }
+        })
+    }
+}
+
+/// Gets the autocommands matching the provided options
+pub fn get_autocmds(opts: &GetAutocmdsOpts) -> Result<KVec<AutocmdInfo>, Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_get_autocmds(opts, arena, &raw mut err);
+                AutocmdInfo::from_c_func_ret_list;
+            }
+        })
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        self as nvimium,
+        nvim_types::{
+            Error, Object, OwnedThinString,
+            opts::{
+                clear_autocmds::ClearAutocmdsOpts, create_augroup::CreateAugroupOpts,
+                create_autocmd::CreateAutocmdOpts, exec_autocmds::ExecAutocmdsOpts,
+                get_autocmds::GetAutocmdsOpts,
+            },
+        },
+    };
+
+    use super::*;
+
+    #[nvim_test::nvim_test]
+    fn augroup_create_del() {
+        let id = create_augroup(c"nvimium_group", &CreateAugroupOpts::default()).unwrap();
+        let mut opts = CreateAugroupOpts::default();
+        opts.clear(false);
+        assert_eq!(create_augroup(c"nvimium_group", &opts).unwrap(), id);
+
+        del_augroup_by_id(id).unwrap();
+        let id = create_augroup(c"nvimium_group", &CreateAugroupOpts::default()).unwrap();
+        del_augroup_by_name(c"nvimium_group").unwrap();
+        del_augroup_by_id(id).unwrap_err();
+    }
+
+    #[nvim_test::nvim_test]
+    fn callback_args_and_data() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let group = create_augroup(c"nvimium_cb", &CreateAugroupOpts::default()).unwrap();
+
+        let mut opts = CreateAutocmdOpts::default();
+        opts.group(group)
+            .pattern(c"NvimiumEvent")
+            .desc(c"test autocmd")
+            .callback(move |args| {
+                assert_eq!(args.event, "User");
+                assert_eq!(args.r#match, "NvimiumEvent");
+                assert_eq!(args.group, Some(group));
+                assert_eq!(args.data, Object::Integer(10));
+                CALLS.fetch_add(1, Ordering::Relaxed);
+                Ok::<_, Error>(false)
+            });
+        let id = create_autocmd(&[c"User"], &opts).unwrap();
+
+        let mut exec_opts = ExecAutocmdsOpts::default();
+        exec_opts.pattern(c"NvimiumEvent").data(Object::Integer(10));
+        exec_autocmds(&[c"User"], &exec_opts).unwrap();
+        exec_autocmds(&[c"User"], &exec_opts).unwrap();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+        let mut get_opts = GetAutocmdsOpts::default();
+        get_opts.group(group);
+        let autocmds = get_autocmds(&get_opts).unwrap();
+        assert_eq!(autocmds.len(), 1);
+        assert_eq!(autocmds[0].id, Some(id));
+        assert_eq!(autocmds[0].event, c"User");
+        assert_eq!(autocmds[0].pattern, c"NvimiumEvent");
+        assert_eq!(
+            autocmds[0].desc,
+            Some(OwnedThinString::from(c"test autocmd"))
+        );
+        assert!(matches!(autocmds[0].callback, Some(Object::LuaRef(_))));
+
+        del_autocmd(id).unwrap();
+        assert!(get_autocmds(&get_opts).unwrap().is_empty());
+    }
+
+    #[nvim_test::nvim_test]
+    fn returning_true_deletes() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let mut opts = CreateAutocmdOpts::default();
+        opts.pattern(c"NvimiumOnce").callback(|_| {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, Error>(true)
+        });
+        create_autocmd(&[c"User"], &opts).unwrap();
+
+        let mut exec_opts = ExecAutocmdsOpts::default();
+        exec_opts.pattern(c"NvimiumOnce");
+        exec_autocmds(&[c"User"], &exec_opts).unwrap();
+        exec_autocmds(&[c"User"], &exec_opts).unwrap();
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    }
+
+    #[nvim_test::nvim_test]
+    fn command_and_clear() {
+        let mut opts = CreateAutocmdOpts::default();
+        opts.pattern(c"NvimiumCmd")
+            .command(c"let g:nvimium_autocmd_ran = 1");
+        create_autocmd(&[c"User"], &opts).unwrap();
+
+        let mut get_opts = GetAutocmdsOpts::default();
+        get_opts.event(c"User").pattern(c"NvimiumCmd");
+        let autocmds = get_autocmds(&get_opts).unwrap();
+        assert_eq!(autocmds.len(), 1);
+        assert_eq!(autocmds[0].command, c"let g:nvimium_autocmd_ran = 1");
+
+        let mut exec_opts = ExecAutocmdsOpts::default();
+        exec_opts.pattern(c"NvimiumCmd");
+        exec_autocmds(&[c"User"], &exec_opts).unwrap();
+        assert_eq!(
+            crate::nvim_funcs::global::get_var(c"nvimium_autocmd_ran").unwrap(),
+            Object::Integer(1)
+        );
+
+        let mut clear_opts = ClearAutocmdsOpts::default();
+        clear_opts.event(c"User").pattern(c"NvimiumCmd");
+        clear_autocmds(&clear_opts).unwrap();
+        assert!(get_autocmds(&get_opts).unwrap().is_empty());
+    }
+}
@@ -3,12 +3,12 @@ use thread_lock::call_check;
 use crate::{
     macros::tri::{tri_ez, tri_nc, tri_ret},
     nvim_funcs::c_funcs::buffer::{
-        nvim_buf_attach, nvim_buf_call, nvim_buf_del_mark, nvim_buf_del_var, nvim_buf_delete,
-        nvim_buf_get_changedtick, nvim_buf_get_keymap, nvim_buf_get_lines, nvim_buf_get_mark,
-        nvim_buf_get_name, nvim_buf_get_offset, nvim_buf_get_text, nvim_buf_get_var,
-        nvim_buf_is_loaded, nvim_buf_is_valid, nvim_buf_line_count, nvim_buf_set_keymap,
-        nvim_buf_set_lines, nvim_buf_set_mark, nvim_buf_set_name, nvim_buf_set_text,
-        nvim_buf_set_var,
+        nvim_buf_attach, nvim_buf_call, nvim_buf_del_keymap, nvim_buf_del_mark, nvim_buf_del_var,
+        nvim_buf_delete, nvim_buf_get_changedtick, nvim_buf_get_keymap, nvim_buf_get_lines,
+        nvim_buf_get_mark, nvim_buf_get_name, nvim_buf_get_offset, nvim_buf_get_text,
+        nvim_buf_get_var, nvim_buf_is_loaded, nvim_buf_is_valid, nvim_buf_line_count,
+        nvim_buf_set_keymap, nvim_buf_set_lines, nvim_buf_set_mark, nvim_buf_set_name,
+        nvim_buf_set_text, nvim_buf_set_var,
     },
     nvim_types::{
         Array, AsThinString, Boolean, Buffer, Channel, Error, Integer, IntoLua, Object,
@@ -242,6 +242,21 @@ pub fn buf_line_count(buf: Buffer) -> Result<Integer, Error> {
     }
 }
 
+pub fn buf_del_keymap<TH: AsThinString>(
+    buf: Buffer,
+    mode: KeyMapMode,
+    lhs: TH,
+) -> Result<(), Error> {
+    call_check();
+
+    unsafe {
+        tri_ez! {
+            err;
+            nvim_buf_del_keymap(Channel::LUA_INTERNAL_CALL, buf, mode, lhs.as_thinstr(), &raw mut err);
+        }
+    }
+}
+
 pub fn buf_set_keymap<TH: AsThinString, TH2: AsThinString>(
     buf: Buffer,
     mode: KeyMapMode,
@@ -339,7 +354,7 @@ pub fn buf_set_var<TH: AsThinString>(buf: Buffer, name: TH, val: &Object) -> Res
 
 #[cfg(all(not(miri), feature = "testing"))]
 mod tests {
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
     use crate::{
         self as nvimium, array,
@@ -392,6 +407,26 @@ mod tests {
         assert!(ON_LINES_FLAG.load(Ordering::SeqCst));
     }
 
+    #[nvim_test::nvim_test]
+    fn buf_attach_detach() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        super::buf_attach(
+            Buffer::new(0),
+            false,
+            BufAttachOpts::default().on_lines(move |_| {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                // returning true detaches the callback
+                Ok::<Boolean, Error>(true)
+            }),
+        )
+        .unwrap();
+
+        paste(c"first", false, PastePhase::Single).unwrap();
+        paste(c"second", false, PastePhase::Single).unwrap();
+
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+
     #[nvim_test::nvim_test]
     fn buf_call() {
         let buf1 = create_buf(true, false).unwrap();
@@ -481,6 +516,22 @@ mod tests {
         feedkeys(c"ib", &mode, false);
     }
 
+    #[nvim_test::nvim_test]
+    fn buf_del_keymap() {
+        super::buf_set_keymap(
+            Buffer::new(0),
+            KeyMapMode::NORMAL,
+            c"b",
+            c"<Nop>",
+            &mut SetKeymapOpts::default(),
+        )
+        .unwrap();
+        super::buf_del_keymap(Buffer::new(0), KeyMapMode::NORMAL, c"b").unwrap();
+        let km = super::buf_get_keymap(Buffer::new(0), KeyMapMode::NORMAL).unwrap();
+        assert!(km.maps.is_empty());
+        super::buf_del_keymap(Buffer::new(0), KeyMapMode::NORMAL, c"b").unwrap_err();
+    }
+
     #[nvim_test::nvim_test]
     fn buf_line_count() {
         let count = super::buf_line_count(Buffer::new(0)).unwrap();
@@ -3,14 +3,17 @@ use thread_lock::call_check;
 use crate::{
     macros::tri::{tri_ez, tri_ret},
     nvim_funcs::c_funcs::command::{
-        nvim_buf_create_user_command, nvim_buf_del_user_command, nvim_buf_get_commands,
-        nvim_create_user_command, nvim_del_user_command, nvim_get_commands,
+        nvim_buf_create_user_command, nvim_buf_del_user_command, nvim_buf_get_commands, nvim_cmd,
+        nvim_create_user_command, nvim_del_user_command, nvim_get_commands, nvim_parse_cmd,
     },
     nvim_types::{
-        AsThinString, Buffer, Channel, Error, call_with_arena,
+        AsThinString, Buffer, Channel, Error, OwnedThinString, call_with_arena,
         func_types::create_user_command::UserCommand,
-        opts::{create_user_command::CreateUserCommandOpts, get_commands::GetCommandOpts},
-        returns::commands::CommandsInfos,
+        opts::{
+            cmd::Cmd, cmd_opts::CmdOpts, create_user_command::CreateUserCommandOpts,
+            get_commands::GetCommandOpts, parse_cmd::ParseCmdOpts,
+        },
+        returns::{cmd::CmdInfo, commands::CommandsInfos},
     },
 };
 
@@ -52,6 +55,26 @@ pub fn buf_get_commands(buf: Buffer, opts: &mut GetCommandOpts) -> Result<Comman
     }
 }
 
+/// Executes an Ex command
+///
+/// Unlike [`command`](crate::nvim_funcs::vimscript::command) the arguments are passed as is, so
+/// there is no need to escape special characters such as spaces in file names.
+///
+/// Returns the output of the command if [`CmdOpts::output`] is set, otherwise an empty string is
+/// returned.
+pub fn cmd(cmd: &Cmd, opts: &CmdOpts) -> Result<OwnedThinString, Error> {
+    call_check();
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_cmd(Channel::LUA_INTERNAL_CALL, cmd, opts, arena, &mut err);
+                (|s: &OwnedThinString| OwnedThinString::from(s.as_thinstr()));
+            }
+        })
+    }
+}
+
 pub fn create_user_command<TH: AsThinString>(
     name: TH,
     command: UserCommand<'_>,
@@ -85,6 +108,23 @@ pub fn get_commands(opts: &mut GetCommandOpts) -> Result<CommandsInfos, Error> {
     }
 }
 
+/// Parses a command line without executing it
+///
+/// The returned [`CmdInfo`] can be converted in to a [`Cmd`] to modify and execute it via
+/// [`cmd`].
+pub fn parse_cmd<TH: AsThinString>(s: TH) -> Result<CmdInfo, Error> {
+    call_check();
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_parse_cmd(s.as_thinstr(), &ParseCmdOpts::default(), arena, &mut err);
+                Cmd::to_info;
+            }
+        })
+    }
+}
+
 #[cfg(all(not(miri), feature = "testing"))]
 mod tests {
     use crate as nvimium;
@@ -99,18 +139,22 @@ mod tests {
             AsThinString, Buffer,
             func_types::create_user_command::UserCommand,
             opts::{
+                cmd::Cmd,
+                cmd_mods::{CmdMods, CmdSplit},
+                cmd_opts::CmdOpts,
                 create_user_command::{
                     CreateUserCommandOpts, UserCommandCompleteKind, UserCommandNarg,
                 },
                 exec::ExecOpts,
                 get_commands::GetCommandOpts,
             },
+            returns::cmd::CmdRange,
         },
     };
 
     use super::{
-        buf_create_user_command, buf_del_user_command, buf_get_commands, create_user_command,
-        del_user_command, get_commands,
+        buf_create_user_command, buf_del_user_command, buf_get_commands, cmd, create_user_command,
+        del_user_command, get_commands, parse_cmd,
     };
 
     #[nvim_test::nvim_test]
@@ -171,4 +215,56 @@ mod tests {
 
         del_user_command(c"MyCmd").unwrap();
     }
+
+    #[nvim_test::nvim_test]
+    fn parse_cmd_info() {
+        let info = parse_cmd(c"botright vertical 1,2yank a").unwrap();
+        assert_eq!(info.cmd, "yank");
+        assert_eq!(info.range, Some(CmdRange::Range(1, 2)));
+        assert_eq!(info.reg.as_ref().unwrap(), "a");
+        assert!(!info.bang);
+        assert!(info.mods.vertical);
+        assert_eq!(info.mods.split, Some(CmdSplit::BotRight));
+        assert_eq!(info.mods.tab, None);
+        assert!(info.mods.filter.is_none());
+
+        let info = parse_cmd(c"echo 'a' 'b'").unwrap();
+        assert_eq!(info.cmd, "echo");
+        assert_eq!(info.args.len(), 2);
+        assert_eq!(info.range, None);
+
+        parse_cmd(c"NotACommandNvimium").unwrap_err();
+    }
+
+    #[nvim_test::nvim_test]
+    fn cmd_output_and_args() {
+        let mut opts = CmdOpts::default();
+        opts.output(true);
+
+        let mut c = Cmd::default();
+        c.cmd(c"echo").args(&[c"'hello'", c"'world'"]);
+        assert_eq!(cmd(&c, &opts).unwrap(), "hello world");
+        assert_eq!(cmd(&c, &CmdOpts::default()).unwrap(), "");
+
+        // arguments are not split on whitespace
+        let mut c = Cmd::default();
+        c.cmd(c"file").args(&[c"name with spaces"]);
+        let mut mods = CmdMods::default();
+        mods.silent(true);
+        c.mods(mods);
+        cmd(&c, &CmdOpts::default()).unwrap();
+        let name = exec2(c"echo expand('%:t')", ExecOpts::default().output(true))
+            .unwrap()
+            .output
+            .unwrap();
+        assert_eq!(name, "name with spaces");
+    }
+
+    #[nvim_test::nvim_test]
+    fn parse_then_cmd() {
+        let info = parse_cmd(c"echo 'parsed'").unwrap();
+        let mut opts = CmdOpts::default();
+        opts.output(true);
+        assert_eq!(cmd(&Cmd::from(&info), &opts).unwrap(), "parsed");
+    }
 }
@@ -0,0 +1,397 @@
+use thread_lock::call_check;
+
+use crate::{
+    macros::tri::{tri_ez, tri_nc, tri_ret},
+    nvim_funcs::c_funcs::extmark::{
+        nvim_buf_clear_namespace, nvim_buf_del_extmark, nvim_buf_get_extmark_by_id,
+        nvim_buf_get_extmarks, nvim_buf_set_extmark, nvim_create_namespace, nvim_get_namespaces,
+        nvim_set_decoration_provider,
+    },
+    nvim_types::{
+        AsThinString, Boolean, Buffer, Error, HandleT, Integer, KVec, NameSpace, Object,
+        OwnedThinString, call_with_arena,
+        extmark::ExtMark,
+        opts::{
+            get_extmark::GetExtmarkOpts,
+            get_extmarks::{ExtmarkPos, GetExtmarksOpts},
+            set_decoration_provider::DecorationProviderOpts,
+            set_extmark::SetExtmarkOpts,
+        },
+        returns::extmark::ExtmarkInfo,
+    },
+};
+
+/// Clears extmarks and highlights of a namespace in the provided line range
+///
+/// Passing a [`NameSpace`] of `-1` clears all namespaces. To clear the whole buffer pass 0 and -1
+/// as the line range.
+pub fn buf_clear_namespace(
+    buf: Buffer,
+    ns: NameSpace,
+    line_start: Integer,
+    line_end: Integer,
+) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_buf_clear_namespace(buf, ns, line_start, line_end, &raw mut err) };
+    }
+}
+
+/// Removes an extmark
+///
+/// Returns true if the extmark was found.
+pub fn buf_del_extmark(buf: Buffer, ns: NameSpace, id: ExtMark) -> Result<Boolean, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_buf_del_extmark(buf, ns, id, &raw mut err) };
+    }
+}
+
+/// Gets the position and optionally the details of an extmark
+///
+/// Returns [`None`] if no extmark with the id exists in the namespace.
+pub fn buf_get_extmark_by_id(
+    buf: Buffer,
+    ns: NameSpace,
+    id: ExtMark,
+    opts: &GetExtmarkOpts,
+) -> Result<Option<ExtmarkInfo>, Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_buf_get_extmark_by_id(buf, ns, id, opts, arena, &raw mut err);
+                |arr| ExtmarkInfo::from_c_func_ret_by_id(id, arr);
+            }
+        })
+    }
+}
+
+/// Gets the extmarks in the range of `start` and `end` (inclusive)
+///
+/// If `end` is before `start` the extmarks are returned in reverse order.
+pub fn buf_get_extmarks(
+    buf: Buffer,
+    ns: NameSpace,
+    start: ExtmarkPos,
+    end: ExtmarkPos,
+    opts: &GetExtmarksOpts,
+) -> Result<KVec<ExtmarkInfo>, Error> {
+    call_check();
+
+    let start = Object::from(start);
+    let end = Object::from(end);
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_buf_get_extmarks(buf, ns, (&start).into(), (&end).into(), opts, arena, &raw mut err);
+                ExtmarkInfo::from_c_func_ret_list;
+            }
+        })
+    }
+}
+
+/// Creates or updates an extmark at the (0,0)-indexed position
+///
+/// Returns the id of the extmark.
+pub fn buf_set_extmark(
+    buf: Buffer,
+    ns: NameSpace,
+    line: Integer,
+    col: Integer,
+    opts: &SetExtmarkOpts,
+) -> Result<ExtMark, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_buf_set_extmark(buf, ns, line, col, opts, &raw mut err) };
+    }
+}
+
+/// Creates a new namespace or gets the existing one with the same name
+///
+/// An empty name creates an anonymous namespace.
+pub fn create_namespace<TH: AsThinString>(name: TH) -> NameSpace {
+    call_check();
+
+    unsafe { nvim_create_namespace(name.as_thinstr()) }
+}
+
+/// Gets all named namespaces
+pub fn get_namespaces() -> KVec<(OwnedThinString, NameSpace)> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            let namespaces = nvim_get_namespaces(arena);
+            let mut kv = KVec::with_capacity(namespaces.len());
+            kv.extend(namespaces.iter().map(|kv| {
+                let ns = kv.object.as_int().expect("found non integer namespace id");
+                (kv.key.clone(), NameSpace::new(ns as HandleT))
+            }));
+            kv
+        })
+    }
+}
+
+/// Sets or replaces the decoration provider of a namespace
+///
+/// The callbacks are invoked during redraw, where most of the API is not allowed to be called.
+/// Setting extmarks with [`SetExtmarkOpts::ephemeral`] is the intended way of adding decorations
+/// from the callbacks.
+///
+/// The callbacks are moved out of `opts` and are owned by Neovim afterwards.
+///
+/// [`SetExtmarkOpts::ephemeral`]: crate::nvim_types::opts::set_extmark::SetExtmarkOpts::ephemeral
+pub fn set_decoration_provider(
+    ns: NameSpace,
+    opts: &mut DecorationProviderOpts,
+) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_set_decoration_provider(ns, opts, &raw mut err) };
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use crate::{
+        self as nvimium,
+        nvim_funcs::{
+            buffer::buf_set_lines,
+            global::{create_buf, get_current_buf, get_hl_id_by_name},
+            vimscript::command,
+        },
+        nvim_types::{
+            Array, HlGroupId, Object,
+            extmark::ExtMark,
+            opts::{
+                get_extmark::GetExtmarkOpts,
+                get_extmarks::{ExtmarkPos, ExtmarkType, GetExtmarksOpts},
+                set_decoration_provider::DecorationProviderOpts,
+                set_extmark::{SetExtmarkOpts, VirtTextPos},
+            },
+        },
+    };
+
+    use super::*;
+
+    #[nvim_test::nvim_test]
+    fn namespaces() {
+        let ns = create_namespace(c"nvimium_ns");
+        assert_eq!(create_namespace(c"nvimium_ns"), ns);
+        let anon = create_namespace(c"");
+        assert_ne!(anon, ns);
+
+        let namespaces = get_namespaces();
+        assert!(
+            namespaces
+                .iter()
+                .any(|(name, id)| name == c"nvimium_ns" && *id == ns)
+        );
+    }
+
+    #[nvim_test::nvim_test]
+    fn set_get_del_extmark() {
+        let buf = create_buf(false, true).unwrap();
+        let lines = Array::from(
+            [
+                Object::from(c"first line"),
+                Object::from(c"second line"),
+                Object::from(c"third line"),
+            ]
+            .as_slice(),
+        );
+        buf_set_lines(buf, 0, -1, true, &lines).unwrap();
+        let ns = create_namespace(c"nvimium_extmarks");
+
+        let hl = HlGroupId::new(get_hl_id_by_name(c"Comment"));
+        let mut opts = SetExtmarkOpts::default();
+        opts.end_row(0)
+            .end_col(5)
+            .hl_group(hl.clone())
+            .priority(200);
+        let hl_mark = buf_set_extmark(buf, ns, 0, 0, &opts).unwrap();
+
+        let virt_text = Array::from(
+            [Object::Array(Array::from(
+                [Object::from(c"virtual"), Object::from(c"Comment")].as_slice(),
+            ))]
+            .as_slice(),
+        );
+        let mut opts = SetExtmarkOpts::default();
+        opts.virt_text(virt_text)
+            .virt_text_pos(VirtTextPos::Eol)
+            .sign_text(c"S");
+        let virt_mark = buf_set_extmark(buf, ns, 2, 1, &opts).unwrap();
+
+        let info = buf_get_extmark_by_id(buf, ns, hl_mark, &GetExtmarkOpts::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!((info.id, info.row, info.col), (hl_mark, 0, 0));
+        assert!(info.details.is_none());
+
+        let mut opts = GetExtmarkOpts::default();
+        opts.details(true);
+        let details = buf_get_extmark_by_id(buf, ns, hl_mark, &opts)
+            .unwrap()
+            .unwrap()
+            .details
+            .unwrap();
+        assert_eq!(details.ns_id, ns);
+        assert_eq!(details.priority, Some(200));
+        assert_eq!(details.end_row, Some(0));
+        assert_eq!(details.end_col, Some(5));
+
+        let marks = buf_get_extmarks(
+            buf,
+            ns,
+            ExtmarkPos::Start,
+            ExtmarkPos::End,
+            &GetExtmarksOpts::default(),
+        )
+        .unwrap();
+        assert_eq!(marks.len(), 2);
+        assert_eq!((marks[0].id, marks[0].row), (hl_mark, 0));
+        assert_eq!((marks[1].id, marks[1].row, marks[1].col), (virt_mark, 2, 1));
+
+        let mut opts = GetExtmarksOpts::default();
+        opts.kind(ExtmarkType::VirtText).details(true);
+        let marks = buf_get_extmarks(buf, ns, ExtmarkPos::Start, ExtmarkPos::End, &opts).unwrap();
+        assert_eq!(marks.len(), 1);
+        let details = marks[0].details.as_ref().unwrap();
+        assert_eq!(details.virt_text_pos, Some(VirtTextPos::Eol));
+        assert!(details.virt_text.is_some());
+
+        let mut opts = GetExtmarksOpts::default();
+        opts.limit(1);
+        let marks = buf_get_extmarks(buf, ns, ExtmarkPos::End, ExtmarkPos::Start, &opts).unwrap();
+        assert_eq!(marks.len(), 1);
+        assert_eq!(marks[0].id, virt_mark);
+
+        let marks = buf_get_extmarks(
+            buf,
+            ns,
+            ExtmarkPos::Pos(1, 0),
+            ExtmarkPos::Id(virt_mark),
+            &GetExtmarksOpts::default(),
+        )
+        .unwrap();
+        assert_eq!(marks.len(), 1);
+
+        assert!(buf_del_extmark(buf, ns, hl_mark).unwrap());
+        assert!(!buf_del_extmark(buf, ns, hl_mark).unwrap());
+        assert!(
+            buf_get_extmark_by_id(buf, ns, hl_mark, &GetExtmarkOpts::default())
+                .unwrap()
+                .is_none()
+        );
+
+        buf_clear_namespace(buf, ns, 0, -1).unwrap();
+        assert!(
+            buf_get_extmark_by_id(buf, ns, virt_mark, &GetExtmarkOpts::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[nvim_test::nvim_test]
+    fn set_extmark_with_id() {
+        let buf = create_buf(false, true).unwrap();
+        let ns = create_namespace(c"nvimium_extmark_id");
+        let mut opts = SetExtmarkOpts::default();
+        opts.id(ExtMark::new(42));
+        assert_eq!(
+            buf_set_extmark(buf, ns, 0, 0, &opts).unwrap(),
+            ExtMark::new(42)
+        );
+
+        // out of range positions error when strict is set (the default)
+        buf_set_extmark(buf, ns, 10, 0, &SetExtmarkOpts::default()).unwrap_err();
+    }
+
+    #[nvim_test::nvim_test]
+    fn decoration_provider() {
+        static STARTS: AtomicUsize = AtomicUsize::new(0);
+        static WINS: AtomicUsize = AtomicUsize::new(0);
+        static LINES: AtomicUsize = AtomicUsize::new(0);
+        static ENDS: AtomicUsize = AtomicUsize::new(0);
+        static SKIP_WIN: AtomicBool = AtomicBool::new(false);
+
+        let buf = get_current_buf();
+        let lines = Array::from(
+            [
+                Object::from(c"first line"),
+                Object::from(c"second line"),
+                Object::from(c"third line"),
+            ]
+            .as_slice(),
+        );
+        buf_set_lines(buf, 0, -1, true, &lines).unwrap();
+        let ns = create_namespace(c"nvimium_decor");
+
+        let mut opts = DecorationProviderOpts::default();
+        opts.on_start(|args| {
+            assert_eq!(args.event, "start");
+            STARTS.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, Error>(true)
+        })
+        .on_win(move |args| {
+            assert_eq!(args.event, "win");
+            assert_eq!(args.buf, buf);
+            WINS.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, Error>(!SKIP_WIN.load(Ordering::Relaxed))
+        })
+        .on_line(move |args| {
+            assert_eq!(args.event, "line");
+            let mut opts = SetExtmarkOpts::default();
+            opts.ephemeral(true).end_col(1).hl_group_name(c"Comment");
+            buf_set_extmark(args.buf, ns, args.row, 0, &opts)?;
+            LINES.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, Error>(true)
+        })
+        .on_end(|args| {
+            assert_eq!(args.event, "end");
+            ENDS.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, Error>(())
+        });
+        set_decoration_provider(ns, &mut opts).unwrap();
+
+        command(c"redraw!").unwrap();
+        assert!(STARTS.load(Ordering::Relaxed) > 0);
+        assert!(WINS.load(Ordering::Relaxed) > 0);
+        assert!(LINES.load(Ordering::Relaxed) >= 3);
+        assert!(ENDS.load(Ordering::Relaxed) > 0);
+
+        // ephemeral extmarks only live for the duration of the redraw
+        let marks = buf_get_extmarks(
+            buf,
+            ns,
+            ExtmarkPos::Start,
+            ExtmarkPos::End,
+            &GetExtmarksOpts::default(),
+        )
+        .unwrap();
+        assert!(marks.is_empty());
+
+        // returning false from on_win skips the lines of the window
+        SKIP_WIN.store(true, Ordering::Relaxed);
+        LINES.store(0, Ordering::Relaxed);
+        command(c"redraw!").unwrap();
+        assert_eq!(LINES.load(Ordering::Relaxed), 0);
+
+        set_decoration_provider(ns, &mut DecorationProviderOpts::default()).unwrap();
+    }
+}
@@ -886,6 +886,286 @@ mod tests {
         assert_eq!(super::list_bufs().len(), 2);
     }
 
+    #[nvim_test::nvim_test]
+    pub fn exec_lua_callback_returns() {
+        use crate::nvim_types::{Boolean, Error, Integer, lua::Function};
+
+        let f = Function::wrap(|()| {
+            Ok::<_, Error>((
+                12 as Integer,
+                true as Boolean,
+                OwnedThinString::from("three"),
+            ))
+        });
+        let res = super::exec_lua(
+            c"local a, b, c = (...)()\nreturn { a, b, c }",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            Object::Array(Array::from(
+                [
+                    Object::Integer(12),
+                    Object::Bool(true),
+                    Object::String(OwnedThinString::from("three")),
+                ]
+                .as_slice()
+            ))
+        );
+
+        let f = Function::wrap(|()| {
+            Ok::<_, Error>(Array::from(
+                [Object::Integer(1), Object::Integer(2)].as_slice(),
+            ))
+        });
+        let res = super::exec_lua(
+            c"return #(...)()",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Integer(2));
+    }
+
+    #[nvim_test::nvim_test]
+    pub fn exec_lua_module() {
+        use crate::nvim_types::{Error, Integer, lua::LuaModule};
+
+        let f = crate::nvim_types::lua::Function::wrap(|()| {
+            let mut utils = LuaModule::new();
+            utils.function(c"double", |i: Integer| Ok::<_, Error>(i * 2));
+            let mut module = LuaModule::new();
+            module
+                .constant(c"name", c"nvimium")
+                .constant(c"answer", 42 as Integer)
+                .module(c"utils", utils);
+            Ok::<_, Error>(module)
+        });
+        let res = super::exec_lua(
+            c"local m = (...)()\nreturn { m.name, m.answer, m.utils.double(4) }",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            Object::Array(Array::from(
+                [
+                    Object::String(OwnedThinString::from("nvimium")),
+                    Object::Integer(42),
+                    Object::Integer(8),
+                ]
+                .as_slice()
+            ))
+        );
+    }
+
+    #[nvim_test::nvim_test]
+    pub fn function_call() {
+        use crate::nvim_types::{
+            Boolean, Integer,
+            lua::{CallError, Function, core::FromLuaErr},
+        };
+
+        let f = Function::wrap(|obj: Object| {
+            let Object::LuaRef(lref) = obj else {
+                panic!("expected a function");
+            };
+            Function::from_luaref(lref).call::<_, Integer>((1 as Integer, 2 as Integer))
+        });
+        let res = super::exec_lua(
+            c"return (...)(function(a, b) return a + b end)",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Integer(3));
+
+        let f = Function::wrap(|obj: Object| {
+            let Object::LuaRef(lref) = obj else {
+                panic!("expected a function");
+            };
+            let err = Function::from_luaref(lref).call::<_, ()>(()).unwrap_err();
+            Ok::<_, CallError>(matches!(
+                err,
+                CallError::Runtime { message, traceback }
+                    if message.contains("boom") && traceback.starts_with("stack traceback:")
+            ) as Boolean)
+        });
+        let res = super::exec_lua(
+            c"return (...)(function() error('boom') end)",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Bool(true));
+
+        // a function without return values does not read the values below its frame
+        let f = Function::wrap(|obj: Object| {
+            let Object::LuaRef(lref) = obj else {
+                panic!("expected a function");
+            };
+            let f = Function::from_luaref(lref);
+            let missing = matches!(
+                f.call::<_, Object>(()),
+                Err(CallError::Conversion(FromLuaErr::NotFound))
+            );
+            let none = matches!(f.call::<_, Option<Object>>(()), Ok(None));
+            Ok::<_, CallError>((missing && none) as Boolean)
+        });
+        let res = super::exec_lua(
+            c"return (...)(function() end)",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Bool(true));
+    }
+
+    #[nvim_test::nvim_test]
+    pub fn userdata() {
+        use crate::nvim_types::{
+            Error, Integer,
+            lua::{Function, LuaUserData, UserData, UserDataMethods},
+        };
+
+        struct Counter(Integer);
+
+        impl LuaUserData for Counter {
+            fn register(methods: &mut UserDataMethods<Self>) {
+                methods
+                    .method(c"get", |c: &Counter, ()| Ok::<_, Error>(c.0))
+                    .method_mut(c"add", |c: &mut Counter, n: Integer| {
+                        c.0 += n;
+                        Ok::<_, Error>(())
+                    })
+                    .index(|c: &Counter, key: OwnedThinString| {
+                        if key.as_thinstr() == c"double".as_thinstr() {
+                            Ok::<_, Error>(Object::Integer(c.0 * 2))
+                        } else {
+                            Ok(Object::Null)
+                        }
+                    })
+                    .newindex(|c: &mut Counter, _: OwnedThinString, n: Integer| {
+                        c.0 = n;
+                        Ok::<_, Error>(())
+                    })
+                    .tostring(|c: &Counter| {
+                        Ok::<_, Error>(OwnedThinString::from(format!("Counter({})", c.0).as_str()))
+                    })
+                    .len(|c: &Counter| Ok::<_, Error>(c.0))
+                    .eq(|a: &Counter, b: &Counter| a.0 == b.0);
+            }
+        }
+
+        let counter = UserData::new(Counter(1));
+        let new = Function::wrap(|n: Integer| Ok::<_, Error>(UserData::new(Counter(n))));
+        let res = super::exec_lua(
+            c"local c, new = ...
+            c:add(2)
+            local before = c:get()
+            c.value = 10
+            return { before, c:get(), c.double, tostring(c), #c, c == new(10), c == new(1) }",
+            &Array::from(
+                [
+                    Object::LuaRef(counter.clone().into_luaref()),
+                    Object::LuaRef(new.into_luaref()),
+                ]
+                .as_slice(),
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            Object::Array(Array::from(
+                [
+                    Object::Integer(3),
+                    Object::Integer(10),
+                    Object::Integer(20),
+                    Object::String(OwnedThinString::from("Counter(10)")),
+                    Object::Integer(10),
+                    Object::Bool(true),
+                    Object::Bool(false),
+                ]
+                .as_slice()
+            ))
+        );
+        assert_eq!(counter.borrow().0, 10);
+
+        // the value is already borrowed so the method must not run
+        let _borrow = counter.borrow_mut();
+        let res = super::exec_lua(
+            c"local c = ...\nc:add(1)\nreturn true",
+            &Array::from([Object::LuaRef(counter.clone().into_luaref())].as_slice()),
+        );
+        drop(_borrow);
+        assert!(res.is_ok());
+        assert_eq!(counter.borrow().0, 10);
+    }
+
+    #[nvim_test::nvim_test]
+    pub fn exec_lua_callback_args() {
+        use crate::nvim_types::{
+            Dict, Error, Integer,
+            lua::{Function, core::FromLuaErr},
+        };
+
+        let f = Function::wrap(|(i, d, a): (Integer, Dict, Array)| {
+            let nested = d.get("nested").and_then(Object::as_array).unwrap();
+            Ok::<_, Error>(i + d.len() as Integer + a.len() as Integer + nested.len() as Integer)
+        });
+        let res = super::exec_lua(
+            c"local shared = { 1, 2 }\nreturn (...)(1, { nested = shared, other = shared }, {})",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Integer(5));
+
+        // the conversion error is reported instead of calling the function
+        let f = Function::wrap(|(_, _): (Integer, Object)| -> Result<Object, Error> {
+            panic!("called with a cyclic table")
+        });
+        let res = super::exec_lua(
+            c"local t = { 1 }\nt[2] = t\nreturn (...)(1, t)",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Null);
+        let mut opts = ExecOpts::default();
+        let messages = exec2(c":messages", opts.output(true))
+            .unwrap()
+            .output
+            .unwrap();
+        assert_eq!(
+            messages,
+            OwnedThinString::from(format!("Error: {}", FromLuaErr::CyclicTable.at_arg(2)).as_str())
+        );
+
+        // missing arguments are only accepted when read as an option
+        let f = Function::wrap(|(i, o): (Integer, Option<Object>)| {
+            Ok::<_, Error>(i + o.is_none() as Integer)
+        });
+        let res = super::exec_lua(
+            c"return (...)(1)",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Integer(2));
+        let f = Function::wrap(|(_, _): (Integer, Object)| -> Result<Object, Error> {
+            panic!("called without its second argument")
+        });
+        let res = super::exec_lua(
+            c"return (...)(1)",
+            &Array::from([Object::LuaRef(f.into_luaref())].as_slice()),
+        )
+        .unwrap();
+        assert_eq!(res, Object::Null);
+        let messages = exec2(c":messages", opts.output(true))
+            .unwrap()
+            .output
+            .unwrap();
+        assert_eq!(
+            messages.as_thinstr().to_str_lossy().lines().last(),
+            Some(format!("Error: {}", FromLuaErr::NotFound.at_arg(2)).as_str())
+        );
+    }
+
     #[nvim_test::nvim_test(no_exit)]
     pub fn nvim_feedkeys() {
         // this test is kind of a hack
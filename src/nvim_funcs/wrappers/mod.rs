@@ -1,7 +1,10 @@
+pub mod autocmd;
 pub mod buffer;
 pub mod command;
+pub mod extmark;
 pub mod global;
 pub mod options;
 pub mod vimscript;
 pub mod tabpage;
 pub mod win_config;
+pub mod window;
@@ -2,15 +2,15 @@ use crate::macros::tri::{tri_ez, tri_ret};
 use crate::nvim_types::Arena;
 use crate::nvim_types::object::ObjectRef;
 use crate::nvim_types::returns::exec2::Exec2;
+use crate::nvim_types::returns::parse_expression::ParsedExpr;
 use crate::nvim_types::{
-    Array, AsThinString, Boolean, Channel, Dict, Error, Object, opts::exec::ExecOpts,
+    Array, AsThinString, Boolean, Channel, Dict, Error, Object, call_with_arena,
+    opts::exec::ExecOpts,
 };
 use thread_lock::call_check;
 
 use crate::nvim_funcs::c_funcs::vimscript;
 
-use crate::tri;
-
 // TODO: add test
 pub fn call_dict_function<S1: AsThinString, S2: AsThinString>(
     dict: S1,
@@ -87,22 +87,31 @@ pub fn exec2<S: AsThinString>(exec: S, opts: &ExecOpts) -> Result<Exec2, Error>
     Ok(Exec2::from_c_func_ret(&mut ret))
 }
 
-// TODO: create proper structs and what not.
-// good amount of work to be done
-fn parse_expression<S: AsThinString, S1: AsThinString>(
-    eval: S,
+/// Parses a Vimscript expression without evaluating it
+///
+/// `flags` is a combination of the following characters:
+/// - `m`: parse multiple expressions, without it only the first expression is parsed.
+/// - `E`: do not allow `<>` (`<SID>`, `<C-R>` etc.) in expressions.
+/// - `l`: allow parsing lvalues such as the left side of `:let`.
+///
+/// If `highlight` is true the highlight chunks of the expression are also returned.
+pub fn parse_expression<S: AsThinString, S1: AsThinString>(
+    expr: S,
     flags: S1,
     highlight: Boolean,
-) -> Result<Dict, Error> {
-    // TODO: likely a memory leak, replace with dedicated struct
-    // it seems that the returned Dict contains a mix of owned and static strings which makes this
-    // pretty hard to expose to users
-    //
-    // this is fine for now but should eventually use a dedicated struct in the return type
-    tri! {
-        let mut err;
-        unsafe { vimscript::nvim_parse_expression(eval.as_thinstr(), flags.as_thinstr(), highlight, core::ptr::null_mut(), &mut err) },
-        Ok(d) => Ok(unsafe { d.assume_init_ref() }.clone())
+) -> Result<ParsedExpr, Error> {
+    call_check();
+
+    // the returned Dict contains a mix of static and arena allocated strings, the result is
+    // copied out and the Dict itself is never dropped
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                vimscript::nvim_parse_expression(expr.as_thinstr(), flags.as_thinstr(), highlight, arena, &mut err);
+                (|d: &mut Dict| ParsedExpr::from_c_func_ret(d));
+            }
+        })
     }
 }
 
@@ -173,4 +182,32 @@ mod tests {
         let expr = c"echo  123";
         super::parse_expression(expr, c"", false).unwrap();
     }
+
+    #[nvim_test::nvim_test]
+    pub fn parse_expression_ast() {
+        use crate::nvim_types::returns::parse_expression::ExprNodeKind;
+
+        let parsed = super::parse_expression(c"1 + g:var", c"", true).unwrap();
+        assert!(parsed.error.is_none());
+        assert_eq!(parsed.len, 9);
+        assert!(!parsed.highlight.is_empty());
+
+        let ast = parsed.ast.unwrap();
+        assert_eq!(ast.kind, ExprNodeKind::BinaryPlus);
+        assert_eq!(ast.children.len(), 2);
+        assert_eq!(ast.children[0].kind, ExprNodeKind::Integer { value: 1 });
+        assert_eq!(ast.children[0].start, (0, 0));
+        assert_eq!(ast.children[0].len, 1);
+        assert_eq!(
+            ast.children[1].kind,
+            ExprNodeKind::PlainIdentifier {
+                scope: b'g' as i64,
+                ident: OwnedThinString::from("var")
+            }
+        );
+
+        let parsed = super::parse_expression(c"1 +", c"", false).unwrap();
+        assert!(parsed.error.is_some());
+        assert!(parsed.highlight.is_empty());
+    }
 }
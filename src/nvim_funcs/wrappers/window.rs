@@ -0,0 +1,440 @@
+use thread_lock::call_check;
+
+use crate::{
+    macros::tri::{tri_ez, tri_nc, tri_ret},
+    nvim_funcs::c_funcs::window::{
+        nvim_win_call, nvim_win_close, nvim_win_del_var, nvim_win_get_buf, nvim_win_get_config,
+        nvim_win_get_cursor, nvim_win_get_height, nvim_win_get_number, nvim_win_get_position,
+        nvim_win_get_tabpage, nvim_win_get_var, nvim_win_get_width, nvim_win_hide,
+        nvim_win_is_valid, nvim_win_set_buf, nvim_win_set_config, nvim_win_set_cursor,
+        nvim_win_set_height, nvim_win_set_hl_ns, nvim_win_set_var, nvim_win_set_width,
+        nvim_win_text_height,
+    },
+    nvim_types::{
+        Array, AsThinString, Boolean, Buffer, Error, Integer, IntoLua, NameSpace, Object, TabPage,
+        Window, call_with_arena,
+        lua::{Function, NvFn},
+        opts::{win_opts::WinConfig, win_text_height::WinTextHeightOpts},
+        returns::{
+            utils::ArrayOf,
+            win_config::{TextHeight, WinConfigInfo},
+        },
+    },
+};
+
+fn pair(arr: &mut ArrayOf<Integer>) -> (Integer, Integer) {
+    let kv = arr.conv_to_kvec();
+    (kv[0], kv[1])
+}
+
+/// Calls a function with the provided window temporarily set as the current window
+pub fn win_call<
+    Err: 'static + std::error::Error,
+    Ret: 'static + IntoLua,
+    F: NvFn + Fn(()) -> Result<Ret, Err>,
+>(
+    win: Window,
+    f: F,
+) -> Result<Object, Error> {
+    call_check();
+
+    unsafe {
+        tri_nc! {
+            err;
+            nvim_win_call(win, Function::wrap(f).into_luaref(), &raw mut err);
+        }
+    }
+}
+
+/// Closes the window
+///
+/// If `force` is true, the window is closed even if the buffer has unsaved changes (the buffer
+/// becomes hidden).
+pub fn win_close(win: Window, force: Boolean) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_close(win, force, &raw mut err) };
+    }
+}
+
+pub fn win_del_var<TH: AsThinString>(win: Window, name: TH) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_del_var(win, name.as_thinstr(), &raw mut err) };
+    }
+}
+
+pub fn win_get_buf(win: Window) -> Result<Buffer, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_win_get_buf(win, &raw mut err) };
+    }
+}
+
+/// Gets the configuration of a window
+///
+/// The returned value can be converted in to a [`WinConfig`] and passed to [`win_set_config`].
+pub fn win_get_config(win: Window) -> Result<WinConfigInfo, Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_win_get_config(win, arena, &raw mut err);
+                WinConfig::to_info;
+            }
+        })
+    }
+}
+
+/// Gets the (1,0)-indexed cursor position of the window
+pub fn win_get_cursor(win: Window) -> Result<(Integer, Integer), Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_win_get_cursor(win, arena, &raw mut err);
+                pair;
+            }
+        })
+    }
+}
+
+pub fn win_get_height(win: Window) -> Result<Integer, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_win_get_height(win, &raw mut err) };
+    }
+}
+
+/// Gets the window number as seen by `winnr()`
+pub fn win_get_number(win: Window) -> Result<Integer, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_win_get_number(win, &raw mut err) };
+    }
+}
+
+/// Gets the (0,0)-indexed position of the window in display cells
+pub fn win_get_position(win: Window) -> Result<(Integer, Integer), Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_win_get_position(win, arena, &raw mut err);
+                pair;
+            }
+        })
+    }
+}
+
+pub fn win_get_tabpage(win: Window) -> Result<TabPage, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_win_get_tabpage(win, &raw mut err) };
+    }
+}
+
+pub fn win_get_var<TH: AsThinString>(win: Window, name: TH) -> Result<Object, Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_win_get_var(win, name.as_thinstr(), arena, &raw mut err);
+                Object::clone;
+            }
+        })
+    }
+}
+
+pub fn win_get_width(win: Window) -> Result<Integer, Error> {
+    call_check();
+
+    tri_nc! {
+        err;
+        unsafe { nvim_win_get_width(win, &raw mut err) };
+    }
+}
+
+/// Closes the window and hides the buffer it contains
+pub fn win_hide(win: Window) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_hide(win, &raw mut err) };
+    }
+}
+
+pub fn win_is_valid(win: Window) -> Boolean {
+    call_check();
+
+    unsafe { nvim_win_is_valid(win) }
+}
+
+pub fn win_set_buf(win: Window, buf: Buffer) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_buf(win, buf, &raw mut err) };
+    }
+}
+
+pub fn win_set_config(win: Window, config: &WinConfig) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_config(win, config, &raw mut err) };
+    }
+}
+
+/// Sets the (1,0)-indexed cursor position of the window
+pub fn win_set_cursor(win: Window, pos: (Integer, Integer)) -> Result<(), Error> {
+    call_check();
+
+    let pos = Array::from([Object::Integer(pos.0), Object::Integer(pos.1)].as_slice());
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_cursor(win, (&pos).into(), &raw mut err) };
+    }
+}
+
+pub fn win_set_height(win: Window, height: Integer) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_height(win, height, &raw mut err) };
+    }
+}
+
+/// Sets the highlight namespace for the window
+pub fn win_set_hl_ns(win: Window, ns_id: NameSpace) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_hl_ns(win, ns_id.as_int().into(), &raw mut err) };
+    }
+}
+
+pub fn win_set_var<TH: AsThinString>(win: Window, name: TH, value: &Object) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_var(win, name.as_thinstr(), value.into(), &raw mut err) };
+    }
+}
+
+pub fn win_set_width(win: Window, width: Integer) -> Result<(), Error> {
+    call_check();
+
+    tri_ez! {
+        err;
+        unsafe { nvim_win_set_width(win, width, &raw mut err) };
+    }
+}
+
+/// Computes the number of screen lines occupied by a range of text in the window
+pub fn win_text_height(win: Window, opts: &WinTextHeightOpts) -> Result<TextHeight, Error> {
+    call_check();
+
+    unsafe {
+        call_with_arena(|arena| {
+            tri_ret! {
+                err;
+                nvim_win_text_height(win, opts, arena, &raw mut err);
+                TextHeight::from_c_func_ret;
+            }
+        })
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use crate::{
+        self as nvimium,
+        nvim_funcs::{
+            buffer::buf_set_lines,
+            global::{create_buf, get_current_buf, get_current_tabpage, get_current_win},
+            win_config::open_win,
+        },
+        nvim_types::{
+            Array, Error, Integer, Object, OwnedThinString, Window,
+            opts::{
+                win_opts::{Anchor, Border, Relative, WinConfig},
+                win_text_height::WinTextHeightOpts,
+            },
+        },
+    };
+
+    use super::*;
+
+    // only called from within the test bodies which are not compiled in to the test binary
+    #[allow(dead_code)]
+    fn float_config() -> WinConfig<'static> {
+        let mut config = WinConfig::default();
+        config
+            .anchor(Anchor::NorthWest)
+            .border(Border::Single)
+            .title(c"my title")
+            .row(2.)
+            .col(6.)
+            .width(30)
+            .height(10)
+            .focusable(true)
+            .relative(Relative::Editor);
+        config
+    }
+
+    #[nvim_test::nvim_test]
+    fn set_get_win_value() {
+        let win = Window::new(0);
+        win_get_var(win, c"super_secret_value").unwrap_err();
+
+        win_set_var(
+            win,
+            c"super_secret_value",
+            &Object::String(OwnedThinString::from(c"important string")),
+        )
+        .unwrap();
+
+        let val = win_get_var(win, c"super_secret_value").unwrap();
+        assert_eq!(
+            val,
+            Object::String(OwnedThinString::from(c"important string"))
+        );
+
+        win_del_var(win, c"super_secret_value").unwrap();
+        win_get_var(win, c"super_secret_value").unwrap_err();
+    }
+
+    #[nvim_test::nvim_test]
+    fn cursor() {
+        let buf = get_current_buf();
+        let lines = Array::from(
+            [
+                Object::from(c"line 1"),
+                Object::from(c"line 2"),
+                Object::from(c"line 3"),
+            ]
+            .as_slice(),
+        );
+        buf_set_lines(buf, 0, -1, true, &lines).unwrap();
+        let win = get_current_win();
+        assert_eq!(win_get_cursor(win).unwrap(), (1, 0));
+        win_set_cursor(win, (3, 2)).unwrap();
+        assert_eq!(win_get_cursor(win).unwrap(), (3, 2));
+        win_set_cursor(win, (10, 0)).unwrap_err();
+    }
+
+    #[nvim_test::nvim_test]
+    fn buf_and_tabpage() {
+        let win = get_current_win();
+        assert_eq!(win_get_tabpage(win).unwrap(), get_current_tabpage());
+        assert_eq!(win_get_number(win).unwrap(), 1);
+
+        let buf = create_buf(true, true).unwrap();
+        win_set_buf(win, buf).unwrap();
+        assert_eq!(win_get_buf(win).unwrap(), buf);
+    }
+
+    #[nvim_test::nvim_test]
+    fn dimensions() {
+        let buf = create_buf(false, true).unwrap();
+        let win = open_win(buf, false, &float_config()).unwrap();
+        assert_eq!(win_get_width(win).unwrap(), 30);
+        assert_eq!(win_get_height(win).unwrap(), 10);
+
+        win_set_width(win, 20).unwrap();
+        win_set_height(win, 5).unwrap();
+        assert_eq!(win_get_width(win).unwrap(), 20);
+        assert_eq!(win_get_height(win).unwrap(), 5);
+
+        win_get_position(win).unwrap();
+
+        let height = win_text_height(win, &WinTextHeightOpts::default()).unwrap();
+        assert_eq!(height.all, 1);
+        assert_eq!(height.fill, 0);
+    }
+
+    #[nvim_test::nvim_test]
+    fn close_and_hide() {
+        let buf = create_buf(false, true).unwrap();
+        let win = open_win(buf, false, &float_config()).unwrap();
+        assert!(win_is_valid(win));
+        win_close(win, true).unwrap();
+        assert!(!win_is_valid(win));
+
+        let win = open_win(buf, false, &float_config()).unwrap();
+        win_hide(win).unwrap();
+        assert!(!win_is_valid(win));
+    }
+
+    #[nvim_test::nvim_test]
+    fn config_round_trip() {
+        let buf = create_buf(false, true).unwrap();
+        let win = open_win(buf, false, &float_config()).unwrap();
+
+        let info = win_get_config(win).unwrap();
+        assert!(matches!(info.relative, Some(Relative::Editor)));
+        assert!(matches!(info.anchor, Some(Anchor::NorthWest)));
+        assert_eq!(info.width, Some(30));
+        assert_eq!(info.height, Some(10));
+        assert_eq!(info.row, Some(2.));
+        assert_eq!(info.col, Some(6.));
+        assert_eq!(info.focusable, Some(true));
+        assert!(info.border.as_ref().is_some_and(|b| b.len() == 8));
+
+        let mut config = WinConfig::from(&info);
+        config.width(40);
+        win_set_config(win, &config).unwrap();
+
+        let new_info = win_get_config(win).unwrap();
+        assert_eq!(new_info.width, Some(40));
+        assert_eq!(new_info.height, info.height);
+        assert_eq!(new_info.border, info.border);
+        assert_eq!(new_info.title, info.title);
+
+        // split windows report an empty relative value
+        let info = win_get_config(get_current_win()).unwrap();
+        assert!(info.relative.is_none());
+        assert!(info.split.is_some());
+    }
+
+    #[nvim_test::nvim_test]
+    fn call_in_window() {
+        let buf = create_buf(false, true).unwrap();
+        let float = open_win(buf, false, &float_config()).unwrap();
+        let cur = get_current_win();
+        let ret = win_call(float, move |_: ()| {
+            assert_eq!(get_current_win(), float);
+            Ok::<Integer, Error>(1)
+        })
+        .unwrap();
+        assert_eq!(ret, Object::Integer(1));
+        assert_eq!(get_current_win(), cur);
+    }
+}
@@ -0,0 +1,69 @@
+use mlua_sys::{LUA_TTABLE, lua_checkstack, lua_getfield, lua_pop, lua_type};
+
+use crate::nvim_types::{
+    Buffer, HandleT, Integer, Object, ThinString,
+    lua::{
+        core::{FromLuaErr, FromLuaMany, object::read_object},
+        utils::{get_table_int_val, get_table_str_val},
+    },
+};
+
+/// The arguments passed to an autocommand callback
+pub struct AutocmdArgs<'a> {
+    /// The id of the autocommand
+    pub id: Integer,
+    /// The name of the triggered event
+    pub event: ThinString<'a>,
+    /// The id of the autocommand group if any
+    pub group: Option<Integer>,
+    /// The expanded value of `<amatch>`
+    pub r#match: ThinString<'a>,
+    /// The expanded value of `<abuf>`
+    pub buf: Buffer,
+    /// The expanded value of `<afile>`
+    pub file: ThinString<'a>,
+    /// The data passed through `exec_autocmds`, [`Object::Null`] if none was provided
+    pub data: Object,
+}
+
+impl<'a> FromLuaMany for AutocmdArgs<'a> {
+    unsafe fn get(
+        l: *mut mlua_sys::lua_State,
+        to_pop: &mut i32,
+    ) -> crate::nvim_types::lua::core::Result<Self> {
+        *to_pop += 1;
+        unsafe {
+            if lua_checkstack(l, 2) == 0 {
+                return Err(FromLuaErr::NotEnoughStackSpace);
+            }
+
+            if LUA_TTABLE != lua_type(l, -1) {
+                return Err(FromLuaErr::IncorrectType);
+            }
+
+            let id = get_table_int_val(l, -1, c"id")? as Integer;
+            let event = get_table_str_val(l, -1, c"event")?;
+            let group = get_table_int_val(l, -1, c"group")
+                .ok()
+                .map(|g| g as Integer);
+            let r#match = get_table_str_val(l, -1, c"match")?;
+            let buf = get_table_int_val(l, -1, c"buf")?;
+            let buf = Buffer::new(HandleT::try_from(buf).map_err(|_| FromLuaErr::IncorrectType)?);
+            let file = get_table_str_val(l, -1, c"file")?;
+
+            lua_getfield(l, -1, c"data".as_ptr());
+            let data = read_object(l, -1);
+            lua_pop(l, 1);
+
+            Ok(Self {
+                id,
+                event,
+                group,
+                r#match,
+                buf,
+                file,
+                data: data?,
+            })
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use libc::c_int;
+use mlua_sys::lua_gettop;
+
+use crate::{
+    macros::utils::count_tts,
+    nvim_types::{
+        Buffer, Integer, ThinString,
+        lua::core::{FromLua, FromLuaErr},
+        window::Window,
+    },
+};
+
+/// Defines the arguments of a decoration provider callback
+///
+/// Every callback receives the event name followed by the listed fields in order.
+macro_rules! decor_args {
+    ($(#[$attr:meta])* $name:ident { $($field:ident: $field_ty:ty),+ $(,)? }) => {
+        $(#[$attr])*
+        pub struct $name<'a> {
+            pub event: ThinString<'a>,
+            $(pub $field: $field_ty,)+
+        }
+
+        impl<'a> crate::nvim_types::lua::core::FromLuaMany for $name<'a> {
+            unsafe fn get(
+                l: *mut mlua_sys::lua_State,
+                to_pop: &mut i32,
+            ) -> crate::nvim_types::lua::core::Result<Self> {
+                const ARG_COUNT: c_int = 1 + count_tts!($($field),+);
+                unsafe {
+                    if lua_gettop(l) != ARG_COUNT {
+                        return Err(FromLuaErr::NotFound);
+                    }
+
+                    let event = ThinString::get(l, 1, to_pop)?;
+                    let mut index = 1;
+                    $(
+                        index += 1;
+                        let $field = <$field_ty as FromLua>::get(l, index, to_pop)?;
+                    )+
+
+                    Ok(Self { event, $($field),+ })
+                }
+            }
+        }
+    };
+}
+
+decor_args!(
+    /// Arguments passed to the `on_start` callback of a decoration provider
+    ///
+    /// Called once at the start of a redraw cycle.
+    DecorOnStartArgs { tick: Integer }
+);
+
+decor_args!(
+    /// Arguments passed to the `on_buf` callback of a decoration provider
+    ///
+    /// Called for each buffer being redrawn.
+    DecorOnBufArgs {
+        buf: Buffer,
+        tick: Integer,
+    }
+);
+
+decor_args!(
+    /// Arguments passed to the `on_win` callback of a decoration provider
+    ///
+    /// Called for each window being redrawn. `toprow` and `botrow` are zero based and inclusive,
+    /// but `botrow` is only an estimate of the last visible line.
+    DecorOnWinArgs {
+        win: Window,
+        buf: Buffer,
+        toprow: Integer,
+        botrow: Integer,
+    }
+);
+
+decor_args!(
+    /// Arguments passed to the `on_line` callback of a decoration provider
+    ///
+    /// Called for each buffer line being redrawn. `row` is zero based.
+    DecorOnLineArgs {
+        win: Window,
+        buf: Buffer,
+        row: Integer,
+    }
+);
+
+decor_args!(
+    /// Arguments passed to the `on_end` callback of a decoration provider
+    ///
+    /// Called once at the end of a redraw cycle.
+    DecorOnEndArgs { tick: Integer }
+);
@@ -1,4 +1,6 @@
+pub mod autocmd;
 pub mod buf_attach_cb;
+pub mod decoration_provider;
 pub mod open_term_cb;
 pub mod user_command;
 pub mod user_command_complete_cb;
@@ -533,6 +533,12 @@ impl<'a> ObjectRef<'a> {
         }
     }
 
+    /// Returns the value as an [`Object`] without taking ownership of it
+    pub(crate) fn as_object(&self) -> &Object {
+        // Object has the same alignment and size as ObjectRef
+        unsafe { transmute(self) }
+    }
+
     pub(crate) const fn copied(&self) -> Self {
         let mut s = MaybeUninit::uninit();
         unsafe { core::ptr::copy_nonoverlapping(self, &raw mut s as *mut Self, 1) };
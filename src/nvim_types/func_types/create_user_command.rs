@@ -36,3 +36,10 @@ impl<'a> Drop for UserCommand<'a> {
         }
     }
 }
+
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for UserCommand<'_> {
+    fn to_object(&self) -> Result<crate::nvim_types::Object, crate::rpc::RpcError> {
+        self.0.to_object()
+    }
+}
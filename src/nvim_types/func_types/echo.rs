@@ -48,3 +48,10 @@ impl<'a> From<&'a Echo> for Borrowed<'a, Echo> {
         Borrowed::new(value)
     }
 }
+
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for Echo {
+    fn to_object(&self) -> Result<Object, crate::rpc::RpcError> {
+        Ok(Object::Array(self.0.clone()))
+    }
+}
@@ -38,3 +38,10 @@ impl KeyMapMode {
 #[repr(transparent)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct KeyMapMode(ThinString<'static>);
+
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for KeyMapMode {
+    fn to_object(&self) -> Result<crate::nvim_types::Object, crate::rpc::RpcError> {
+        self.0.to_object()
+    }
+}
@@ -0,0 +1,646 @@
+//! JSON encoding and decoding of [`Object`] trees
+//!
+//! The conversions follow the semantics of `vim.json`:
+//! - `null` is decoded as [`Object::Null`] which is `vim.NIL` in Lua.
+//! - `{}` is decoded as an empty [`Dict`] and `[]` as an empty [`Array`] so the difference
+//!   between `vim.empty_dict()` and an empty list is kept when converting back.
+//! - Numbers without a fraction or exponent are decoded as [`Object::Integer`] unless they do not
+//!   fit in an [`Integer`]. [`Object::Float`] is always encoded with a fraction or exponent so it
+//!   is decoded as a float again.
+//! - [`Buffer`](crate::nvim_types::Buffer), [`Window`](crate::nvim_types::Window) and
+//!   [`TabPage`](crate::nvim_types::TabPage) are encoded as their handle.
+//!
+//! Strings are written byte for byte, they are not validated as UTF-8.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::nvim_types::{Dict, Object};
+//!
+//! let msg = br#"{"jsonrpc": "2.0", "id": 1, "result": null}"#;
+//! let d = Dict::from_json(msg).unwrap();
+//! assert_eq!(d.get(c"id"), Some(&Object::Integer(1)));
+//!
+//! let mut out = Vec::new();
+//! d.to_json(&mut out).unwrap();
+//! assert_eq!(out, br#"{"jsonrpc":"2.0","id":1,"result":null}"#);
+//! ```
+
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::nvim_types::{
+    Array, Dict, Float, Integer, KVec, NvString, Object, OwnedThinString, dictionary::KeyValuePair,
+};
+
+/// The maximum nesting of arrays and objects
+///
+/// Decoding is recursive so this is kept the same as the msgpack decoder to avoid overflowing
+/// small thread stacks.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug)]
+pub enum JsonError {
+    /// The input ended before a complete value was read
+    Incomplete,
+    /// The input is not valid JSON
+    Syntax {
+        /// The byte offset of the error
+        pos: usize,
+        reason: &'static str,
+    },
+    /// Arrays and objects are nested too deeply
+    DepthLimit,
+    /// NaN and infinity cannot be encoded
+    NonFinite,
+    /// A [`LuaRef`](crate::nvim_types::LuaRef) cannot be encoded
+    LuaRef,
+    /// Writing the encoded value failed
+    Io(io::Error),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Incomplete => f.write_str("unexpected end of JSON input"),
+            Self::Syntax { pos, reason } => write!(f, "invalid JSON at byte {}: {}", pos, reason),
+            Self::DepthLimit => f.write_str("JSON input is nested too deeply"),
+            Self::NonFinite => f.write_str("NaN and infinity cannot be encoded as JSON"),
+            Self::LuaRef => f.write_str("a LuaRef cannot be encoded as JSON"),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<io::Error> for JsonError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Object {
+    /// Writes the object as JSON
+    ///
+    /// Many small writes are performed so `writer` should be buffered.
+    pub fn to_json<W: Write>(&self, writer: &mut W) -> Result<(), JsonError> {
+        encode(self, writer)
+    }
+
+    /// Decodes a JSON value that takes up the entire buffer apart from whitespace
+    pub fn from_json(buf: &[u8]) -> Result<Self, JsonError> {
+        let mut parser = Parser { buf, pos: 0 };
+        let obj = parser.value(0)?;
+        parser.skip_ws();
+        match parser.pos == buf.len() {
+            true => Ok(obj),
+            false => Err(parser.err("trailing characters after the value")),
+        }
+    }
+}
+
+impl Array {
+    /// Writes the array as JSON
+    pub fn to_json<W: Write>(&self, writer: &mut W) -> Result<(), JsonError> {
+        encode_array(self, writer)
+    }
+
+    /// Decodes a JSON array that takes up the entire buffer apart from whitespace
+    pub fn from_json(buf: &[u8]) -> Result<Self, JsonError> {
+        match Object::from_json(buf)? {
+            Object::Array(arr) => Ok(arr),
+            _ => Err(JsonError::Syntax {
+                pos: 0,
+                reason: "expected an array",
+            }),
+        }
+    }
+}
+
+impl Dict {
+    /// Writes the dictionary as a JSON object
+    pub fn to_json<W: Write>(&self, writer: &mut W) -> Result<(), JsonError> {
+        encode_dict(self, writer)
+    }
+
+    /// Decodes a JSON object that takes up the entire buffer apart from whitespace
+    pub fn from_json(buf: &[u8]) -> Result<Self, JsonError> {
+        match Object::from_json(buf)? {
+            Object::Dict(d) => Ok(d),
+            _ => Err(JsonError::Syntax {
+                pos: 0,
+                reason: "expected an object",
+            }),
+        }
+    }
+}
+
+/// Decodes a single JSON value from the start of the buffer
+///
+/// Returns the value and the number of bytes read so values can be read from a stream.
+/// [`JsonError::Incomplete`] is returned if more input is needed to decode the value. This
+/// includes a number that ends at the end of the buffer as more digits may follow it.
+pub fn decode(buf: &[u8]) -> Result<(Object, usize), JsonError> {
+    let mut parser = Parser { buf, pos: 0 };
+    let obj = parser.value(0)?;
+    if let Object::Integer(_) | Object::Float(_) = obj
+        && parser.pos == buf.len()
+    {
+        return Err(JsonError::Incomplete);
+    }
+    Ok((obj, parser.pos))
+}
+
+fn encode<W: Write>(obj: &Object, w: &mut W) -> Result<(), JsonError> {
+    match obj {
+        Object::Null => w.write_all(b"null")?,
+        Object::Bool(true) => w.write_all(b"true")?,
+        Object::Bool(false) => w.write_all(b"false")?,
+        Object::Integer(i) => write!(w, "{}", i)?,
+        Object::Float(f) => encode_float(*f, w)?,
+        Object::String(s) => encode_str(s.as_thinstr().as_slice(), w)?,
+        Object::Array(arr) => encode_array(arr, w)?,
+        Object::Dict(d) => encode_dict(d, w)?,
+        Object::LuaRef(_) => return Err(JsonError::LuaRef),
+        Object::Buffer(b) => write!(w, "{}", b.as_int())?,
+        Object::Window(win) => write!(w, "{}", win.as_int())?,
+        Object::TabPage(t) => write!(w, "{}", t.as_int())?,
+    }
+
+    Ok(())
+}
+
+fn encode_array<W: Write>(arr: &Array, w: &mut W) -> Result<(), JsonError> {
+    w.write_all(b"[")?;
+    for (i, obj) in arr.iter().enumerate() {
+        if i != 0 {
+            w.write_all(b",")?;
+        }
+        encode(obj, w)?;
+    }
+    w.write_all(b"]")?;
+    Ok(())
+}
+
+fn encode_dict<W: Write>(d: &Dict, w: &mut W) -> Result<(), JsonError> {
+    w.write_all(b"{")?;
+    for (i, kv) in d.iter().enumerate() {
+        if i != 0 {
+            w.write_all(b",")?;
+        }
+        encode_str(kv.key.as_thinstr().as_slice(), w)?;
+        w.write_all(b":")?;
+        encode(&kv.object, w)?;
+    }
+    w.write_all(b"}")?;
+    Ok(())
+}
+
+fn encode_float<W: Write>(f: Float, w: &mut W) -> Result<(), JsonError> {
+    if !f.is_finite() {
+        return Err(JsonError::NonFinite);
+    }
+    // the debug representation always contains a fraction or an exponent and round trips
+    write!(w, "{:?}", f)?;
+    Ok(())
+}
+
+fn encode_str<W: Write>(s: &[u8], w: &mut W) -> io::Result<()> {
+    w.write_all(b"\"")?;
+    let mut start = 0;
+    for (i, &b) in s.iter().enumerate() {
+        let escape: &[u8] = match b {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x08 => b"\\b",
+            0x0c => b"\\f",
+            0..0x20 => b"",
+            _ => continue,
+        };
+        w.write_all(&s[start..i])?;
+        match escape {
+            [] => write!(w, "\\u{:04x}", b)?,
+            escape => w.write_all(escape)?,
+        }
+        start = i + 1;
+    }
+    w.write_all(&s[start..])?;
+    w.write_all(b"\"")
+}
+
+struct Parser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn err(&self, reason: &'static str) -> JsonError {
+        JsonError::Syntax {
+            pos: self.pos,
+            reason,
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.buf.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace and returns the next byte without consuming it
+    fn peek(&mut self) -> Result<u8, JsonError> {
+        self.skip_ws();
+        self.buf.get(self.pos).copied().ok_or(JsonError::Incomplete)
+    }
+
+    fn expect(&mut self, lit: &'static [u8]) -> Result<(), JsonError> {
+        let rest = &self.buf[self.pos..];
+        if rest.len() < lit.len() && lit.starts_with(rest) {
+            return Err(JsonError::Incomplete);
+        }
+        if !rest.starts_with(lit) {
+            return Err(self.err("invalid literal"));
+        }
+        self.pos += lit.len();
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Object, JsonError> {
+        if depth > MAX_DEPTH {
+            return Err(JsonError::DepthLimit);
+        }
+
+        Ok(match self.peek()? {
+            b'n' => {
+                self.expect(b"null")?;
+                Object::Null
+            }
+            b't' => {
+                self.expect(b"true")?;
+                Object::Bool(true)
+            }
+            b'f' => {
+                self.expect(b"false")?;
+                Object::Bool(false)
+            }
+            b'"' => Object::String(self.string()?),
+            b'[' => Object::Array(self.array(depth)?),
+            b'{' => Object::Dict(self.object(depth)?),
+            b'-' | b'0'..=b'9' => self.number()?,
+            _ => return Err(self.err("expected a value")),
+        })
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Array, JsonError> {
+        self.pos += 1;
+        let mut arr = KVec::new();
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Ok(Array::from(arr));
+        }
+        loop {
+            arr.push(self.value(depth + 1)?);
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Ok(Array::from(arr));
+                }
+                _ => return Err(self.err("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Dict, JsonError> {
+        self.pos += 1;
+        let mut pairs = KVec::new();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Ok(Dict::from(pairs));
+        }
+        loop {
+            if self.peek()? != b'"' {
+                return Err(self.err("expected a string key"));
+            }
+            let key = self.string()?;
+            if self.peek()? != b':' {
+                return Err(self.err("expected ':'"));
+            }
+            self.pos += 1;
+            let object = self.value(depth + 1)?;
+            pairs.push(KeyValuePair { key, object });
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b'}' => {
+                    self.pos += 1;
+                    return Ok(Dict::from(dedup_keys(pairs)));
+                }
+                _ => return Err(self.err("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Object, JsonError> {
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let start = p.pos;
+            while let Some(b'0'..=b'9') = p.buf.get(p.pos) {
+                p.pos += 1;
+            }
+            p.pos - start
+        };
+
+        if self.buf[self.pos] == b'-' {
+            self.pos += 1;
+        }
+        let int_start = self.pos;
+        match digits(self) {
+            0 if self.pos == self.buf.len() => return Err(JsonError::Incomplete),
+            0 => return Err(self.err("expected a digit")),
+            n if n > 1 && self.buf[int_start] == b'0' => {
+                return Err(self.err("leading zeros are not allowed"));
+            }
+            _ => {}
+        }
+
+        let mut float = false;
+        if self.buf.get(self.pos) == Some(&b'.') {
+            float = true;
+            self.pos += 1;
+            if digits(self) == 0 {
+                return Err(self.incomplete_or("expected a digit after '.'"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.buf.get(self.pos) {
+            float = true;
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.buf.get(self.pos) {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.incomplete_or("expected a digit in the exponent"));
+            }
+        }
+
+        // only ASCII digits and signs were consumed
+        let s = unsafe { core::str::from_utf8_unchecked(&self.buf[start..self.pos]) };
+        if !float && let Ok(i) = s.parse::<Integer>() {
+            return Ok(Object::Integer(i));
+        }
+        s.parse::<Float>()
+            .map(Object::Float)
+            .map_err(|_| self.err("invalid number"))
+    }
+
+    fn incomplete_or(&self, reason: &'static str) -> JsonError {
+        match self.pos == self.buf.len() {
+            true => JsonError::Incomplete,
+            false => self.err(reason),
+        }
+    }
+
+    fn string(&mut self) -> Result<OwnedThinString, JsonError> {
+        self.pos += 1;
+        let start = self.pos;
+
+        // find the end of the string first so it is only allocated once
+        let mut end = start;
+        let mut escaped = false;
+        loop {
+            match self.buf.get(end) {
+                None => return Err(JsonError::Incomplete),
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    escaped = true;
+                    end += 2;
+                }
+                Some(0..0x20) => {
+                    self.pos = end;
+                    return Err(self.err("control characters must be escaped"));
+                }
+                Some(_) => end += 1,
+            }
+        }
+
+        if !escaped {
+            self.pos = end + 1;
+            return Ok(OwnedThinString::from(&self.buf[start..end]));
+        }
+
+        let mut s = NvString::with_capacity(end - start);
+        let mut run = start;
+        while self.pos < end {
+            if self.buf[self.pos] != b'\\' {
+                self.pos += 1;
+                continue;
+            }
+            s.push(&self.buf[run..self.pos]);
+            self.pos += 1;
+            let unescaped = match self.buf[self.pos] {
+                b'"' => b'"',
+                b'\\' => b'\\',
+                b'/' => b'/',
+                b'b' => 0x08,
+                b'f' => 0x0c,
+                b'n' => b'\n',
+                b'r' => b'\r',
+                b't' => b'\t',
+                b'u' => {
+                    let c = self.unicode_escape()?;
+                    s.push(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    run = self.pos;
+                    continue;
+                }
+                _ => return Err(self.err("invalid escape")),
+            };
+            s.push([unescaped]);
+            self.pos += 1;
+            run = self.pos;
+        }
+        s.push(&self.buf[run..end]);
+        self.pos = end + 1;
+
+        Ok(OwnedThinString::from(s))
+    }
+
+    /// Reads the digits of a `\u` escape and a trailing low surrogate if needed
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let hex = |p: &mut Self| {
+            let digits = p
+                .buf
+                .get(p.pos + 1..p.pos + 5)
+                .filter(|d| d.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|d| core::str::from_utf8(d).ok())
+                .and_then(|d| u16::from_str_radix(d, 16).ok())
+                .ok_or(p.err("invalid unicode escape"))?;
+            p.pos += 5;
+            Ok::<_, JsonError>(digits)
+        };
+
+        let high = hex(self)?;
+        let code = match high {
+            0xd800..=0xdbff => {
+                if self.buf.get(self.pos..self.pos + 2) != Some(b"\\u") {
+                    return Err(self.err("unpaired surrogate"));
+                }
+                self.pos += 1;
+                let low = hex(self)?;
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return Err(self.err("unpaired surrogate"));
+                }
+                0x10000 + ((u32::from(high) - 0xd800) << 10) + (u32::from(low) - 0xdc00)
+            }
+            code => code.into(),
+        };
+        char::from_u32(code).ok_or(self.err("unpaired surrogate"))
+    }
+}
+
+/// Removes duplicate keys, the last value is kept like `vim.json.decode`
+///
+/// The value is moved to the position of the first occurrence of the key.
+fn dedup_keys(mut pairs: KVec<KeyValuePair>) -> KVec<KeyValuePair> {
+    let mut seen = HashMap::with_capacity(pairs.len());
+    // the index of each duplicate and the index of the first occurrence of its key
+    let mut dups = Vec::new();
+    for (i, kv) in pairs.iter().enumerate() {
+        match seen.entry(kv.key.as_thinstr().as_slice()) {
+            Entry::Occupied(first) => dups.push((i, *first.get())),
+            Entry::Vacant(slot) => {
+                slot.insert(i);
+            }
+        }
+    }
+    if dups.is_empty() {
+        return pairs;
+    }
+
+    let mut keep = vec![true; pairs.len()];
+    for (dup, first) in dups {
+        let object = core::mem::replace(&mut pairs[dup].object, Object::Null);
+        pairs[first].object = object;
+        keep[dup] = false;
+    }
+    pairs
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(kv, keep)| keep.then_some(kv))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        array, dict,
+        nvim_types::{Array, Buffer, Dict, Object, OwnedThinString},
+    };
+
+    use super::{JsonError, decode};
+
+    fn encode(obj: &Object) -> String {
+        let mut out = Vec::new();
+        obj.to_json(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn string(s: &str) -> Object {
+        Object::String(OwnedThinString::from(s))
+    }
+
+    #[test]
+    fn vim_json_semantics() {
+        let obj = Object::from_json(br#"[null, {}, [], 1, 1.0, 1e2, -0.5, 99999999999999999999]"#)
+            .unwrap();
+        assert_eq!(
+            obj,
+            Object::Array(array![
+                (Object::Null),
+                (Object::Dict(Dict::default())),
+                (Object::Array(Array::default())),
+                1,
+                1.0,
+                100.0,
+                (-0.5),
+                1e20
+            ])
+        );
+        assert_eq!(encode(&obj), "[null,{},[],1,1.0,100.0,-0.5,1e20]");
+        assert_eq!(Object::from_json(encode(&obj).as_bytes()).unwrap(), obj);
+        assert_eq!(encode(&Object::Buffer(Buffer::new(3))), "3");
+        assert!(matches!(
+            Object::Float(f64::NAN).to_json(&mut Vec::new()),
+            Err(JsonError::NonFinite)
+        ));
+    }
+
+    #[test]
+    fn strings() {
+        let s = "quote \" slash \\ / newline \n tab \t bell \x07 é 𝄞";
+        let json = encode(&string(s));
+        assert_eq!(
+            json,
+            "\"quote \\\" slash \\\\ / newline \\n tab \\t bell \\u0007 é 𝄞\""
+        );
+        assert_eq!(Object::from_json(json.as_bytes()).unwrap(), string(s));
+        assert_eq!(
+            Object::from_json(r#""é𝄞\/\u00e9\ud834\udd1e""#.as_bytes()).unwrap(),
+            string("é𝄞/é𝄞")
+        );
+        assert!(Object::from_json(br#""\ud834""#).is_err());
+        assert!(Object::from_json(br#""\udd1e""#).is_err());
+        assert!(Object::from_json(b"\"raw\nnewline\"").is_err());
+        assert!(Object::from_json(br#""\u+041""#).is_err());
+    }
+
+    #[test]
+    fn objects() {
+        let d = Dict::from_json(br#" { "a" : 1, "b": {"c": [true, false]}, "a": 2 } "#).unwrap();
+        let inner = dict! { "c" = [true, false] };
+        assert_eq!(d, dict! { "a" = 2, "b" = (inner) });
+        assert!(Dict::from_json(b"[]").is_err());
+        assert!(Array::from_json(b"{}").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        for input in [
+            &b"[1, 2"[..],
+            b"{\"a\"",
+            b"\"abc",
+            b"tru",
+            b"-",
+            b"1.",
+            b"12",
+            b"1e5",
+        ] {
+            assert!(
+                matches!(decode(input), Err(JsonError::Incomplete)),
+                "{:?}",
+                input
+            );
+        }
+        for input in [&b"[1,]"[..], b"{1: 2}", b"01", b"nul!", b"1 2", b"[1 2]"] {
+            assert!(
+                matches!(Object::from_json(input), Err(JsonError::Syntax { .. })),
+                "{:?}",
+                input
+            );
+        }
+        assert!(matches!(
+            Object::from_json(&[b'['; 2000]),
+            Err(JsonError::DepthLimit)
+        ));
+        // values can be read one after another
+        let (obj, read) = decode(b"{}{\"a\": 1}").unwrap();
+        assert_eq!((obj, read), (Object::Dict(Dict::default()), 2));
+        // a number can only end before the end of the buffer in a stream
+        assert_eq!(decode(b"12 3").unwrap(), (Object::Integer(12), 2));
+        assert_eq!(Object::from_json(b"12").unwrap(), Object::Integer(12));
+    }
+}
@@ -10,9 +10,10 @@ use std::{
 
 use libc::c_int;
 use mlua_sys::{
-    LUA_REGISTRYINDEX, lua_State, lua_checkstack, lua_newuserdata, lua_pop, lua_pushcclosure,
-    lua_pushcfunction, lua_pushlightuserdata, lua_rawgeti, lua_setfield, lua_setmetatable,
-    lua_tolightuserdata, lua_touserdata, lua_upvalueindex, luaL_newmetatable, luaL_ref,
+    LUA_REGISTRYINDEX, lua_State, lua_checkstack, lua_gettop, lua_newuserdata, lua_pop,
+    lua_pushcclosure, lua_pushcfunction, lua_pushlightuserdata, lua_rawgeti, lua_setfield,
+    lua_setmetatable, lua_tolightuserdata, lua_touserdata, lua_upvalueindex, luaL_newmetatable,
+    luaL_ref,
 };
 use rand::{SeedableRng, distr::Distribution, rngs::SmallRng};
 use thread_lock::init_lua_ptr;
@@ -27,8 +28,6 @@ use super::core::FromLuaMany;
 static FALLBACK_TYPE_NAME: &CStr = c"NVIMIUM FALLBACK CALLBACK ID";
 static TYPE_NAME: AtomicPtr<c_char> = AtomicPtr::new(FALLBACK_TYPE_NAME.as_ptr() as *mut c_char);
 
-// TODO: return results in the functions below.
-
 /// Sets the callback function string identifier.
 /// This is used to ensure that a user data is the one associated with this callback.
 ///
@@ -104,10 +103,53 @@ fn metatable_key(l: *mut lua_State) -> i32 {
 /// The key to the callback metatable.
 static KEY: OnceLock<i32> = OnceLock::new();
 
-pub fn register<E: Error, F: 'static + Fn(A) -> Result<R, E>, A: FromLuaMany, R>(
+pub fn register<E: Error, F: 'static + Fn(A) -> Result<R, E>, A: FromLuaMany, R: super::IntoLua>(
     l: *mut lua_State,
     f: F,
 ) -> i32 {
+    // f must be moved or else it gets freed at the end of the scope
+    let f: Box<dyn Fn(*mut lua_State) -> c_int> = Box::new(move |l| unsafe {
+        let mut to_pop = 0;
+        let arg = match A::get(l, &mut to_pop) {
+            Ok(arg) => arg,
+            Err(err) => {
+                handle_callback_err_ret(l, &err);
+                return 0;
+            }
+        };
+        let ret = f(arg);
+        lua_pop(l, to_pop);
+        push_callback_ret(l, ret)
+    });
+
+    register_raw(l, f)
+}
+
+/// Pushes the value returned from a callback and returns the number of pushed values
+///
+/// Errors are passed to [`handle_callback_err_ret`] and push nothing.
+pub(crate) unsafe fn push_callback_ret<R: super::IntoLua, E: Error>(
+    l: *mut lua_State,
+    ret: Result<R, E>,
+) -> c_int {
+    match ret {
+        Ok(r) => unsafe {
+            // the number of pushed values depends on the implementation so compare the
+            // stack size instead
+            let top = lua_gettop(l);
+            super::IntoLua::push(&r, l);
+            lua_gettop(l) - top
+        },
+        Err(err) => {
+            unsafe { handle_callback_err_ret(l, &err) };
+            0
+        }
+    }
+}
+
+/// Registers a closure that reads its arguments from and pushes its return values on to the
+/// stack, returning the number of pushed values
+pub(crate) fn register_raw(l: *mut lua_State, f: Box<dyn Fn(*mut lua_State) -> c_int>) -> i32 {
     extern "C-unwind" fn call(l: *mut lua_State) -> i32 {
         // before calling init in case a jump happens
         unsafe {
@@ -131,27 +173,6 @@ pub fn register<E: Error, F: 'static + Fn(A) -> Result<R, E>, A: FromLuaMany, R>
             panic!("not enough stack space to set Callback metatable");
         }
 
-        // f must be moved or else it gets freed at the end of the scope
-        let f: Box<dyn Fn(*mut lua_State) -> c_int> = Box::new(move |l| {
-            let mut to_pop = 0;
-            let arg = match A::get(l, &mut to_pop) {
-                Ok(arg) => arg,
-                Err(err) => {
-                    handle_callback_err_ret(l, &err);
-                    return 0;
-                }
-            };
-            let ret = f(arg);
-            lua_pop(l, to_pop);
-            match ret {
-                Ok(r) => 0,
-                Err(err) => {
-                    handle_callback_err_ret(l, &err);
-                    0
-                }
-            }
-        });
-
         let ud = lua_newuserdata(l, size_of::<Box<dyn Fn(*mut lua_State) -> c_int>>())
             as *mut Box<dyn Fn(*mut lua_State) -> c_int>;
         ud.write(f);
@@ -0,0 +1,141 @@
+use std::{error::Error, fmt::Display, slice};
+
+use libc::c_int;
+use mlua_sys::{
+    LUA_MULTRET, LUA_TSTRING, lua_State, lua_checkstack, lua_gettop, lua_insert, lua_pcall,
+    lua_pop, lua_pushcfunction, lua_pushlightuserdata, lua_remove, lua_tolightuserdata,
+    lua_tolstring, lua_type, luaL_traceback,
+};
+use thread_lock::{call_check, get_lua_ptr};
+
+use super::{
+    Function,
+    core::{FromLuaErr, FromLuaMany, IntoLuaMany},
+};
+
+/// An error returned from [`Function::call`]
+#[derive(Clone, Debug)]
+pub enum CallError {
+    /// The function raised an error
+    Runtime {
+        message: String,
+        /// The stack traceback at the point of the error.
+        traceback: String,
+    },
+    /// The arguments could not be pushed or the return values could not be read
+    Conversion(FromLuaErr),
+}
+
+impl Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Runtime { message, traceback } => write!(f, "{}\n{}", message, traceback),
+            Self::Conversion(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for CallError {}
+
+impl From<FromLuaErr> for CallError {
+    fn from(value: FromLuaErr) -> Self {
+        Self::Conversion(value)
+    }
+}
+
+const TRACEBACK_HEADER: &str = "\nstack traceback:";
+
+impl CallError {
+    unsafe fn from_stack(l: *mut lua_State, index: c_int) -> Self {
+        let err = unsafe { lossy_string(l, index) };
+        let (message, traceback) = match err.find(TRACEBACK_HEADER) {
+            Some(i) => (err[..i].to_string(), err[i + 1..].to_string()),
+            None => (err, String::new()),
+        };
+
+        Self::Runtime { message, traceback }
+    }
+}
+
+unsafe fn lossy_string(l: *mut lua_State, index: c_int) -> String {
+    unsafe {
+        if lua_type(l, index) != LUA_TSTRING {
+            return String::from("(error object is not a string)");
+        }
+        let mut len = 0;
+        let ptr = lua_tolstring(l, index, &mut len);
+        String::from_utf8_lossy(slice::from_raw_parts(ptr as *const u8, len)).into_owned()
+    }
+}
+
+/// Appends the traceback to the error message, used as the message handler for `lua_pcall`
+extern "C-unwind" fn traceback(l: *mut lua_State) -> c_int {
+    unsafe {
+        if lua_type(l, 1) == LUA_TSTRING {
+            let msg = lua_tolstring(l, 1, std::ptr::null_mut());
+            luaL_traceback(l, l, msg, 1);
+        } else {
+            luaL_traceback(l, l, c"(error object is not a string)".as_ptr(), 1);
+        }
+        1
+    }
+}
+
+/// Calls the function with its arguments in a new stack frame
+///
+/// The return values are read with [`FromLuaMany`] which expects them to start at index 1. This
+/// is only true in a fresh frame so the call is done from a C function.
+extern "C-unwind" fn call_in_frame<R: FromLuaMany>(l: *mut lua_State) -> c_int {
+    unsafe {
+        let ret = lua_tolightuserdata(l, 1) as *mut Option<Result<R, CallError>>;
+        lua_remove(l, 1);
+
+        // C functions are guaranteed LUA_MINSTACK slots so there is space for the handler
+        lua_pushcfunction(l, traceback);
+        lua_insert(l, 1);
+        let result = if lua_pcall(l, lua_gettop(l) - 2, LUA_MULTRET, 1) != 0 {
+            Err(CallError::from_stack(l, -1))
+        } else {
+            lua_remove(l, 1);
+            let mut to_pop = 0;
+            R::get(l, &mut to_pop).map_err(CallError::Conversion)
+        };
+        ret.write(Some(result));
+
+        0
+    }
+}
+
+impl Function {
+    /// Calls the Lua function with the provided arguments
+    ///
+    /// Tuples are pushed as multiple arguments and read from multiple return values. Errors raised
+    /// by the function are returned as [`CallError::Runtime`] along with the stack traceback.
+    ///
+    /// # Panics
+    ///
+    /// If called from a thread that does not have access to Neovim.
+    pub fn call<A: IntoLuaMany, R: 'static + FromLuaMany>(&self, args: A) -> Result<R, CallError> {
+        call_check();
+        let mut l = get_lua_ptr();
+        let l = l.as_ptr();
+        let mut ret: Option<Result<R, CallError>> = None;
+        unsafe {
+            // the trampoline, the return value pointer, the function and its arguments
+            if lua_checkstack(l, 3 + A::VALUES) == 0 {
+                return Err(FromLuaErr::NotEnoughStackSpace.into());
+            }
+            lua_pushcfunction(l, call_in_frame::<R>);
+            lua_pushlightuserdata(l, (&raw mut ret).cast());
+            super::IntoLua::push(&self.0, l);
+            let nargs = args.push_many(l);
+            if lua_pcall(l, nargs + 2, 0, 0) != 0 {
+                let err = CallError::from_stack(l, -1);
+                lua_pop(l, 1);
+                return Err(err);
+            }
+        }
+
+        ret.expect("lua function call did not write its result")
+    }
+}
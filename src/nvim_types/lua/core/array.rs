@@ -10,7 +10,7 @@ impl IntoLua for Array {
         unsafe {
             lua_createtable(l, self.0.len().try_into().unwrap(), 0);
         }
-        for (obj, i) in self.0.iter().zip(1..self.0.len()) {
+        for (obj, i) in self.0.iter().zip(1..=self.0.len()) {
             unsafe {
                 obj.push(l);
                 lua_rawseti(l, -2, i.try_into().unwrap());
@@ -1,11 +1,30 @@
-use mlua_sys::{LUA_REGISTRYINDEX, lua_rawgeti};
+use libc::c_int;
+use mlua_sys::{LUA_REGISTRYINDEX, lua_checkstack, lua_pushvalue, lua_rawgeti, luaL_ref};
 
 use crate::nvim_types::{lua::LuaInteger, lua_ref::LuaRef};
 
-use super::IntoLua;
+use super::{FromLua, FromLuaErr, IntoLua};
 
 impl IntoLua for LuaRef {
     unsafe fn push(&self, l: *mut mlua_sys::lua_State) {
         unsafe { lua_rawgeti(l, LUA_REGISTRYINDEX, self.as_int() as LuaInteger) };
     }
 }
+
+/// Stores any value in the registry, `nil` is stored as a reference to `nil`
+impl FromLua for LuaRef {
+    unsafe fn get(
+        l: *mut mlua_sys::lua_State,
+        index: c_int,
+        to_pop: &mut i32,
+    ) -> super::Result<Self> {
+        unsafe {
+            if lua_checkstack(l, 1) == 0 {
+                return Err(FromLuaErr::NotEnoughStackSpace);
+            }
+            lua_pushvalue(l, index);
+            *to_pop += 1;
+            Ok(LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX)))
+        }
+    }
+}
@@ -7,7 +7,7 @@ mod hl_group;
 mod integer;
 mod lua_ref;
 mod namespace;
-mod object;
+pub(crate) mod object;
 mod string;
 mod tabpage;
 mod window;
@@ -15,19 +15,102 @@ mod window;
 use std::{error::Error, fmt::Display};
 
 use libc::c_int;
-use mlua_sys::{lua_State, lua_pop};
+use mlua_sys::{lua_State, lua_gettop, lua_pop};
 
 pub trait FromLuaMany: Sized {
     unsafe fn get(l: *mut lua_State, to_pop: &mut i32) -> Result<Self>;
 }
+/// Reads the value at the top of the stack
 impl<T> FromLuaMany for T
 where
     T: FromLua,
 {
     unsafe fn get(l: *mut lua_State, to_pop: &mut i32) -> Result<Self> {
-        unsafe { <Self as FromLua>::get(l, -1, to_pop) }
+        unsafe {
+            // without any values -1 points below the frame, reading position 1 reports it missing
+            match lua_gettop(l) {
+                0 => <Self as FromLua>::get(l, 1, to_pop),
+                _ => <Self as FromLua>::get(l, -1, to_pop),
+            }
+        }
     }
 }
+/// Reads each element of the tuple from the argument at the same position
+///
+/// Missing arguments return [`FromLuaErr::NotFound`] for most types, use an [`Option`] for
+/// arguments that may be missing. Errors report the position of the argument that failed.
+macro_rules! tuple_from_lua_many {
+    ($($name:ident = $pos:literal),+) => {
+        impl<$($name: FromLua),+> FromLuaMany for ($($name,)+) {
+            unsafe fn get(l: *mut lua_State, to_pop: &mut i32) -> Result<Self> {
+                Ok(($(
+                    unsafe { <$name as FromLua>::get(l, $pos, to_pop) }
+                        .map_err(|err| err.at_arg($pos))?,
+                )+))
+            }
+        }
+    };
+}
+
+tuple_from_lua_many!(A = 1);
+tuple_from_lua_many!(A = 1, B = 2);
+tuple_from_lua_many!(A = 1, B = 2, C = 3);
+tuple_from_lua_many!(A = 1, B = 2, C = 3, D = 4);
+tuple_from_lua_many!(A = 1, B = 2, C = 3, D = 4, E = 5);
+tuple_from_lua_many!(A = 1, B = 2, C = 3, D = 4, E = 5, F = 6);
+tuple_from_lua_many!(A = 1, B = 2, C = 3, D = 4, E = 5, F = 6, G = 7);
+tuple_from_lua_many!(A = 1, B = 2, C = 3, D = 4, E = 5, F = 6, G = 7, H = 8);
+tuple_from_lua_many!(
+    A = 1,
+    B = 2,
+    C = 3,
+    D = 4,
+    E = 5,
+    F = 6,
+    G = 7,
+    H = 8,
+    I = 9
+);
+tuple_from_lua_many!(
+    A = 1,
+    B = 2,
+    C = 3,
+    D = 4,
+    E = 5,
+    F = 6,
+    G = 7,
+    H = 8,
+    I = 9,
+    J = 10
+);
+tuple_from_lua_many!(
+    A = 1,
+    B = 2,
+    C = 3,
+    D = 4,
+    E = 5,
+    F = 6,
+    G = 7,
+    H = 8,
+    I = 9,
+    J = 10,
+    K = 11
+);
+tuple_from_lua_many!(
+    A = 1,
+    B = 2,
+    C = 3,
+    D = 4,
+    E = 5,
+    F = 6,
+    G = 7,
+    H = 8,
+    I = 9,
+    J = 10,
+    K = 11,
+    L = 12
+);
+
 pub trait FromLua: 'static + Sized {
     unsafe fn pop(l: *mut lua_State, index: c_int) -> Result<Self> {
         unsafe {
@@ -47,6 +130,24 @@ pub enum FromLuaErr {
     NotFound,
     IncorrectType,
     NotEnoughStackSpace,
+    /// A table contains a reference to itself or one of its parent tables.
+    CyclicTable,
+    /// Failed to read the argument at the 1 based position, the reason being the inner error.
+    Argument(c_int, &'static FromLuaErr),
+}
+
+impl FromLuaErr {
+    /// Wraps the error with the position of the argument that caused it
+    pub(crate) fn at_arg(self, position: c_int) -> Self {
+        let reason = match self {
+            Self::NotFound => &Self::NotFound,
+            Self::IncorrectType => &Self::IncorrectType,
+            Self::NotEnoughStackSpace => &Self::NotEnoughStackSpace,
+            Self::CyclicTable => &Self::CyclicTable,
+            Self::Argument(_, reason) => reason,
+        };
+        Self::Argument(position, reason)
+    }
 }
 
 impl Display for FromLuaErr {
@@ -55,6 +156,10 @@ impl Display for FromLuaErr {
             Self::NotFound => "field not found",
             Self::IncorrectType => "incorrect lua type found",
             Self::NotEnoughStackSpace => "not enough stack space to read lua values",
+            Self::CyclicTable => "table contains a reference cycle",
+            Self::Argument(position, reason) => {
+                return write!(f, "bad argument #{}: {}", position, reason);
+            }
         };
         write!(f, "{}", s)
     }
@@ -73,19 +178,80 @@ impl<T: FromLua> FromLua for Option<T> {
 }
 
 pub trait IntoLua {
+    /// The number of values pushed by [`IntoLua::push`]
+    const VALUES: c_int = 1;
+
     /// Pushes a lua value onto the stack
     ///
     /// # Safety
     ///
     /// Every call must push a value even if it is a nill. Not doing so can result in UB in some
-    /// cases.
+    /// cases. The only exceptions are `()` which pushes nothing and tuples which push a value for
+    /// each of their elements.
     unsafe fn push(&self, l: *mut lua_State);
 }
 
+/// Pushes any number of values on to the stack, such as the arguments of a function call
+///
+/// Implemented for every [`IntoLua`] type, tuples pushing a value for each of their elements.
+pub trait IntoLuaMany {
+    /// The number of values pushed by [`IntoLuaMany::push_many`]
+    const VALUES: c_int;
+
+    /// Pushes the values and returns how many were pushed
+    ///
+    /// # Safety
+    ///
+    /// The return value must match the number of values pushed on to the stack.
+    unsafe fn push_many(&self, l: *mut lua_State) -> c_int;
+}
+
+impl<T: IntoLua> IntoLuaMany for T {
+    const VALUES: c_int = <T as IntoLua>::VALUES;
+
+    unsafe fn push_many(&self, l: *mut lua_State) -> c_int {
+        unsafe {
+            let top = lua_gettop(l);
+            self.push(l);
+            lua_gettop(l) - top
+        }
+    }
+}
+
 impl IntoLua for () {
+    const VALUES: c_int = 0;
+
     unsafe fn push(&self, _: *mut lua_State) {}
 }
 
+/// Pushes each element of the tuple in order so that a callback can return multiple values
+macro_rules! tuple_into_lua {
+    ($($name:ident),+) => {
+        impl<$($name: IntoLua),+> IntoLua for ($($name,)+) {
+            const VALUES: c_int = 0 $(+ $name::VALUES)+;
+
+            #[allow(non_snake_case)]
+            unsafe fn push(&self, l: *mut lua_State) {
+                let ($($name,)+) = self;
+                $(unsafe { $name.push(l) };)+
+            }
+        }
+    };
+}
+
+tuple_into_lua!(A);
+tuple_into_lua!(A, B);
+tuple_into_lua!(A, B, C);
+tuple_into_lua!(A, B, C, D);
+tuple_into_lua!(A, B, C, D, E);
+tuple_into_lua!(A, B, C, D, E, F);
+tuple_into_lua!(A, B, C, D, E, F, G);
+tuple_into_lua!(A, B, C, D, E, F, G, H);
+tuple_into_lua!(A, B, C, D, E, F, G, H, I);
+tuple_into_lua!(A, B, C, D, E, F, G, H, I, J);
+tuple_into_lua!(A, B, C, D, E, F, G, H, I, J, K);
+tuple_into_lua!(A, B, C, D, E, F, G, H, I, J, K, L);
+
 impl FromLua for () {
     unsafe fn get(_l: *mut lua_State, _index: c_int, _to_pop: &mut i32) -> Result<Self> {
         Ok(())
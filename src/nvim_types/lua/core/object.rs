@@ -1,8 +1,16 @@
-use mlua_sys::{lua_checkstack, lua_pushnil};
+use libc::{c_int, c_void};
+use mlua_sys::{
+    LUA_REGISTRYINDEX, LUA_TBOOLEAN, LUA_TFUNCTION, LUA_TLIGHTUSERDATA, LUA_TNIL, LUA_TNONE,
+    LUA_TNUMBER, LUA_TSTRING, LUA_TTABLE, lua_State, lua_checkstack, lua_next, lua_objlen, lua_pop,
+    lua_pushnil, lua_pushvalue, lua_toboolean, lua_tolstring, lua_tonumber, lua_topointer,
+    lua_touserdata, lua_type, luaL_ref,
+};
 
-use crate::nvim_types::Object;
+use crate::nvim_types::{
+    Array, Dict, Integer, KVec, KeyValuePair, LuaRef, Object, OwnedThinString, ThinString,
+};
 
-use super::IntoLua;
+use super::{FromLua, FromLuaErr, IntoLua, Result};
 
 impl IntoLua for Object {
     unsafe fn push(&self, l: *mut mlua_sys::lua_State) {
@@ -26,3 +34,180 @@ impl IntoLua for Object {
         }
     }
 }
+
+/// Reads the value at `index` as an [`Object`] without popping it
+///
+/// Tables with only sequential integer keys starting from 1 are read as an [`Array`], any other
+/// table is read as a [`Dict`] where every key must be a string. Functions are stored as a
+/// [`LuaRef`] and `vim.NIL` is read as [`Object::Null`].
+///
+/// A table that contains itself or one of its parents returns [`FromLuaErr::CyclicTable`], the
+/// same table can otherwise appear multiple times and is copied for each occurrence. Empty tables
+/// are read as an empty [`Array`].
+pub(crate) unsafe fn read_object(l: *mut lua_State, index: c_int) -> Result<Object> {
+    unsafe { read_value(l, index, &mut Vec::new()) }
+}
+
+/// Reads the value at `index`, `parents` being the tables that are currently being read
+unsafe fn read_value(
+    l: *mut lua_State,
+    index: c_int,
+    parents: &mut Vec<*const c_void>,
+) -> Result<Object> {
+    unsafe {
+        match lua_type(l, index) {
+            LUA_TNONE => Err(FromLuaErr::NotFound),
+            LUA_TNIL => Ok(Object::Null),
+            // vim.NIL
+            LUA_TLIGHTUSERDATA if lua_touserdata(l, index).is_null() => Ok(Object::Null),
+            LUA_TBOOLEAN => Ok(Object::Bool(lua_toboolean(l, index) != 0)),
+            LUA_TNUMBER => {
+                let n = lua_tonumber(l, index);
+                if n.fract() == 0. && n >= Integer::MIN as f64 && n <= Integer::MAX as f64 {
+                    Ok(Object::Integer(n as Integer))
+                } else {
+                    Ok(Object::Float(n))
+                }
+            }
+            LUA_TSTRING => {
+                let mut len = 0;
+                let ptr = lua_tolstring(l, index, &mut len);
+                Ok(Object::String(OwnedThinString::from(ThinString::new(
+                    len, ptr,
+                ))))
+            }
+            LUA_TFUNCTION => {
+                if lua_checkstack(l, 1) == 0 {
+                    return Err(FromLuaErr::NotEnoughStackSpace);
+                }
+                lua_pushvalue(l, index);
+                Ok(Object::LuaRef(LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX))))
+            }
+            LUA_TTABLE => {
+                let ptr = lua_topointer(l, index);
+                if parents.contains(&ptr) {
+                    return Err(FromLuaErr::CyclicTable);
+                }
+                parents.push(ptr);
+                let table = read_table(l, index, parents);
+                parents.pop();
+                table
+            }
+            _ => Err(FromLuaErr::IncorrectType),
+        }
+    }
+}
+
+unsafe fn read_table(
+    l: *mut lua_State,
+    index: c_int,
+    parents: &mut Vec<*const c_void>,
+) -> Result<Object> {
+    unsafe {
+        // key, value and a copy of the key for lua_tolstring
+        if lua_checkstack(l, 3) == 0 {
+            return Err(FromLuaErr::NotEnoughStackSpace);
+        }
+
+        // make the index absolute as we will be pushing values on to the stack
+        let index = if index < 0 {
+            mlua_sys::lua_gettop(l) + index + 1
+        } else {
+            index
+        };
+        let len = lua_objlen(l, index);
+
+        let mut arr = KVec::with_capacity(len);
+        let mut dict = KVec::new();
+        lua_pushnil(l);
+        while lua_next(l, index) != 0 {
+            let val = match read_value(l, -1, parents) {
+                Ok(val) => val,
+                Err(err) => {
+                    lua_pop(l, 2);
+                    return Err(err);
+                }
+            };
+            match lua_type(l, -2) {
+                LUA_TNUMBER if dict.is_empty() => {
+                    let i = lua_tonumber(l, -2);
+                    if i.fract() != 0. || i < 1. || i > len as f64 {
+                        lua_pop(l, 2);
+                        return Err(FromLuaErr::IncorrectType);
+                    }
+                    // lua_next does not guarantee any order
+                    arr.push((i as usize, val));
+                }
+                LUA_TSTRING if arr.is_empty() => {
+                    // lua_tolstring converts numbers in place which breaks lua_next so use a
+                    // copy of the key
+                    lua_pushvalue(l, -2);
+                    let mut len = 0;
+                    let ptr = lua_tolstring(l, -1, &mut len);
+                    let key = OwnedThinString::from(ThinString::new(len, ptr));
+                    lua_pop(l, 1);
+                    dict.push(KeyValuePair { key, object: val });
+                }
+                _ => {
+                    lua_pop(l, 2);
+                    return Err(FromLuaErr::IncorrectType);
+                }
+            }
+            // pop the value, leave the key for the next iteration
+            lua_pop(l, 1);
+        }
+
+        if !dict.is_empty() {
+            return Ok(Object::Dict(Dict(dict)));
+        }
+
+        arr.sort_unstable_by_key(|(i, _)| *i);
+        let mut items = KVec::with_capacity(arr.len());
+        items.extend(arr.into_iter().map(|(_, obj)| obj));
+        Ok(Object::Array(Array(items)))
+    }
+}
+
+impl FromLua for Object {
+    unsafe fn get(l: *mut lua_State, index: c_int, to_pop: &mut i32) -> Result<Self> {
+        let obj = unsafe { read_object(l, index) }?;
+        *to_pop += 1;
+        Ok(obj)
+    }
+}
+
+impl FromLua for Array {
+    unsafe fn get(l: *mut lua_State, index: c_int, to_pop: &mut i32) -> Result<Self> {
+        if unsafe { lua_type(l, index) } == LUA_TNONE {
+            return Err(FromLuaErr::NotFound);
+        }
+        *to_pop += 1;
+        if unsafe { lua_type(l, index) } != LUA_TTABLE {
+            return Err(FromLuaErr::IncorrectType);
+        }
+
+        match unsafe { read_object(l, index) }? {
+            Object::Array(arr) => Ok(arr),
+            _ => Err(FromLuaErr::IncorrectType),
+        }
+    }
+}
+
+impl FromLua for Dict {
+    unsafe fn get(l: *mut lua_State, index: c_int, to_pop: &mut i32) -> Result<Self> {
+        if unsafe { lua_type(l, index) } == LUA_TNONE {
+            return Err(FromLuaErr::NotFound);
+        }
+        *to_pop += 1;
+        if unsafe { lua_type(l, index) } != LUA_TTABLE {
+            return Err(FromLuaErr::IncorrectType);
+        }
+
+        match unsafe { read_object(l, index) }? {
+            Object::Dict(dict) => Ok(dict),
+            // an empty table cannot be told apart from an empty dictionary
+            Object::Array(arr) if arr.is_empty() => Ok(Dict::default()),
+            _ => Err(FromLuaErr::IncorrectType),
+        }
+    }
+}
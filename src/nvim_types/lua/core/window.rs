@@ -1,6 +1,22 @@
-use crate::nvim_types::{lua::LuaInteger, window::Window};
+use libc::c_int;
 
-use super::IntoLua;
+use crate::nvim_types::{HandleT, Integer, lua::LuaInteger, window::Window};
+
+use super::{FromLua, FromLuaErr, IntoLua};
+
+impl FromLua for Window {
+    unsafe fn get(
+        l: *mut mlua_sys::lua_State,
+        index: c_int,
+        to_pop: &mut i32,
+    ) -> super::Result<Self> {
+        let int = unsafe { Integer::get(l, index, to_pop) }?;
+
+        Ok(Self::new(
+            HandleT::try_from(int).map_err(|_| FromLuaErr::IncorrectType)?,
+        ))
+    }
+}
 
 impl IntoLua for Window {
     unsafe fn push(&self, l: *mut mlua_sys::lua_State) {
@@ -15,19 +15,25 @@
 // while some parts could still be used from mlua it is not really worth bringing in another dependency
 // as all use cases have a fix set of arguments which we can handle internally
 mod box_fn;
+mod call;
 pub mod core;
+mod module;
+mod userdata;
 pub(crate) mod utils;
 
 // used from a plugin! macro in user crate
 #[doc(hidden)]
 pub use box_fn::set_callback_name;
 
+pub use call::CallError;
 use core::FromLuaMany;
-pub use core::{FromLua, IntoLua};
+pub use core::{FromLua, IntoLua, IntoLuaMany};
+pub use module::LuaModule;
 use std::{
     error::Error,
     panic::{RefUnwindSafe, UnwindSafe},
 };
+pub use userdata::{LuaUserData, UserData, UserDataMethods};
 
 #[doc(hidden)]
 pub use mlua_sys::lua_State;
@@ -56,6 +62,13 @@ impl Function {
         Self(unsafe { LuaRef::new(box_fn::register(l.as_ptr(), f)) })
     }
 
+    /// Treats the reference as a function so that it can be called from Rust
+    ///
+    /// Calling a reference that is not a function returns an error from [`Function::call`].
+    pub fn from_luaref(lref: LuaRef) -> Self {
+        Self(lref)
+    }
+
     pub fn into_luaref(self) -> LuaRef {
         self.0
     }
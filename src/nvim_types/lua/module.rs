@@ -0,0 +1,80 @@
+use std::error::Error;
+
+use mlua_sys::lua_State;
+
+use crate::nvim_types::{AsThinString, Dict, Object, OwnedThinString};
+
+use super::{Function, IntoLua, NvFn, core::FromLuaMany};
+
+/// A Lua table that is returned from a plugin's entrypoint
+///
+/// Functions, constants and nested modules are stored under their names so that Lua users can
+/// call them through `require`.
+///
+/// # Example
+/// ```no_run
+/// use nvimium::{plugin, nvim_types::{Error, Integer, lua::LuaModule}};
+/// fn my_plugin() -> Result<LuaModule, Error> {
+///     let mut utils = LuaModule::new();
+///     utils.function(c"double", |i: Integer| Ok::<_, Error>(i * 2));
+///
+///     let mut module = LuaModule::new();
+///     module
+///         .constant(c"version", c"0.1.0")
+///         .module(c"utils", utils);
+///
+///     Ok(module)
+/// }
+///
+/// // `require('my_plugin').utils.double(2)` returns 4
+/// plugin!(luaopen_my_plugin, my_plugin);
+/// ```
+#[derive(Debug, Default)]
+pub struct LuaModule(Dict);
+
+impl LuaModule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a function under the provided name
+    ///
+    /// Registering a value with an existing name replaces the previous value.
+    pub fn function<
+        TH: AsThinString,
+        A: 'static + FromLuaMany,
+        R: 'static + IntoLua,
+        E: 'static + Error,
+        F: NvFn + Fn(A) -> Result<R, E>,
+    >(
+        &mut self,
+        name: TH,
+        f: F,
+    ) -> &mut Self {
+        self.set(name, Object::LuaRef(Function::wrap(f).into_luaref()))
+    }
+
+    /// Registers a constant value under the provided name
+    pub fn constant<TH: AsThinString, T: Into<Object>>(&mut self, name: TH, value: T) -> &mut Self {
+        self.set(name, value.into())
+    }
+
+    /// Registers a nested table under the provided name
+    pub fn module<TH: AsThinString>(&mut self, name: TH, module: LuaModule) -> &mut Self {
+        self.set(name, Object::Dict(module.0))
+    }
+
+    fn set<TH: AsThinString>(&mut self, name: TH, value: Object) -> &mut Self {
+        self.0
+            .insert(OwnedThinString::from(name.as_thinstr()), value);
+        self
+    }
+}
+
+impl IntoLua for LuaModule {
+    unsafe fn push(&self, l: *mut lua_State) {
+        // functions are stored as references in the registry, pushing the table copies the
+        // function in to the table so the references can be released once the module is dropped
+        unsafe { self.0.push(l) };
+    }
+}
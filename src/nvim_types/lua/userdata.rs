@@ -0,0 +1,519 @@
+use std::{
+    any::TypeId,
+    cell::{Ref, RefCell, RefMut},
+    error::Error,
+    ffi::CStr,
+    marker::PhantomData,
+    sync::Mutex,
+};
+
+use libc::{c_char, c_int};
+use mlua_sys::{
+    LUA_REGISTRYINDEX, LUA_TNONE, LUA_TUSERDATA, lua_State, lua_checkstack, lua_createtable,
+    lua_getmetatable, lua_isnil, lua_newuserdata, lua_pop, lua_pushcfunction, lua_pushnil,
+    lua_pushvalue, lua_rawequal, lua_rawget, lua_rawgeti, lua_remove, lua_setfield,
+    lua_setmetatable, lua_touserdata, lua_type, luaL_ref, luaL_unref,
+};
+use thread_lock::{call_check, get_lua_ptr};
+
+use crate::nvim_types::{AsThinString, Boolean, LuaRef, OwnedThinString};
+
+use super::{
+    FromLua, IntoLua, NvFn,
+    box_fn::{push_callback_ret, register_raw},
+    core::{FromLuaErr, FromLuaMany},
+    utils::handle_callback_err_ret,
+};
+
+/// A Rust type that can be passed to Lua as userdata
+///
+/// The value is stored in a [`RefCell`] so that a method taking `&mut self` returns an error
+/// instead of aliasing when the value is already borrowed.
+///
+/// # Example
+/// ```no_run
+/// use nvimium::nvim_types::{
+///     Error, Integer, OwnedThinString,
+///     lua::{LuaUserData, UserData, UserDataMethods},
+/// };
+///
+/// struct Counter(Integer);
+///
+/// impl LuaUserData for Counter {
+///     fn register(methods: &mut UserDataMethods<Self>) {
+///         methods
+///             .method(c"get", |c: &Counter, ()| Ok::<_, Error>(c.0))
+///             .method_mut(c"add", |c: &mut Counter, n: Integer| {
+///                 c.0 += n;
+///                 Ok::<_, Error>(())
+///             })
+///             .tostring(|c: &Counter| {
+///                 Ok::<_, Error>(OwnedThinString::from(format!("Counter({})", c.0).as_str()))
+///             });
+///     }
+/// }
+///
+/// // `counter:add(2)` in Lua is visible from Rust
+/// let counter = UserData::new(Counter(0));
+/// assert_eq!(counter.borrow().0, 0);
+/// ```
+pub trait LuaUserData: 'static + Sized {
+    /// Registers the methods and metamethods of the type
+    ///
+    /// Called once, the first time a value of the type is created.
+    fn register(methods: &mut UserDataMethods<Self>);
+}
+
+type RawFn = Box<dyn Fn(*mut lua_State) -> c_int>;
+
+/// The methods and metamethods of a [`LuaUserData`] type
+///
+/// Methods are called with the `:` syntax in Lua. The value itself is not passed to the
+/// arguments, so a method taking a single [`Integer`](crate::nvim_types::Integer) is called as
+/// `value:method(1)`.
+pub struct UserDataMethods<T> {
+    methods: Vec<(OwnedThinString, RawFn)>,
+    index: Option<RawFn>,
+    meta: Vec<(&'static CStr, RawFn)>,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T: LuaUserData> UserDataMethods<T> {
+    /// Registers a method that borrows the value
+    pub fn method<
+        TH: AsThinString,
+        A: 'static + FromLuaMany,
+        R: 'static + IntoLua,
+        E: 'static + Error,
+        F: NvFn + Fn(&T, A) -> Result<R, E>,
+    >(
+        &mut self,
+        name: TH,
+        f: F,
+    ) -> &mut Self {
+        let f: RawFn = Box::new(move |l| unsafe {
+            with_self::<T>(l, |l, cell| {
+                let this = match cell.try_borrow() {
+                    Ok(this) => this,
+                    Err(err) => {
+                        handle_callback_err_ret(l, &err);
+                        return 0;
+                    }
+                };
+                call_with_args(l, |arg| f(&this, arg))
+            })
+        });
+        self.methods
+            .push((OwnedThinString::from(name.as_thinstr()), f));
+        self
+    }
+
+    /// Registers a method that mutably borrows the value
+    pub fn method_mut<
+        TH: AsThinString,
+        A: 'static + FromLuaMany,
+        R: 'static + IntoLua,
+        E: 'static + Error,
+        F: NvFn + Fn(&mut T, A) -> Result<R, E>,
+    >(
+        &mut self,
+        name: TH,
+        f: F,
+    ) -> &mut Self {
+        let f: RawFn = Box::new(move |l| unsafe {
+            with_self::<T>(l, |l, cell| {
+                let mut this = match cell.try_borrow_mut() {
+                    Ok(this) => this,
+                    Err(err) => {
+                        handle_callback_err_ret(l, &err);
+                        return 0;
+                    }
+                };
+                call_with_args(l, |arg| f(&mut this, arg))
+            })
+        });
+        self.methods
+            .push((OwnedThinString::from(name.as_thinstr()), f));
+        self
+    }
+
+    /// Sets the `__index` metamethod, called when a key is not a registered method
+    ///
+    /// Without it, indexing a key that is not a method returns nil.
+    pub fn index<
+        K: FromLua,
+        R: 'static + IntoLua,
+        E: 'static + Error,
+        F: NvFn + Fn(&T, K) -> Result<R, E>,
+    >(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.index = Some(Box::new(move |l| unsafe {
+            with_self::<T>(l, |l, cell| {
+                let this = match cell.try_borrow() {
+                    Ok(this) => this,
+                    Err(err) => {
+                        handle_callback_err_ret(l, &err);
+                        return 0;
+                    }
+                };
+                call_with_args(l, |key: Key<K>| f(&this, key.0))
+            })
+        }));
+        self
+    }
+
+    /// Sets the `__newindex` metamethod, called when assigning to a key
+    pub fn newindex<
+        K: FromLua,
+        V: FromLua,
+        E: 'static + Error,
+        F: NvFn + Fn(&mut T, K, V) -> Result<(), E>,
+    >(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.meta.push((
+            c"__newindex",
+            Box::new(move |l| unsafe {
+                with_self::<T>(l, |l, cell| {
+                    let mut this = match cell.try_borrow_mut() {
+                        Ok(this) => this,
+                        Err(err) => {
+                            handle_callback_err_ret(l, &err);
+                            return 0;
+                        }
+                    };
+                    call_with_args(l, |(key, value): (K, V)| f(&mut this, key, value))
+                })
+            }),
+        ));
+        self
+    }
+
+    /// Sets the `__tostring` metamethod, used by `tostring` and `print`
+    pub fn tostring<R: 'static + IntoLua, E: 'static + Error, F: NvFn + Fn(&T) -> Result<R, E>>(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.meta.push((c"__tostring", borrowing_meta(f)));
+        self
+    }
+
+    /// Sets the `__len` metamethod, used by the `#` operator
+    pub fn len<R: 'static + IntoLua, E: 'static + Error, F: NvFn + Fn(&T) -> Result<R, E>>(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.meta.push((c"__len", borrowing_meta(f)));
+        self
+    }
+
+    /// Sets the `__eq` metamethod, used by the `==` and `~=` operators
+    ///
+    /// Lua only calls it when both values are userdata of the same type, values of any other
+    /// type are never equal.
+    pub fn eq<F: NvFn + Fn(&T, &T) -> bool>(&mut self, f: F) -> &mut Self {
+        self.meta.push((
+            c"__eq",
+            Box::new(move |l| unsafe {
+                with_self::<T>(l, |l, cell| {
+                    let Some(other) = to_cell::<T>(l, 1) else {
+                        false.push(l);
+                        return 1;
+                    };
+                    let eq = match (cell.try_borrow(), other.try_borrow()) {
+                        (Ok(this), Ok(other)) => f(&this, &other),
+                        (Err(err), _) | (_, Err(err)) => {
+                            handle_callback_err_ret(l, &err);
+                            return 0;
+                        }
+                    };
+                    (eq as Boolean).push(l);
+                    1
+                })
+            }),
+        ));
+        self
+    }
+}
+
+fn borrowing_meta<
+    T: LuaUserData,
+    R: 'static + IntoLua,
+    E: 'static + Error,
+    F: NvFn + Fn(&T) -> Result<R, E>,
+>(
+    f: F,
+) -> RawFn {
+    Box::new(move |l| unsafe {
+        with_self::<T>(l, |l, cell| {
+            let this = match cell.try_borrow() {
+                Ok(this) => this,
+                Err(err) => {
+                    handle_callback_err_ret(l, &err);
+                    return 0;
+                }
+            };
+            let ret = f(&this);
+            drop(this);
+            push_callback_ret(l, ret)
+        })
+    })
+}
+
+/// Reads a single key at index 1
+///
+/// The blanket [`FromLuaMany`] implementation reads the top of the stack which is the value
+/// rather than the key for some metamethods.
+struct Key<K>(K);
+
+impl<K: FromLua> FromLuaMany for Key<K> {
+    unsafe fn get(l: *mut lua_State, to_pop: &mut i32) -> super::core::Result<Self> {
+        unsafe { K::get(l, 1, to_pop) }.map(Key)
+    }
+}
+
+/// Reads the arguments and calls the function, pushing its return values
+unsafe fn call_with_args<A: FromLuaMany, R: IntoLua, E: Error>(
+    l: *mut lua_State,
+    f: impl FnOnce(A) -> Result<R, E>,
+) -> c_int {
+    unsafe {
+        let mut to_pop = 0;
+        let arg = match A::get(l, &mut to_pop) {
+            Ok(arg) => arg,
+            Err(err) => {
+                handle_callback_err_ret(l, &err);
+                return 0;
+            }
+        };
+        let ret = f(arg);
+        lua_pop(l, to_pop);
+        push_callback_ret(l, ret)
+    }
+}
+
+/// Gets the value at index 1 and removes it from the stack so that the remaining arguments start
+/// at index 1
+unsafe fn with_self<T: LuaUserData>(
+    l: *mut lua_State,
+    f: impl FnOnce(*mut lua_State, &RefCell<T>) -> c_int,
+) -> c_int {
+    unsafe {
+        let Some(cell) = to_cell::<T>(l, 1) else {
+            handle_callback_err_ret(l, &FromLuaErr::IncorrectType.at_arg(1));
+            return 0;
+        };
+
+        // the value is no longer on the stack so keep a reference to it until the call returns
+        lua_pushvalue(l, 1);
+        let _guard = LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX));
+        lua_remove(l, 1);
+        f(l, cell)
+    }
+}
+
+/// Returns the value at `index` if it is a userdata of type `T`
+unsafe fn to_cell<'a, T: LuaUserData>(l: *mut lua_State, index: c_int) -> Option<&'a RefCell<T>> {
+    unsafe {
+        if lua_type(l, index) != LUA_TUSERDATA || lua_checkstack(l, 2) == 0 {
+            return None;
+        }
+        if lua_getmetatable(l, index) == 0 {
+            return None;
+        }
+        lua_rawgeti(l, LUA_REGISTRYINDEX, metatable::<T>(l).into());
+        let is_t = lua_rawequal(l, -1, -2) != 0;
+        lua_pop(l, 2);
+        if !is_t {
+            return None;
+        }
+
+        let ud = lua_touserdata(l, index) as *const Box<RefCell<T>>;
+        Some(&**ud)
+    }
+}
+
+/// The metatables of each [`LuaUserData`] type stored in the registry
+static METATABLES: Mutex<Vec<(TypeId, c_int)>> = Mutex::new(Vec::new());
+
+fn metatable<T: LuaUserData>(l: *mut lua_State) -> c_int {
+    let id = TypeId::of::<T>();
+    if let Some((_, key)) = METATABLES.lock().unwrap().iter().find(|(t, _)| *t == id) {
+        return *key;
+    }
+
+    let mut methods = UserDataMethods {
+        methods: Vec::new(),
+        index: None,
+        meta: Vec::new(),
+        _marker: PhantomData,
+    };
+    T::register(&mut methods);
+
+    unsafe {
+        if lua_checkstack(l, 3) == 0 {
+            panic!("not enough stack space to define userdata metatable");
+        }
+
+        lua_createtable(l, 0, methods.methods.len().try_into().unwrap());
+        for (name, f) in methods.methods {
+            set_fn(l, f, name.as_thinstr().as_ptr() as *const c_char);
+        }
+        let methods_table = LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX));
+
+        lua_createtable(l, 0, methods.meta.len() as c_int + 2);
+        let index = methods.index;
+        let index: RawFn = Box::new(move |l| {
+            // the value is at index 1 and the key at index 2
+            lua_rawgeti(l, LUA_REGISTRYINDEX, methods_table.as_int().into());
+            lua_pushvalue(l, 2);
+            lua_rawget(l, -2);
+            if lua_isnil(l, -1) == 0 {
+                return 1;
+            }
+            lua_pop(l, 2);
+
+            match &index {
+                Some(index) => index(l),
+                None => {
+                    lua_pushnil(l);
+                    1
+                }
+            }
+        });
+        set_fn(l, index, c"__index".as_ptr());
+        for (name, f) in methods.meta {
+            set_fn(l, f, name.as_ptr());
+        }
+        lua_pushcfunction(l, gc::<T>);
+        lua_setfield(l, -2, c"__gc".as_ptr());
+        let key = luaL_ref(l, LUA_REGISTRYINDEX);
+
+        METATABLES.lock().unwrap().push((id, key));
+        key
+    }
+}
+
+/// Sets a field of the table at the top of the stack to the function
+unsafe fn set_fn(l: *mut lua_State, f: RawFn, field: *const c_char) {
+    let key = register_raw(l, f);
+    unsafe {
+        lua_rawgeti(l, LUA_REGISTRYINDEX, key.into());
+        luaL_unref(l, LUA_REGISTRYINDEX, key);
+        lua_setfield(l, -2, field);
+    }
+}
+
+extern "C-unwind" fn gc<T: LuaUserData>(l: *mut lua_State) -> c_int {
+    let ud = unsafe { lua_touserdata(l, 1) } as *mut Box<RefCell<T>>;
+    debug_assert!(!ud.is_null());
+    if !ud.is_null() {
+        unsafe { ud.drop_in_place() };
+    }
+    0
+}
+
+/// A [`LuaUserData`] value owned by Lua
+///
+/// The value is dropped once Lua no longer references it and every [`UserData`] pointing to it
+/// is dropped. Borrows are shared with Lua, a method taking `&mut self` fails while the value is
+/// borrowed from Rust and vice versa.
+pub struct UserData<T> {
+    lref: LuaRef,
+    // the box is never moved so this stays valid as long as the reference is held
+    ptr: *const RefCell<T>,
+}
+
+impl<T: LuaUserData> UserData<T> {
+    /// Moves the value in to Lua
+    ///
+    /// # Panics
+    ///
+    /// If called from a thread that does not have access to Neovim.
+    pub fn new(value: T) -> Self {
+        call_check();
+        let l = get_lua_ptr().as_ptr();
+        let mt = metatable::<T>(l);
+        unsafe {
+            if lua_checkstack(l, 2) == 0 {
+                panic!("not enough stack space to create userdata");
+            }
+            let boxed = Box::new(RefCell::new(value));
+            let ptr = &raw const *boxed;
+            let ud = lua_newuserdata(l, size_of::<Box<RefCell<T>>>()) as *mut Box<RefCell<T>>;
+            ud.write(boxed);
+            lua_rawgeti(l, LUA_REGISTRYINDEX, mt.into());
+            lua_setmetatable(l, -2);
+
+            Self {
+                lref: LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX)),
+                ptr,
+            }
+        }
+    }
+
+    /// Immutably borrows the value
+    ///
+    /// # Panics
+    ///
+    /// If the value is mutably borrowed, including by a method that is currently running.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        unsafe { &*self.ptr }.borrow()
+    }
+
+    /// Mutably borrows the value
+    ///
+    /// # Panics
+    ///
+    /// If the value is borrowed, including by a method that is currently running.
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        unsafe { &*self.ptr }.borrow_mut()
+    }
+
+    pub fn into_luaref(self) -> LuaRef {
+        self.lref
+    }
+}
+
+impl<T> Clone for UserData<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lref: self.lref.clone(),
+            ptr: self.ptr,
+        }
+    }
+}
+
+impl<T: LuaUserData> IntoLua for UserData<T> {
+    unsafe fn push(&self, l: *mut lua_State) {
+        unsafe {
+            lua_checkstack(l, 1);
+            self.lref.push(l);
+        }
+    }
+}
+
+impl<T: LuaUserData> FromLua for UserData<T> {
+    unsafe fn get(l: *mut lua_State, index: c_int, to_pop: &mut i32) -> super::core::Result<Self> {
+        unsafe {
+            if lua_type(l, index) == LUA_TNONE {
+                return Err(FromLuaErr::NotFound);
+            }
+            *to_pop += 1;
+            let Some(cell) = to_cell::<T>(l, index) else {
+                return Err(FromLuaErr::IncorrectType);
+            };
+            if lua_checkstack(l, 1) == 0 {
+                return Err(FromLuaErr::NotEnoughStackSpace);
+            }
+            lua_pushvalue(l, index);
+            Ok(Self {
+                lref: LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX)),
+                ptr: cell,
+            })
+        }
+    }
+}
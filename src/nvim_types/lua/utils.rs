@@ -2,19 +2,21 @@ use std::{error::Error, ffi::CStr, mem::MaybeUninit};
 
 use libc::c_int;
 use mlua_sys::{
-    LUA_TBOOLEAN, LUA_TNUMBER, LUA_TSTRING, lua_State, lua_checkstack, lua_error, lua_getfield,
-    lua_pop, lua_toboolean, lua_tointeger, lua_tolstring,
+    LUA_REGISTRYINDEX, LUA_TBOOLEAN, LUA_TNUMBER, LUA_TSTRING, lua_State, lua_checkstack,
+    lua_error, lua_getfield, lua_getglobal, lua_pop, lua_remove, lua_toboolean, lua_tointeger,
+    lua_tolstring, luaL_ref,
 };
+use thread_lock::{call_check, get_lua_ptr};
 
 use crate::{
     nvim_funcs::global::echo,
     nvim_types::{
-        AsThinString, Boolean, IntoLua, NvString, TRACKED_ARENA, ThinString,
+        AsThinString, Boolean, IntoLua, LuaRef, NvString, TRACKED_ARENA, ThinString,
         func_types::echo::Echo, opts::echo::EchoOpts,
     },
 };
 
-use super::{LuaInteger, core::FromLuaErr};
+use super::{Function, LuaInteger, core::FromLuaErr};
 
 // whenever an error is returned from a callback this should be used
 //
@@ -145,3 +147,23 @@ pub(crate) unsafe fn cb_ret_handle_arena(was_active: bool) {
 pub(crate) unsafe fn cb_entry_set_arena_flag(was_active: bool) {
     unsafe { (&raw mut TRACKED_ARENA.is_nested).write(was_active) };
 }
+
+/// Returns the function at `vim.<path>`
+///
+/// A missing function is returned as `nil` and calling it returns an error.
+pub(crate) fn vim_function(path: &[&CStr]) -> Function {
+    call_check();
+    let mut l = get_lua_ptr();
+    let l = l.as_ptr();
+    unsafe {
+        if lua_checkstack(l, 2) == 0 {
+            panic!("not enough stack space to get vim function");
+        }
+        lua_getglobal(l, c"vim".as_ptr());
+        for name in path {
+            lua_getfield(l, -1, name.as_ptr());
+            lua_remove(l, -2);
+        }
+        Function::from_luaref(LuaRef::new(luaL_ref(l, LUA_REGISTRYINDEX)))
+    }
+}
@@ -7,10 +7,14 @@ pub mod args;
 pub mod core;
 pub mod func_types;
 pub mod iter;
+pub mod json;
 pub mod lua;
+pub mod msgpack;
 pub mod object_subs;
 pub mod opts;
 pub mod returns;
+#[cfg(feature = "serde")]
+pub mod serde;
 
 pub use arena::*;
 pub use core::*;
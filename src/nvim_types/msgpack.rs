@@ -0,0 +1,807 @@
+//! Msgpack encoding and decoding of [`Object`] trees
+//!
+//! [`Buffer`], [`Window`] and [`TabPage`] are encoded as EXT types in the same way Neovim does
+//! over RPC, the payload being the handle encoded as a msgpack integer. Binary values are decoded
+//! as strings and map keys must be strings.
+//!
+//! Decoding is limited by [`DecodeLimits`] so untrusted input cannot exhaust the stack or memory.
+//! [`ObjectView`] decodes without copying the strings when the input can be modified in place.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{dict, nvim_types::{Dict, Object}};
+//!
+//! let state = dict! { "count" = 3, "files" = ["a.rs", "b.rs"] };
+//! let mut bytes = Vec::new();
+//! state.to_msgpack(&mut bytes).unwrap();
+//! assert_eq!(Dict::from_msgpack(&bytes).unwrap(), state);
+//! ```
+
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    marker::PhantomData,
+};
+
+use crate::nvim_types::{
+    Array, Boolean, Buffer, Dict, Float, Integer, KVec, Object, OwnedThinString, TabPage,
+    ThinString, Window, dictionary::KeyValuePair,
+};
+
+pub const EXT_BUFFER: i8 = 0;
+pub const EXT_WINDOW: i8 = 1;
+pub const EXT_TABPAGE: i8 = 2;
+
+#[derive(Debug)]
+pub enum MsgpackError {
+    /// The input ended before a complete value was read
+    Incomplete,
+    /// The input is not valid msgpack or contains a value that cannot be stored in an [`Object`]
+    Invalid(&'static str),
+    /// Arrays and maps are nested deeper than [`DecodeLimits::max_depth`]
+    DepthLimit,
+    /// The value is larger than [`DecodeLimits::max_size`]
+    SizeLimit,
+    /// A [`LuaRef`](crate::nvim_types::LuaRef) cannot be encoded as it is only valid in the
+    /// current Lua state
+    LuaRef,
+    /// Writing the encoded value failed
+    Io(io::Error),
+}
+
+impl Display for MsgpackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Incomplete => f.write_str("unexpected end of msgpack input"),
+            Self::Invalid(reason) => write!(f, "invalid msgpack input: {}", reason),
+            Self::DepthLimit => f.write_str("msgpack input is nested too deeply"),
+            Self::SizeLimit => f.write_str("msgpack input is too large"),
+            Self::LuaRef => f.write_str("a LuaRef cannot be encoded as msgpack"),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for MsgpackError {}
+
+impl From<io::Error> for MsgpackError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Limits applied when decoding
+///
+/// Every element of an array or map takes at least one byte of input so `max_size` also bounds
+/// the memory used by the decoded value.
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeLimits {
+    /// The maximum nesting of arrays and maps
+    pub max_depth: usize,
+    /// The maximum number of bytes a single value may take up in the input
+    pub max_size: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            max_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl Object {
+    /// Writes the msgpack encoding of the object
+    ///
+    /// Many small writes are performed so `writer` should be buffered.
+    pub fn to_msgpack<W: Write>(&self, writer: &mut W) -> Result<(), MsgpackError> {
+        encode(self, writer)
+    }
+
+    /// Decodes an object that takes up the entire buffer with the default [`DecodeLimits`]
+    pub fn from_msgpack(buf: &[u8]) -> Result<Self, MsgpackError> {
+        Self::from_msgpack_with_limits(buf, &DecodeLimits::default())
+    }
+
+    /// Decodes an object that takes up the entire buffer
+    pub fn from_msgpack_with_limits(
+        buf: &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, MsgpackError> {
+        match decode_with_limits(buf, limits)? {
+            (obj, read) if read == buf.len() => Ok(obj),
+            _ => Err(MsgpackError::Invalid("trailing bytes after the value")),
+        }
+    }
+}
+
+impl Array {
+    /// Writes the msgpack encoding of the array
+    pub fn to_msgpack<W: Write>(&self, writer: &mut W) -> Result<(), MsgpackError> {
+        encode_array(self, writer)
+    }
+
+    /// Decodes an array that takes up the entire buffer with the default [`DecodeLimits`]
+    pub fn from_msgpack(buf: &[u8]) -> Result<Self, MsgpackError> {
+        Self::from_msgpack_with_limits(buf, &DecodeLimits::default())
+    }
+
+    /// Decodes an array that takes up the entire buffer
+    pub fn from_msgpack_with_limits(
+        buf: &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, MsgpackError> {
+        Object::from_msgpack_with_limits(buf, limits)?
+            .into_array()
+            .ok_or(MsgpackError::Invalid("expected an array"))
+    }
+}
+
+impl Dict {
+    /// Writes the msgpack encoding of the dictionary
+    pub fn to_msgpack<W: Write>(&self, writer: &mut W) -> Result<(), MsgpackError> {
+        encode_dict(self, writer)
+    }
+
+    /// Decodes a dictionary that takes up the entire buffer with the default [`DecodeLimits`]
+    pub fn from_msgpack(buf: &[u8]) -> Result<Self, MsgpackError> {
+        Self::from_msgpack_with_limits(buf, &DecodeLimits::default())
+    }
+
+    /// Decodes a dictionary that takes up the entire buffer
+    pub fn from_msgpack_with_limits(
+        buf: &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, MsgpackError> {
+        Object::from_msgpack_with_limits(buf, limits)?
+            .into_dict()
+            .ok_or(MsgpackError::Invalid("expected a map"))
+    }
+}
+
+fn encode<W: Write>(obj: &Object, w: &mut W) -> Result<(), MsgpackError> {
+    match obj {
+        Object::Null => w.write_all(&[0xc0])?,
+        Object::Bool(b) => w.write_all(&[if *b { 0xc3 } else { 0xc2 }])?,
+        Object::Integer(i) => encode_int(*i, w)?,
+        Object::Float(f) => {
+            w.write_all(&[0xcb])?;
+            w.write_all(&f.to_be_bytes())?;
+        }
+        Object::String(s) => encode_str(s.as_thinstr().as_slice(), w)?,
+        Object::Array(arr) => encode_array(arr, w)?,
+        Object::Dict(d) => encode_dict(d, w)?,
+        Object::LuaRef(_) => return Err(MsgpackError::LuaRef),
+        Object::Buffer(b) => encode_ext(EXT_BUFFER, b.as_int().into(), w)?,
+        Object::Window(win) => encode_ext(EXT_WINDOW, win.as_int().into(), w)?,
+        Object::TabPage(t) => encode_ext(EXT_TABPAGE, t.as_int().into(), w)?,
+    }
+
+    Ok(())
+}
+
+fn encode_array<W: Write>(arr: &Array, w: &mut W) -> Result<(), MsgpackError> {
+    encode_len(arr.len(), [0x90, 0xdc, 0xdd], 16, w)?;
+    for obj in arr.iter() {
+        encode(obj, w)?;
+    }
+    Ok(())
+}
+
+fn encode_dict<W: Write>(d: &Dict, w: &mut W) -> Result<(), MsgpackError> {
+    encode_len(d.len(), [0x80, 0xde, 0xdf], 16, w)?;
+    for kv in d.iter() {
+        encode_str(kv.key.as_thinstr().as_slice(), w)?;
+        encode(&kv.object, w)?;
+    }
+    Ok(())
+}
+
+/// Encodes the integer in the smallest representation
+fn encode_int<W: Write>(i: Integer, w: &mut W) -> io::Result<()> {
+    if i >= 0 {
+        match i {
+            0..=0x7f => w.write_all(&[i as u8]),
+            0x80..=0xff => w.write_all(&[0xcc, i as u8]),
+            0x100..=0xffff => {
+                w.write_all(&[0xcd])?;
+                w.write_all(&(i as u16).to_be_bytes())
+            }
+            0x10000..=0xffff_ffff => {
+                w.write_all(&[0xce])?;
+                w.write_all(&(i as u32).to_be_bytes())
+            }
+            _ => {
+                w.write_all(&[0xcf])?;
+                w.write_all(&(i as u64).to_be_bytes())
+            }
+        }
+    } else if i >= -32 {
+        w.write_all(&[i as i8 as u8])
+    } else if i >= i8::MIN.into() {
+        w.write_all(&[0xd0, i as i8 as u8])
+    } else if i >= i16::MIN.into() {
+        w.write_all(&[0xd1])?;
+        w.write_all(&(i as i16).to_be_bytes())
+    } else if i >= i32::MIN.into() {
+        w.write_all(&[0xd2])?;
+        w.write_all(&(i as i32).to_be_bytes())
+    } else {
+        w.write_all(&[0xd3])?;
+        w.write_all(&i.to_be_bytes())
+    }
+}
+
+fn encode_str<W: Write>(s: &[u8], w: &mut W) -> io::Result<()> {
+    if s.len() < 32 {
+        w.write_all(&[0xa0 | s.len() as u8])?;
+    } else if s.len() <= u8::MAX as usize {
+        w.write_all(&[0xd9, s.len() as u8])?;
+    } else {
+        encode_len(s.len(), [0, 0xda, 0xdb], 0, w)?;
+    }
+    w.write_all(s)
+}
+
+/// Encodes the header of a container or string
+///
+/// `markers` holds the fix, 16 bit and 32 bit markers. Lengths below `fix_max` use the fix marker.
+fn encode_len<W: Write>(len: usize, markers: [u8; 3], fix_max: usize, w: &mut W) -> io::Result<()> {
+    if len < fix_max {
+        w.write_all(&[markers[0] | len as u8])
+    } else if len <= u16::MAX as usize {
+        w.write_all(&[markers[1]])?;
+        w.write_all(&(len as u16).to_be_bytes())
+    } else {
+        let len = u32::try_from(len).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "msgpack values are limited to 2^32 - 1 elements",
+            )
+        })?;
+        w.write_all(&[markers[2]])?;
+        w.write_all(&len.to_be_bytes())
+    }
+}
+
+fn encode_ext<W: Write>(ty: i8, handle: Integer, w: &mut W) -> io::Result<()> {
+    let mut payload = Vec::with_capacity(9);
+    encode_int(handle, &mut payload)?;
+    match payload.len() {
+        1 => w.write_all(&[0xd4])?,
+        2 => w.write_all(&[0xd5])?,
+        4 => w.write_all(&[0xd6])?,
+        8 => w.write_all(&[0xd7])?,
+        len => w.write_all(&[0xc7, len as u8])?,
+    }
+    w.write_all(&[ty as u8])?;
+    w.write_all(&payload)
+}
+
+/// Decodes a single object from the start of the buffer with the default [`DecodeLimits`]
+///
+/// Returns the object and the number of bytes read so values can be read from a stream.
+/// [`MsgpackError::Incomplete`] is returned if more input is needed to decode the object.
+pub fn decode(buf: &[u8]) -> Result<(Object, usize), MsgpackError> {
+    decode_with_limits(buf, &DecodeLimits::default())
+}
+
+/// Decodes a single object from the start of the buffer
+///
+/// See [`decode`].
+pub fn decode_with_limits(
+    buf: &[u8],
+    limits: &DecodeLimits,
+) -> Result<(Object, usize), MsgpackError> {
+    // the buffer is never written to when decoding an `Object`
+    let mut decoder = Decoder::new(buf.as_ptr().cast_mut(), buf.len(), limits);
+    let obj = decoder.node::<Object>(0)?;
+    Ok((obj, decoder.pos))
+}
+
+/// A decoded msgpack value that borrows its strings from the input
+///
+/// Decoding moves every string one byte towards the start of the buffer, overwriting the last
+/// byte of its header, and null terminates it so it can be used as a [`ThinString`] without being
+/// copied. The buffer no longer contains valid msgpack afterwards. The value is validated before
+/// any string is moved so the buffer is left untouched when decoding fails.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjectView<'a> {
+    Null,
+    Bool(Boolean),
+    Integer(Integer),
+    Float(Float),
+    String(ThinString<'a>),
+    Array(Vec<ObjectView<'a>>),
+    Dict(Vec<(ThinString<'a>, ObjectView<'a>)>),
+    Buffer(Buffer),
+    Window(Window),
+    TabPage(TabPage),
+}
+
+impl<'a> ObjectView<'a> {
+    /// Decodes a single value from the start of the buffer with the default [`DecodeLimits`]
+    ///
+    /// Returns the value and the number of bytes read.
+    pub fn from_msgpack(buf: &'a mut [u8]) -> Result<(Self, usize), MsgpackError> {
+        Self::from_msgpack_with_limits(buf, &DecodeLimits::default())
+    }
+
+    /// Decodes a single value from the start of the buffer
+    ///
+    /// Returns the value and the number of bytes read. On [`MsgpackError::Incomplete`] the buffer
+    /// is unchanged so decoding can be retried once more input is read in to it.
+    pub fn from_msgpack_with_limits(
+        buf: &'a mut [u8],
+        limits: &DecodeLimits,
+    ) -> Result<(Self, usize), MsgpackError> {
+        // the scan does not write to the buffer, once it succeeds decoding cannot fail
+        let mut decoder = Decoder::new(buf.as_mut_ptr(), buf.len(), limits);
+        decoder.node::<Scanned>(0)?;
+        let mut decoder = Decoder::new(buf.as_mut_ptr(), buf.len(), limits);
+        let view = decoder.node::<Self>(0)?;
+        Ok((view, decoder.pos))
+    }
+
+    /// Copies the value in to an [`Object`]
+    pub fn to_object(&self) -> Object {
+        match self {
+            Self::Null => Object::Null,
+            Self::Bool(b) => Object::Bool(*b),
+            Self::Integer(i) => Object::Integer(*i),
+            Self::Float(f) => Object::Float(*f),
+            Self::String(s) => Object::String(OwnedThinString::from(*s)),
+            Self::Array(arr) => Object::Array(Array::from(KVec::from_iter(
+                arr.iter().map(Self::to_object),
+            ))),
+            Self::Dict(d) => Object::Dict(Dict::from_iter(
+                d.iter()
+                    .map(|(k, v)| (OwnedThinString::from(*k), v.to_object())),
+            )),
+            Self::Buffer(b) => Object::Buffer(*b),
+            Self::Window(w) => Object::Window(*w),
+            Self::TabPage(t) => Object::TabPage(*t),
+        }
+    }
+}
+
+/// A value that can be built by the [`Decoder`]
+trait Node<'a>: Sized {
+    type Str;
+
+    /// Reads a string of `len` bytes at the current position
+    fn string(dec: &mut Decoder<'a>, len: usize) -> Result<Self::Str, MsgpackError>;
+    fn from_str(s: Self::Str) -> Self;
+    fn into_str(self) -> Option<Self::Str>;
+    /// Converts a decoded value that is not a string or container
+    fn scalar(obj: Object) -> Self;
+    fn array(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError>;
+    fn map(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError>;
+}
+
+impl<'a> Node<'a> for Object {
+    type Str = OwnedThinString;
+
+    fn string(dec: &mut Decoder<'a>, len: usize) -> Result<Self::Str, MsgpackError> {
+        Ok(OwnedThinString::from(dec.take(len)?))
+    }
+
+    fn from_str(s: Self::Str) -> Self {
+        Object::String(s)
+    }
+
+    fn into_str(self) -> Option<Self::Str> {
+        self.into_string()
+    }
+
+    fn scalar(obj: Object) -> Self {
+        obj
+    }
+
+    fn array(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError> {
+        let mut arr = KVec::with_capacity(len.min(dec.remaining()));
+        for _ in 0..len {
+            arr.push(dec.node(depth + 1)?);
+        }
+        Ok(Object::Array(Array::from(arr)))
+    }
+
+    fn map(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError> {
+        let mut d = KVec::with_capacity(len.min(dec.remaining() / 2));
+        for _ in 0..len {
+            let (key, object) = dec.pair(depth)?;
+            d.push(KeyValuePair { key, object });
+        }
+        Ok(Object::Dict(Dict::from(d)))
+    }
+}
+
+impl<'a> Node<'a> for ObjectView<'a> {
+    type Str = ThinString<'a>;
+
+    fn string(dec: &mut Decoder<'a>, len: usize) -> Result<Self::Str, MsgpackError> {
+        // SAFETY: the decoder was created from a mutable slice in `ObjectView::from_msgpack`.
+        // Every string is preceded by at least one byte of header that has already been read and
+        // is not borrowed, and the bytes after the string have not been read yet.
+        let start = dec.pos;
+        dec.take(len)?;
+        unsafe {
+            let dst = dec.ptr.add(start - 1);
+            core::ptr::copy(dst.add(1), dst, len);
+            dst.add(len).write(0);
+            Ok(ThinString::new(len, dst.cast()))
+        }
+    }
+
+    fn from_str(s: Self::Str) -> Self {
+        ObjectView::String(s)
+    }
+
+    fn into_str(self) -> Option<Self::Str> {
+        match self {
+            ObjectView::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn scalar(obj: Object) -> Self {
+        match obj {
+            Object::Bool(b) => ObjectView::Bool(b),
+            Object::Integer(i) => ObjectView::Integer(i),
+            Object::Float(f) => ObjectView::Float(f),
+            Object::Buffer(b) => ObjectView::Buffer(b),
+            Object::Window(w) => ObjectView::Window(w),
+            Object::TabPage(t) => ObjectView::TabPage(t),
+            _ => ObjectView::Null,
+        }
+    }
+
+    fn array(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError> {
+        let mut arr = Vec::with_capacity(len.min(dec.remaining()));
+        for _ in 0..len {
+            arr.push(dec.node(depth + 1)?);
+        }
+        Ok(ObjectView::Array(arr))
+    }
+
+    fn map(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError> {
+        let mut d = Vec::with_capacity(len.min(dec.remaining() / 2));
+        for _ in 0..len {
+            d.push(dec.pair(depth)?);
+        }
+        Ok(ObjectView::Dict(d))
+    }
+}
+
+/// Validates a value without building it, only keeping track of what is a string for map keys
+#[derive(Clone, Copy)]
+enum Scanned {
+    String,
+    Other,
+}
+
+impl<'a> Node<'a> for Scanned {
+    type Str = ();
+
+    fn string(dec: &mut Decoder<'a>, len: usize) -> Result<Self::Str, MsgpackError> {
+        dec.take(len).map(drop)
+    }
+
+    fn from_str(_: Self::Str) -> Self {
+        Self::String
+    }
+
+    fn into_str(self) -> Option<Self::Str> {
+        matches!(self, Self::String).then_some(())
+    }
+
+    fn scalar(_: Object) -> Self {
+        Self::Other
+    }
+
+    fn array(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError> {
+        for _ in 0..len {
+            dec.node::<Self>(depth + 1)?;
+        }
+        Ok(Self::Other)
+    }
+
+    fn map(dec: &mut Decoder<'a>, len: usize, depth: usize) -> Result<Self, MsgpackError> {
+        for _ in 0..len {
+            dec.pair::<Self>(depth)?;
+        }
+        Ok(Self::Other)
+    }
+}
+
+struct Decoder<'a> {
+    ptr: *mut u8,
+    /// The length of the buffer after applying [`DecodeLimits::max_size`]
+    len: usize,
+    /// Whether the buffer was shortened to apply [`DecodeLimits::max_size`]
+    truncated: bool,
+    max_depth: usize,
+    pos: usize,
+    __p: PhantomData<&'a [u8]>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(ptr: *mut u8, len: usize, limits: &DecodeLimits) -> Self {
+        Self {
+            ptr,
+            len: len.min(limits.max_size),
+            truncated: len > limits.max_size,
+            max_depth: limits.max_depth,
+            pos: 0,
+            __p: PhantomData,
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], MsgpackError> {
+        match self.pos.checked_add(n) {
+            Some(end) if end <= self.len => {
+                let bytes = unsafe { core::slice::from_raw_parts(self.ptr.add(self.pos), n) };
+                self.pos = end;
+                Ok(bytes)
+            }
+            _ if self.truncated => Err(MsgpackError::SizeLimit),
+            _ => Err(MsgpackError::Incomplete),
+        }
+    }
+
+    fn be<const N: usize>(&mut self) -> Result<[u8; N], MsgpackError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn len(
+        &mut self,
+        marker: u8,
+        fix_mask: u8,
+        [l8, l16, l32]: [u8; 3],
+    ) -> Result<usize, MsgpackError> {
+        Ok(match marker {
+            m if m == l8 => self.be::<1>()?[0] as usize,
+            m if m == l16 => u16::from_be_bytes(self.be()?) as usize,
+            m if m == l32 => u32::from_be_bytes(self.be()?) as usize,
+            m => (m & fix_mask) as usize,
+        })
+    }
+
+    /// The remaining bytes, every element of a container takes at least one byte
+    fn remaining(&self) -> usize {
+        self.len - self.pos
+    }
+
+    fn pair<N: Node<'a>>(&mut self, depth: usize) -> Result<(N::Str, N), MsgpackError> {
+        let key = self
+            .node::<N>(depth + 1)?
+            .into_str()
+            .ok_or(MsgpackError::Invalid("map keys must be strings"))?;
+        Ok((key, self.node(depth + 1)?))
+    }
+
+    fn node<N: Node<'a>>(&mut self, depth: usize) -> Result<N, MsgpackError> {
+        if depth > self.max_depth {
+            return Err(MsgpackError::DepthLimit);
+        }
+
+        let marker = self.be::<1>()?[0];
+        let scalar = match marker {
+            0x00..=0x7f => Object::Integer(marker.into()),
+            0xe0..=0xff => Object::Integer((marker as i8).into()),
+            0xc0 => Object::Null,
+            0xc2 => Object::Bool(false),
+            0xc3 => Object::Bool(true),
+            0xcc => Object::Integer(self.be::<1>()?[0].into()),
+            0xcd => Object::Integer(u16::from_be_bytes(self.be()?).into()),
+            0xce => Object::Integer(u32::from_be_bytes(self.be()?).into()),
+            0xcf => Object::Integer(
+                Integer::try_from(u64::from_be_bytes(self.be()?))
+                    .map_err(|_| MsgpackError::Invalid("integer does not fit in an i64"))?,
+            ),
+            0xd0 => Object::Integer((self.be::<1>()?[0] as i8).into()),
+            0xd1 => Object::Integer(i16::from_be_bytes(self.be()?).into()),
+            0xd2 => Object::Integer(i32::from_be_bytes(self.be()?).into()),
+            0xd3 => Object::Integer(i64::from_be_bytes(self.be()?)),
+            0xca => Object::Float(f32::from_be_bytes(self.be()?).into()),
+            0xcb => Object::Float(f64::from_be_bytes(self.be()?)),
+            0xa0..=0xbf | 0xd9..=0xdb => {
+                let len = self.len(marker, 0x1f, [0xd9, 0xda, 0xdb])?;
+                return Ok(N::from_str(N::string(self, len)?));
+            }
+            0xc4..=0xc6 => {
+                let len = self.len(marker, 0, [0xc4, 0xc5, 0xc6])?;
+                return Ok(N::from_str(N::string(self, len)?));
+            }
+            0x90..=0x9f | 0xdc | 0xdd => {
+                let len = self.len(marker, 0x0f, [0, 0xdc, 0xdd])?;
+                return N::array(self, len, depth);
+            }
+            0x80..=0x8f | 0xde | 0xdf => {
+                let len = self.len(marker, 0x0f, [0, 0xde, 0xdf])?;
+                return N::map(self, len, depth);
+            }
+            0xd4..=0xd8 | 0xc7..=0xc9 => {
+                let len = match marker {
+                    0xd4..=0xd8 => 1 << (marker - 0xd4),
+                    _ => self.len(marker, 0, [0xc7, 0xc8, 0xc9])?,
+                };
+                let ty = self.be::<1>()?[0] as i8;
+                self.ext(ty, len)?
+            }
+            0xc1 => return Err(MsgpackError::Invalid("reserved marker 0xc1")),
+        };
+
+        Ok(N::scalar(scalar))
+    }
+
+    fn ext(&mut self, ty: i8, len: usize) -> Result<Object, MsgpackError> {
+        let payload = self.take(len)?;
+        let handle = match decode(payload) {
+            Ok((Object::Integer(i), read)) if read == len => i32::try_from(i)
+                .map_err(|_| MsgpackError::Invalid("handle does not fit in an i32"))?,
+            _ => return Err(MsgpackError::Invalid("EXT payload is not an integer")),
+        };
+        Ok(match ty {
+            EXT_BUFFER => Object::Buffer(Buffer::new(handle)),
+            EXT_WINDOW => Object::Window(Window::new(handle)),
+            EXT_TABPAGE => Object::TabPage(TabPage::new(handle)),
+            _ => return Err(MsgpackError::Invalid("unknown EXT type")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        array, dict,
+        nvim_types::{Array, Buffer, Dict, KVec, Object, OwnedThinString, TabPage, Window},
+    };
+
+    use super::{DecodeLimits, MsgpackError, ObjectView, decode, decode_with_limits};
+
+    fn round_trip(obj: Object) -> Vec<u8> {
+        let mut buf = Vec::new();
+        obj.to_msgpack(&mut buf).unwrap();
+        assert_eq!(Object::from_msgpack(&buf).unwrap(), obj);
+        let mut copy = buf.clone();
+        let (view, read) = ObjectView::from_msgpack(&mut copy).unwrap();
+        assert_eq!((view.to_object(), read), (obj, buf.len()));
+        buf
+    }
+
+    #[test]
+    fn integers() {
+        for (i, len) in [
+            (0, 1),
+            (127, 1),
+            (128, 2),
+            (-32, 1),
+            (-33, 2),
+            (-129, 3),
+            (65536, 5),
+            (i64::MAX, 9),
+            (i64::MIN, 9),
+        ] {
+            assert_eq!(round_trip(Object::Integer(i)).len(), len);
+        }
+        assert!(matches!(
+            decode(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            Err(MsgpackError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn containers() {
+        let long = "a".repeat(300);
+        let d = Object::Dict(dict! { "key" = [1, 2, 3], "empty" = [] });
+        round_trip(Object::Array(array![
+            true,
+            1.5,
+            "a string",
+            "",
+            (long.as_str()),
+            (d)
+        ]));
+        let mut arr = KVec::from_iter([Object::Null]);
+        arr.extend_from_slice(&[const { Object::Integer(0) }; 20]);
+        round_trip(Object::Array(Array::from(arr)));
+
+        let d = dict! { "a" = 1 };
+        let mut buf = Vec::new();
+        d.to_msgpack(&mut buf).unwrap();
+        assert_eq!(Dict::from_msgpack(&buf).unwrap(), d);
+        assert!(Array::from_msgpack(&buf).is_err());
+    }
+
+    #[test]
+    fn ext_handles() {
+        let buf = round_trip(Object::Buffer(Buffer::new(1)));
+        assert_eq!(buf, [0xd4, 0, 1]);
+        round_trip(Object::Window(Window::new(1000)));
+        round_trip(Object::TabPage(TabPage::new(70000)));
+    }
+
+    #[test]
+    fn view_is_null_terminated() {
+        let mut buf = Vec::new();
+        Object::Array(array!["one", "two"])
+            .to_msgpack(&mut buf)
+            .unwrap();
+        let (view, _) = ObjectView::from_msgpack(&mut buf).unwrap();
+        let ObjectView::Array(arr) = view else {
+            panic!("expected an array");
+        };
+        for (view, s) in arr.iter().zip(["one", "two"]) {
+            let ObjectView::String(th) = view else {
+                panic!("expected a string");
+            };
+            assert_eq!(th.as_slice_with_null(), format!("{}\0", s).as_bytes());
+        }
+    }
+
+    #[test]
+    fn view_keeps_buffer_on_error() {
+        let mut encoded = Vec::new();
+        Object::Array(array!["one", "two", 3])
+            .to_msgpack(&mut encoded)
+            .unwrap();
+        // the strings are complete but the value is not
+        let mut buf = encoded[..encoded.len() - 1].to_vec();
+        assert!(matches!(
+            ObjectView::from_msgpack(&mut buf),
+            Err(MsgpackError::Incomplete)
+        ));
+        assert_eq!(buf, encoded[..encoded.len() - 1]);
+        buf.push(encoded[encoded.len() - 1]);
+        let (view, read) = ObjectView::from_msgpack(&mut buf).unwrap();
+        assert_eq!(
+            (view.to_object(), read),
+            (Object::Array(array!["one", "two", 3]), encoded.len())
+        );
+
+        // a map key that is not a string is only found after the strings before it
+        let mut buf = vec![0x82, 0xa1, b'a', 0xa1, b'b', 0x01, 0xc0];
+        let copy = buf.clone();
+        assert!(matches!(
+            ObjectView::from_msgpack(&mut buf),
+            Err(MsgpackError::Invalid(_))
+        ));
+        assert_eq!(buf, copy);
+    }
+
+    #[test]
+    fn limits() {
+        let mut buf = Vec::new();
+        Object::String(OwnedThinString::from("incomplete"))
+            .to_msgpack(&mut buf)
+            .unwrap();
+        for i in 0..buf.len() {
+            assert!(matches!(decode(&buf[..i]), Err(MsgpackError::Incomplete)));
+        }
+        assert!(Object::from_msgpack(&[0xc0, 0xc0]).is_err());
+
+        let limits = DecodeLimits {
+            max_depth: 4,
+            max_size: 4,
+        };
+        assert!(matches!(
+            decode_with_limits(&buf, &limits),
+            Err(MsgpackError::SizeLimit)
+        ));
+        let limits = DecodeLimits {
+            max_depth: 4,
+            ..Default::default()
+        };
+        assert!(matches!(
+            decode_with_limits(&[0x91; 200], &limits),
+            Err(MsgpackError::DepthLimit)
+        ));
+        // a huge length must not allocate before the data is read
+        assert!(matches!(
+            decode(&[0xdd, 0xff, 0xff, 0xff, 0xff]),
+            Err(MsgpackError::Incomplete)
+        ));
+    }
+}
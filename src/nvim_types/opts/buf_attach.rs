@@ -38,6 +38,17 @@ impl BufAttachOpts {
     >(
         &mut self,
         lines: F,
+    ) -> &mut Self {
+        self.local_on_lines(lines)
+    }
+
+    /// Same as [`BufAttachOpts::on_lines`] for callbacks that are only used on the main thread
+    pub(crate) fn local_on_lines<
+        E: 'static + Error,
+        F: 'static + for<'a> Fn(BufOnLinesArgs<'a>) -> Result<Boolean, E> + Unpin,
+    >(
+        &mut self,
+        lines: F,
     ) -> &mut Self {
         const MASK: u64 = 1 << builder::MASK_OFFSETS[0];
         if self.mask & MASK == MASK {
@@ -55,6 +66,17 @@ impl BufAttachOpts {
     >(
         &mut self,
         bytes: F,
+    ) -> &mut Self {
+        self.local_on_bytes(bytes)
+    }
+
+    /// Same as [`BufAttachOpts::on_bytes`] for callbacks that are only used on the main thread
+    pub(crate) fn local_on_bytes<
+        E: 'static + Error,
+        F: 'static + for<'a> Fn(BufOnBytesArgs<'a>) -> Result<Boolean, E> + Unpin,
+    >(
+        &mut self,
+        bytes: F,
     ) -> &mut Self {
         const MASK: u64 = 1 << builder::MASK_OFFSETS[1];
         if self.mask & MASK == MASK {
@@ -72,6 +94,17 @@ impl BufAttachOpts {
     >(
         &mut self,
         changedtick: F,
+    ) -> &mut Self {
+        self.local_on_changedtick(changedtick)
+    }
+
+    /// Same as [`BufAttachOpts::on_changedtick`] for callbacks that are only used on the main thread
+    pub(crate) fn local_on_changedtick<
+        E: 'static + Error,
+        F: 'static + for<'a> Fn(BufOnChangedTickArgs<'a>) -> Result<Boolean, E> + Unpin,
+    >(
+        &mut self,
+        changedtick: F,
     ) -> &mut Self {
         const MASK: u64 = 1 << builder::MASK_OFFSETS[2];
         if self.mask & MASK == MASK {
@@ -90,6 +123,17 @@ impl BufAttachOpts {
     >(
         &mut self,
         detach: F,
+    ) -> &mut Self {
+        self.local_on_detach(detach)
+    }
+
+    /// Same as [`BufAttachOpts::on_detach`] for callbacks that are only used on the main thread
+    pub(crate) fn local_on_detach<
+        E: 'static + Error,
+        F: 'static + for<'a> Fn(BufOnDetach<'a>) -> Result<(), E> + Unpin,
+    >(
+        &mut self,
+        detach: F,
     ) -> &mut Self {
         const MASK: u64 = 1 << builder::MASK_OFFSETS[3];
         if self.mask & MASK == MASK {
@@ -107,6 +151,17 @@ impl BufAttachOpts {
     >(
         &mut self,
         reload: F,
+    ) -> &mut Self {
+        self.local_on_reload(reload)
+    }
+
+    /// Same as [`BufAttachOpts::on_reload`] for callbacks that are only used on the main thread
+    pub(crate) fn local_on_reload<
+        E: 'static + Error,
+        F: 'static + for<'a> Fn(BufOnReload<'a>) -> Result<(), E> + Unpin,
+    >(
+        &mut self,
+        reload: F,
     ) -> &mut Self {
         const MASK: u64 = 1 << builder::MASK_OFFSETS[4];
         if self.mask & MASK == MASK {
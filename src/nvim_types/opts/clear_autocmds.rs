@@ -0,0 +1,26 @@
+use crate::{
+    macros::{
+        masked_builder::masked_builder, obj_ref_setters::obj_ref_setters,
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{Buffer, object::ObjectRef},
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct ClearAutocmdsOpts<'a> {
+        buffer: Buffer,
+        #[builder(skip)]
+        event: ObjectRef<'a>,
+        #[builder(skip)]
+        group: ObjectRef<'a>,
+        #[builder(skip)]
+        pattern: ObjectRef<'a>,
+    }
+}
+
+zeroed_default!(ClearAutocmdsOpts<'_>);
+
+impl<'a> ClearAutocmdsOpts<'a> {
+    obj_ref_setters!(1 => event, 3 => pattern; group = 2);
+}
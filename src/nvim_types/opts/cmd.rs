@@ -0,0 +1,158 @@
+use std::mem::MaybeUninit;
+
+use crate::{
+    macros::{
+        masked_builder::{assign_field, masked_builder},
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{
+        Array, AsThinString, Boolean, Integer, KVec, Object, OwnedThinString, ThinString,
+        opts::{cmd_magic::CmdMagic, cmd_mods::CmdMods},
+        returns::cmd::{CmdInfo, CmdRange},
+    },
+};
+
+masked_builder!(
+    #[repr(C)]
+    pub struct Cmd<'a> {
+        #[builder(nv_str)]
+        cmd: ThinString<'a>,
+        #[builder(skip)]
+        range: Array,
+        count: Integer,
+        #[builder(nv_str)]
+        reg: ThinString<'a>,
+        bang: Boolean,
+        #[builder(skip)]
+        args: Array,
+        magic: CmdMagic,
+        mods: CmdMods<'a>,
+        #[builder(skip)]
+        nargs: Object,
+        #[builder(skip)]
+        addr: Object,
+        #[builder(skip)]
+        nextcmd: Object,
+    }
+);
+
+zeroed_default!(Cmd<'_>);
+
+impl<'a> Cmd<'a> {
+    /// Sets the line or line range the command operates on
+    pub fn range(&mut self, range: CmdRange) -> &mut Self {
+        let range = match range {
+            CmdRange::Line(line) => Array::from([Object::Integer(line)].as_slice()),
+            CmdRange::Range(start, end) => {
+                Array::from([Object::Integer(start), Object::Integer(end)].as_slice())
+            }
+        };
+        unsafe {
+            assign_field(
+                1,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.range,
+                range,
+            )
+        };
+        self
+    }
+
+    /// Sets the arguments passed to the command
+    ///
+    /// Each argument is passed as is, file names do not need to be escaped.
+    pub fn args<TH: AsThinString>(&mut self, args: &[TH]) -> &mut Self {
+        let args = Array::from(
+            args.iter()
+                .map(|arg| Object::String(OwnedThinString::from(arg.as_thinstr())))
+                .collect::<KVec<Object>>(),
+        );
+        unsafe {
+            assign_field(
+                5,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.args,
+                args,
+            )
+        };
+        self
+    }
+
+    #[inline]
+    fn get_field<'b, T>(&self, idx: usize, field: &'b MaybeUninit<T>) -> Option<&'b T> {
+        let mask: u64 = 1 << builder::MASK_OFFSETS[idx];
+        (self.mask & mask == mask).then(|| unsafe { field.assume_init_ref() })
+    }
+
+    pub(crate) fn to_info(&self) -> CmdInfo {
+        let string = |o: &Object| {
+            o.as_string()
+                .map(|s| s.as_thinstr())
+                .filter(|s| !s.is_empty())
+                .map(OwnedThinString::from)
+        };
+
+        CmdInfo {
+            cmd: self
+                .get_field(0, &self.cmd)
+                .map(|c| OwnedThinString::from(*c))
+                .unwrap_or_default(),
+            range: self.get_field(1, &self.range).and_then(|r| {
+                match (
+                    r.first().and_then(Object::as_int),
+                    r.get(1).and_then(Object::as_int),
+                ) {
+                    (Some(start), Some(end)) => Some(CmdRange::Range(start, end)),
+                    (Some(line), None) => Some(CmdRange::Line(line)),
+                    _ => None,
+                }
+            }),
+            count: self.get_field(2, &self.count).copied(),
+            reg: self
+                .get_field(3, &self.reg)
+                .filter(|r| !r.is_empty())
+                .map(|r| OwnedThinString::from(*r)),
+            bang: self.get_field(4, &self.bang).copied().unwrap_or_default(),
+            args: self
+                .get_field(5, &self.args)
+                .map(|args| {
+                    args.iter()
+                        .filter_map(|a| {
+                            a.as_string().map(|s| OwnedThinString::from(s.as_thinstr()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            magic: self
+                .get_field(6, &self.magic)
+                .map(CmdMagic::to_info)
+                .unwrap_or_default(),
+            mods: self
+                .get_field(7, &self.mods)
+                .map(CmdMods::to_info)
+                .unwrap_or_default(),
+            nargs: self.get_field(8, &self.nargs).and_then(|o| match o {
+                Object::Integer(i) => Some(OwnedThinString::from(i.to_string().as_str())),
+                o => string(o),
+            }),
+            addr: self.get_field(9, &self.addr).and_then(string),
+            nextcmd: self.get_field(10, &self.nextcmd).and_then(string),
+        }
+    }
+}
+
+// neovim only reads the values so the owned fields are still ours to drop
+impl Drop for Cmd<'_> {
+    fn drop(&mut self) {
+        const RANGE_MASK: u64 = 1 << builder::MASK_OFFSETS[1];
+        const ARGS_MASK: u64 = 1 << builder::MASK_OFFSETS[5];
+        if self.mask & RANGE_MASK == RANGE_MASK {
+            unsafe { self.range.assume_init_drop() };
+        }
+        if self.mask & ARGS_MASK == ARGS_MASK {
+            unsafe { self.args.assume_init_drop() };
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::mem::MaybeUninit;
+
+use crate::{
+    macros::{masked_builder::masked_builder, zeroed_default::zeroed_default},
+    nvim_types::{Boolean, returns::cmd::CmdMagicInfo},
+};
+
+masked_builder!(
+    #[repr(C)]
+    pub struct CmdMagic {
+        file: Boolean,
+        bar: Boolean,
+    }
+);
+
+zeroed_default!(CmdMagic);
+
+impl CmdMagic {
+    #[inline]
+    fn get_field<'a, T>(&self, idx: usize, field: &'a MaybeUninit<T>) -> Option<&'a T> {
+        let mask: u64 = 1 << builder::MASK_OFFSETS[idx];
+        (self.mask & mask == mask).then(|| unsafe { field.assume_init_ref() })
+    }
+
+    pub(crate) fn to_info(&self) -> CmdMagicInfo {
+        CmdMagicInfo {
+            file: self.get_field(0, &self.file).copied().unwrap_or_default(),
+            bar: self.get_field(1, &self.bar).copied().unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::mem::MaybeUninit;
+
+use crate::{
+    macros::{
+        masked_builder::masked_builder, nv_enum::nv_str_enum, zeroed_default::zeroed_default,
+    },
+    nvim_types::{
+        Boolean, Integer, opts::cmd_mods_filter::CmdModsFilter, returns::cmd::CmdModsInfo,
+    },
+};
+
+nv_str_enum!(
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum CmdSplit {
+        AboveLeft = "aboveleft",
+        BelowRight = "belowright",
+        TopLeft = "topleft",
+        BotRight = "botright",
+    }
+);
+
+masked_builder!(
+    #[repr(C)]
+    pub struct CmdMods<'a> {
+        silent: Boolean,
+        emsg_silent: Boolean,
+        unsilent: Boolean,
+        filter: CmdModsFilter<'a>,
+        sandbox: Boolean,
+        noautocmd: Boolean,
+        browse: Boolean,
+        confirm: Boolean,
+        hide: Boolean,
+        horizontal: Boolean,
+        keepalt: Boolean,
+        keepjumps: Boolean,
+        keepmarks: Boolean,
+        keeppatterns: Boolean,
+        lockmarks: Boolean,
+        noswapfile: Boolean,
+        tab: Integer,
+        verbose: Integer,
+        vertical: Boolean,
+        #[builder(nv_str_enum)]
+        split: CmdSplit,
+    }
+);
+
+zeroed_default!(CmdMods<'_>);
+
+impl CmdMods<'_> {
+    #[inline]
+    fn get_field<'b, T>(&self, idx: usize, field: &'b MaybeUninit<T>) -> Option<&'b T> {
+        let mask: u64 = 1 << builder::MASK_OFFSETS[idx];
+        (self.mask & mask == mask).then(|| unsafe { field.assume_init_ref() })
+    }
+
+    pub(crate) fn to_info(&self) -> CmdModsInfo {
+        macro_rules! flag {
+            ($idx:literal, $field:ident) => {
+                self.get_field($idx, &self.$field)
+                    .copied()
+                    .unwrap_or_default()
+            };
+        }
+
+        // neovim uses -1 for the count modifiers that were not provided
+        let count = |c: Option<&Integer>| c.copied().filter(|c| *c >= 0);
+
+        CmdModsInfo {
+            silent: flag!(0, silent),
+            emsg_silent: flag!(1, emsg_silent),
+            unsilent: flag!(2, unsilent),
+            filter: self.get_field(3, &self.filter).and_then(|f| f.to_info()),
+            sandbox: flag!(4, sandbox),
+            noautocmd: flag!(5, noautocmd),
+            browse: flag!(6, browse),
+            confirm: flag!(7, confirm),
+            hide: flag!(8, hide),
+            horizontal: flag!(9, horizontal),
+            keepalt: flag!(10, keepalt),
+            keepjumps: flag!(11, keepjumps),
+            keepmarks: flag!(12, keepmarks),
+            keeppatterns: flag!(13, keeppatterns),
+            lockmarks: flag!(14, lockmarks),
+            noswapfile: flag!(15, noswapfile),
+            tab: count(self.get_field(16, &self.tab)),
+            verbose: count(self.get_field(17, &self.verbose)),
+            vertical: flag!(18, vertical),
+            split: self
+                .get_field(19, &self.split)
+                .and_then(|s| CmdSplit::from_enum_str(*s)),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::mem::MaybeUninit;
+
+use crate::{
+    macros::{masked_builder::masked_builder, zeroed_default::zeroed_default},
+    nvim_types::{Boolean, OwnedThinString, ThinString, returns::cmd::CmdFilterInfo},
+};
+
+masked_builder!(
+    #[repr(C)]
+    pub struct CmdModsFilter<'a> {
+        #[builder(nv_str)]
+        pattern: ThinString<'a>,
+        force: Boolean,
+    }
+);
+
+zeroed_default!(CmdModsFilter<'_>);
+
+impl CmdModsFilter<'_> {
+    #[inline]
+    fn get_field<'b, T>(&self, idx: usize, field: &'b MaybeUninit<T>) -> Option<&'b T> {
+        let mask: u64 = 1 << builder::MASK_OFFSETS[idx];
+        (self.mask & mask == mask).then(|| unsafe { field.assume_init_ref() })
+    }
+
+    /// Neovim always returns a filter, an empty pattern means that no filter is applied.
+    pub(crate) fn to_info(&self) -> Option<CmdFilterInfo> {
+        let pattern = self.get_field(0, &self.pattern).filter(|p| !p.is_empty())?;
+        Some(CmdFilterInfo {
+            pattern: OwnedThinString::from(*pattern),
+            force: self.get_field(1, &self.force).copied().unwrap_or_default(),
+        })
+    }
+}
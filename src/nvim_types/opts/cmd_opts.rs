@@ -0,0 +1,13 @@
+use crate::{
+    macros::{masked_builder::masked_builder, zeroed_default::zeroed_default},
+    nvim_types::Boolean,
+};
+
+masked_builder!(
+    #[repr(C)]
+    pub struct CmdOpts {
+        output: Boolean,
+    }
+);
+
+zeroed_default!(CmdOpts);
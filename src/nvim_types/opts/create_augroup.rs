@@ -0,0 +1,33 @@
+use crate::{
+    macros::{
+        masked_builder::{assign_field, masked_builder},
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{Boolean, object::ObjectRef},
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct CreateAugroupOpts {
+        #[builder(skip)]
+        clear: ObjectRef<'static>,
+    }
+}
+
+zeroed_default!(CreateAugroupOpts);
+
+impl CreateAugroupOpts {
+    /// Clear existing autocommands of the group if it already exists (defaults to true)
+    pub fn clear(&mut self, clear: Boolean) -> &mut Self {
+        unsafe {
+            assign_field(
+                0,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.clear,
+                ObjectRef::new_bool(clear),
+            )
+        };
+        self
+    }
+}
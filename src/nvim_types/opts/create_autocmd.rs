@@ -0,0 +1,92 @@
+use std::error::Error;
+
+use crate::{
+    macros::{
+        masked_builder::{assign_field, masked_builder},
+        obj_ref_setters::obj_ref_setters,
+        zeroed_default::zeroed_default,
+    },
+    nvim_funcs::autocmd::del_autocmd,
+    nvim_types::{
+        Boolean, Buffer, Object, ThinString,
+        args::autocmd::AutocmdArgs,
+        lua::{Function, NvFn},
+        object::ObjectRef,
+    },
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct CreateAutocmdOpts<'a> {
+        buffer: Buffer,
+        #[builder(skip)]
+        callback: Object,
+        #[builder(nv_str)]
+        command: ThinString<'a>,
+        #[builder(nv_str)]
+        desc: ThinString<'a>,
+        #[builder(skip)]
+        group: ObjectRef<'a>,
+        nested: Boolean,
+        once: Boolean,
+        #[builder(skip)]
+        pattern: ObjectRef<'a>,
+    }
+}
+
+zeroed_default!(CreateAutocmdOpts<'_>);
+
+impl<'a> CreateAutocmdOpts<'a> {
+    /// Set the function to call when the event is triggered
+    ///
+    /// Returning true from the callback deletes the autocommand.
+    pub fn callback<
+        E: 'static + Error,
+        F: 'static + NvFn + Fn(AutocmdArgs) -> Result<Boolean, E>,
+    >(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.local_callback(f)
+    }
+
+    /// Same as [`CreateAutocmdOpts::callback`] for callbacks that are only used on the main thread
+    pub(crate) fn local_callback<
+        E: 'static + Error,
+        F: 'static + Fn(AutocmdArgs) -> Result<Boolean, E> + Unpin,
+    >(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        let f = Function::wrap(move |args: AutocmdArgs| {
+            let id = args.id;
+            if f(args)? {
+                // the callback may have already deleted the autocommand itself
+                let _ = del_autocmd(id);
+            }
+            Ok::<(), E>(())
+        });
+        unsafe {
+            assign_field(
+                1,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.callback,
+                Object::LuaRef(f.into_luaref()),
+            )
+        };
+        self
+    }
+
+    obj_ref_setters!(7 => pattern; group = 4);
+}
+
+// neovim takes its own reference to the callback so we must unref ours to avoid a leak
+impl Drop for CreateAutocmdOpts<'_> {
+    fn drop(&mut self) {
+        const MASK: u64 = 1 << builder::MASK_OFFSETS[1];
+        if self.mask & MASK == MASK {
+            unsafe { self.callback.assume_init_drop() };
+        }
+    }
+}
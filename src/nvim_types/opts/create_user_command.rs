@@ -7,7 +7,7 @@ use crate::{
         zeroed_default::zeroed_default,
     },
     nvim_types::{
-        AsThinString, Boolean, Integer, Object, ThinString,
+        Boolean, Integer, IntoLua, Object, ThinString,
         args::user_command_complete_cb::UserCommandCompleteArgs,
         lua::Function,
         object::{ObjectRef, ObjectTag},
@@ -91,8 +91,10 @@ nv_str_enum!(
 #[repr(C)]
 pub struct UserCommandComplete(ObjectRef<'static>);
 
+/// Lua completion functions are treated as `customlist`, the callback should return an
+/// [`Array`](crate::nvim_types::Array) of candidates.
 impl<
-    R: 'static + AsThinString,
+    R: 'static + IntoLua,
     E: 'static + Error,
     F: 'static + for<'a> Fn(UserCommandCompleteArgs<'a>) -> Result<R, E> + Unpin,
 > From<F> for UserCommandComplete
@@ -109,6 +111,13 @@ impl From<UserCommandCompleteKind> for UserCommandComplete {
     }
 }
 
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for UserCommandComplete {
+    fn to_object(&self) -> Result<Object, crate::rpc::RpcError> {
+        self.0.to_object()
+    }
+}
+
 impl Drop for UserCommandComplete {
     fn drop(&mut self) {
         if self.0.tag == ObjectTag::LuaRef {
@@ -153,6 +162,13 @@ impl From<UserCommandRange> for UserCommandRangeInner {
     }
 }
 
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for UserCommandRangeInner {
+    fn to_object(&self) -> Result<Object, crate::rpc::RpcError> {
+        self.0.to_object()
+    }
+}
+
 masked_builder!(
     #[repr(C)]
     pub struct CreateUserCommandOpts<'a> {
@@ -8,3 +8,15 @@ builder!(
         verbose: Boolean,
     }
 );
+
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for EchoOpts {
+    fn to_object(&self) -> Result<crate::nvim_types::Object, crate::rpc::RpcError> {
+        use crate::nvim_types::{Dict, Object};
+
+        Ok(Object::Dict(Dict::from_iter([
+            ("err", Object::Bool(self.err)),
+            ("verbose", Object::Bool(self.verbose)),
+        ])))
+    }
+}
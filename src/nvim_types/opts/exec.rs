@@ -7,3 +7,15 @@ builder! {
         output: Boolean
     }
 }
+
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for ExecOpts {
+    fn to_object(&self) -> Result<crate::nvim_types::Object, crate::rpc::RpcError> {
+        use crate::nvim_types::{Dict, Object};
+
+        Ok(Object::Dict(Dict::from_iter([(
+            "output",
+            Object::Bool(self.output),
+        )])))
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    macros::{
+        masked_builder::masked_builder, obj_ref_setters::obj_ref_setters,
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{Boolean, Buffer, Object, object::ObjectRef},
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct ExecAutocmdsOpts<'a> {
+        buffer: Buffer,
+        #[builder(skip)]
+        group: ObjectRef<'a>,
+        modeline: Boolean,
+        #[builder(skip)]
+        pattern: ObjectRef<'a>,
+        data: Object,
+    }
+}
+
+zeroed_default!(ExecAutocmdsOpts<'_>);
+
+impl<'a> ExecAutocmdsOpts<'a> {
+    obj_ref_setters!(3 => pattern; group = 1);
+}
+
+// the data is copied by neovim so the value is still owned by us
+impl Drop for ExecAutocmdsOpts<'_> {
+    fn drop(&mut self) {
+        const MASK: u64 = 1 << builder::MASK_OFFSETS[4];
+        if self.mask & MASK == MASK {
+            unsafe { self.data.assume_init_drop() };
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{
+    macros::{
+        masked_builder::{assign_field, masked_builder},
+        obj_ref_setters::obj_ref_setters,
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{Buffer, Integer, object::ObjectRef},
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct GetAutocmdsOpts<'a> {
+        #[builder(skip)]
+        event: ObjectRef<'a>,
+        #[builder(skip)]
+        group: ObjectRef<'a>,
+        #[builder(skip)]
+        pattern: ObjectRef<'a>,
+        #[builder(skip)]
+        buffer: ObjectRef<'a>,
+        id: Integer,
+    }
+}
+
+zeroed_default!(GetAutocmdsOpts<'_>);
+
+impl<'a> GetAutocmdsOpts<'a> {
+    obj_ref_setters!(0 => event, 2 => pattern; group = 1);
+
+    /// Only return the buffer local autocommands of the buffer
+    pub fn buffer(&mut self, buf: Buffer) -> &mut Self {
+        unsafe {
+            assign_field(
+                3,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.buffer,
+                ObjectRef::new_int(buf.as_int().into()),
+            )
+        };
+        self
+    }
+}
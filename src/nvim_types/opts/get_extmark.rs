@@ -0,0 +1,14 @@
+use crate::{
+    macros::{masked_builder::masked_builder, zeroed_default::zeroed_default},
+    nvim_types::Boolean,
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct GetExtmarkOpts {
+        details: Boolean,
+        hl_name: Boolean,
+    }
+}
+
+zeroed_default!(GetExtmarkOpts);
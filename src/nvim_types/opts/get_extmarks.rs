@@ -0,0 +1,60 @@
+use crate::{
+    macros::{
+        masked_builder::masked_builder, nv_enum::nv_str_enum, zeroed_default::zeroed_default,
+    },
+    nvim_types::{Boolean, Integer, Object, extmark::ExtMark},
+};
+
+nv_str_enum!(
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ExtmarkType {
+        Highlight = "highlight",
+        Sign = "sign",
+        VirtText = "virt_text",
+        VirtLines = "virt_lines",
+    }
+);
+
+masked_builder! {
+    #[repr(C)]
+    pub struct GetExtmarksOpts {
+        limit: Integer,
+        details: Boolean,
+        hl_name: Boolean,
+        overlap: Boolean,
+        #[builder(nv_str_enum)]
+        kind: ExtmarkType = "type",
+    }
+}
+
+zeroed_default!(GetExtmarksOpts);
+
+/// A position used to define the range of [`buf_get_extmarks`]
+///
+/// [`buf_get_extmarks`]: crate::nvim_funcs::extmark::buf_get_extmarks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtmarkPos {
+    /// The position of an existing extmark
+    Id(ExtMark),
+    /// A (0,0)-indexed (row, col) position
+    Pos(Integer, Integer),
+    /// The start of the buffer
+    Start,
+    /// The end of the buffer
+    End,
+}
+
+impl From<ExtmarkPos> for Object {
+    fn from(value: ExtmarkPos) -> Self {
+        use crate::nvim_types::Array;
+        let (row, col) = match value {
+            ExtmarkPos::Id(id) => return Object::Integer(id.as_int().into()),
+            ExtmarkPos::Pos(row, col) => (row, col),
+            ExtmarkPos::Start => (0, 0),
+            ExtmarkPos::End => (-1, -1),
+        };
+        Object::Array(Array::from(
+            [Object::Integer(row), Object::Integer(col)].as_slice(),
+        ))
+    }
+}
@@ -1,20 +1,36 @@
 pub mod buf_attach;
 pub mod buf_delete;
+pub mod clear_autocmds;
+pub mod cmd;
+pub mod cmd_magic;
+pub mod cmd_mods;
+pub mod cmd_mods_filter;
+pub mod cmd_opts;
 pub mod context;
+pub mod create_augroup;
+pub mod create_autocmd;
 pub mod create_user_command;
 pub mod echo;
 pub mod eval_statusline;
 pub mod exec;
+pub mod exec_autocmds;
+pub mod get_autocmds;
 pub mod get_commands;
+pub mod get_extmark;
+pub mod get_extmarks;
 pub mod get_hl;
 pub mod get_hl_ns;
 pub mod get_mark;
 pub mod get_text;
 pub mod open_term;
 pub mod win_opts;
+pub mod win_text_height;
 pub mod option;
+pub mod parse_cmd;
 pub mod paste;
 pub mod select_popupmenu_item;
+pub mod set_decoration_provider;
+pub mod set_extmark;
 pub mod set_hl;
 pub mod set_keymap;
 pub mod set_mark;
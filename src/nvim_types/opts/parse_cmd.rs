@@ -0,0 +1,8 @@
+use crate::macros::{masked_builder::masked_builder, zeroed_default::zeroed_default};
+
+masked_builder!(
+    #[repr(C)]
+    pub struct ParseCmdOpts {}
+);
+
+zeroed_default!(ParseCmdOpts);
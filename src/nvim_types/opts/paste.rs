@@ -6,3 +6,10 @@ pub enum PastePhase {
     Continue = 2,
     End = 3,
 }
+
+#[cfg(feature = "rpc")]
+impl crate::rpc::RpcArg for PastePhase {
+    fn to_object(&self) -> Result<crate::nvim_types::Object, crate::rpc::RpcError> {
+        Ok(crate::nvim_types::Object::Integer(self.clone() as i64))
+    }
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+
+use crate::{
+    macros::{
+        masked_builder::{assign_field, masked_builder},
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{
+        Boolean, LuaRef,
+        args::decoration_provider::{
+            DecorOnBufArgs, DecorOnEndArgs, DecorOnLineArgs, DecorOnStartArgs, DecorOnWinArgs,
+        },
+        lua::{Function, NvFn},
+    },
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct DecorationProviderOpts {
+        #[builder(skip)]
+        on_start: LuaRef,
+        #[builder(skip)]
+        on_buf: LuaRef,
+        #[builder(skip)]
+        on_win: LuaRef,
+        #[builder(skip)]
+        on_line: LuaRef,
+        #[builder(skip)]
+        on_end: LuaRef,
+        #[builder(skip)]
+        _on_hl_def: LuaRef,
+        #[builder(skip)]
+        _on_spell_nav: LuaRef,
+        #[builder(skip)]
+        _on_conceal_line: LuaRef,
+    }
+}
+
+zeroed_default!(DecorationProviderOpts);
+
+/// Generates a setter for each callback that wraps the closure and stores its reference
+macro_rules! decor_callbacks {
+    ($($(#[$attr:meta])* $idx:literal => $field:ident($args:ident) -> $ret:ty;)+) => {
+        $(
+            $(#[$attr])*
+            pub fn $field<
+                E: 'static + Error,
+                F: 'static + NvFn + for<'a> Fn($args<'a>) -> Result<$ret, E>,
+            >(
+                &mut self,
+                $field: F,
+            ) -> &mut Self {
+                unsafe {
+                    assign_field(
+                        $idx,
+                        &mut self.mask,
+                        &builder::MASK_OFFSETS,
+                        &mut self.$field,
+                        Function::wrap($field).into_luaref(),
+                    )
+                };
+                self
+            }
+        )+
+    };
+}
+
+impl DecorationProviderOpts {
+    decor_callbacks! {
+        /// Called at the start of a redraw cycle
+        ///
+        /// Returning false disables the provider until the next redraw.
+        0 => on_start(DecorOnStartArgs) -> Boolean;
+
+        /// Called for each buffer being redrawn
+        1 => on_buf(DecorOnBufArgs) -> ();
+
+        /// Called for each window being redrawn
+        ///
+        /// Returning false skips the [`DecorationProviderOpts::on_line`] callbacks for the window.
+        2 => on_win(DecorOnWinArgs) -> Boolean;
+
+        /// Called for each line being redrawn
+        ///
+        /// Extmarks placed with [`SetExtmarkOpts::ephemeral`] only last for the current redraw so
+        /// this is the place to set them.
+        ///
+        /// [`SetExtmarkOpts::ephemeral`]: crate::nvim_types::opts::set_extmark::SetExtmarkOpts::ephemeral
+        3 => on_line(DecorOnLineArgs) -> Boolean;
+
+        /// Called at the end of a redraw cycle
+        4 => on_end(DecorOnEndArgs) -> ();
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    macros::{
+        masked_builder::masked_builder, nv_enum::nv_str_enum, zeroed_default::zeroed_default,
+    },
+    nvim_types::{
+        Array, AsThinString, Boolean, HlGroupId, Integer, Object, OwnedThinString, ThinString,
+        extmark::ExtMark,
+    },
+};
+
+nv_str_enum!(
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum VirtTextPos {
+        Eol = "eol",
+        EolRightAlign = "eol_right_align",
+        Overlay = "overlay",
+        RightAlign = "right_align",
+        Inline = "inline",
+    }
+);
+
+nv_str_enum!(
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum HlMode {
+        Replace = "replace",
+        Combine = "combine",
+        Blend = "blend",
+    }
+);
+
+masked_builder! {
+    #[repr(C)]
+    pub struct SetExtmarkOpts<'a> {
+        #[builder(skip)]
+        id: Integer,
+        // deprecated alias of end_row
+        #[builder(skip)]
+        end_line: Integer,
+        end_row: Integer,
+        end_col: Integer,
+        #[builder(skip)]
+        hl_group: Object,
+        // TODO: use better type for chunks
+        virt_text: Array,
+        #[builder(nv_str_enum)]
+        virt_text_pos: VirtTextPos,
+        virt_text_win_col: Integer,
+        virt_text_hide: Boolean,
+        virt_text_repeat_linebreak: Boolean,
+        hl_eol: Boolean,
+        #[builder(nv_str_enum)]
+        hl_mode: HlMode,
+        invalidate: Boolean,
+        ephemeral: Boolean,
+        priority: Integer,
+        right_gravity: Boolean,
+        end_right_gravity: Boolean,
+        // TODO: use better type for lines of chunks
+        virt_lines: Array,
+        virt_lines_above: Boolean,
+        virt_lines_leftcol: Boolean,
+        strict: Boolean,
+        #[builder(nv_str)]
+        sign_text: ThinString<'a>,
+        sign_hl_group: HlGroupId,
+        number_hl_group: HlGroupId,
+        line_hl_group: HlGroupId,
+        cursorline_hl_group: HlGroupId,
+        #[builder(nv_str)]
+        conceal: ThinString<'a>,
+        #[builder(nv_str)]
+        conceal_lines: ThinString<'a>,
+        spell: Boolean,
+        ui_watched: Boolean,
+        undo_restore: Boolean,
+        #[builder(nv_str)]
+        url: ThinString<'a>,
+        scoped: Boolean,
+        #[builder(skip)]
+        _subpriority: Integer,
+    }
+}
+
+zeroed_default!(SetExtmarkOpts<'_>);
+
+impl SetExtmarkOpts<'_> {
+    /// Set the id of the extmark
+    ///
+    /// If an extmark with the same id exists in the namespace it is moved instead of creating a
+    /// new one.
+    pub fn id(&mut self, id: ExtMark) -> &mut Self {
+        const MASK: u64 = 1 << builder::MASK_OFFSETS[0];
+        self.id.write(id.as_int().into());
+        self.mask |= MASK;
+
+        self
+    }
+
+    /// Set the highlight group used to highlight the text between the extmark and the end position
+    pub fn hl_group(&mut self, hl_group: HlGroupId) -> &mut Self {
+        self.set_hl_group(Object::Integer(hl_group.as_int()))
+    }
+
+    /// Same as [`SetExtmarkOpts::hl_group`] but uses the name of the highlight group
+    pub fn hl_group_name<TH: AsThinString>(&mut self, name: TH) -> &mut Self {
+        self.set_hl_group(Object::String(OwnedThinString::from(name.as_thinstr())))
+    }
+
+    fn set_hl_group(&mut self, hl_group: Object) -> &mut Self {
+        const MASK: u64 = 1 << builder::MASK_OFFSETS[4];
+        if self.mask & MASK == MASK {
+            unsafe { self.hl_group.assume_init_drop() };
+        }
+        self.hl_group.write(hl_group);
+        self.mask |= MASK;
+
+        self
+    }
+}
+
+// neovim only reads the values so the owned fields are still ours to drop
+impl Drop for SetExtmarkOpts<'_> {
+    fn drop(&mut self) {
+        const HL_GROUP_MASK: u64 = 1 << builder::MASK_OFFSETS[4];
+        const VIRT_TEXT_MASK: u64 = 1 << builder::MASK_OFFSETS[5];
+        const VIRT_LINES_MASK: u64 = 1 << builder::MASK_OFFSETS[17];
+        if self.mask & HL_GROUP_MASK == HL_GROUP_MASK {
+            unsafe { self.hl_group.assume_init_drop() };
+        }
+        if self.mask & VIRT_TEXT_MASK == VIRT_TEXT_MASK {
+            unsafe { self.virt_text.assume_init_drop() };
+        }
+        if self.mask & VIRT_LINES_MASK == VIRT_LINES_MASK {
+            unsafe { self.virt_lines.assume_init_drop() };
+        }
+    }
+}
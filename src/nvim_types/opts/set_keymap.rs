@@ -5,7 +5,7 @@ use crate::{
     macros::{masked_builder::masked_builder, zeroed_default::zeroed_default},
     nvim_types::{
         Boolean,
-        lua::{Function, NvFn},
+        lua::{Function, IntoLua, NvFn},
         lua_ref::LuaRef,
         string::ThinString,
     },
@@ -36,7 +36,27 @@ impl<'a> SetKeymapOpts<'a> {
         &mut self,
         f: F,
     ) -> &mut Self {
-        let lref = Function::wrap(f).into_luaref();
+        self.set_callback(Function::wrap(f).into_luaref())
+    }
+
+    /// Sets a callback for an `expr` mapping, the returned value is used as the right hand side
+    ///
+    /// Also enables `expr`. Keycodes such as `<CR>` in the returned string are only replaced if
+    /// `replace_keycodes` is enabled.
+    pub fn expr_callback<
+        R: 'static + IntoLua,
+        E: 'static + Error,
+        F: NvFn + Fn(()) -> Result<R, E>,
+    >(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.expr(true)
+            .set_callback(Function::wrap(f).into_luaref())
+    }
+
+    /// Sets the callback to an existing Lua function
+    pub(crate) fn set_callback(&mut self, lref: LuaRef) -> &mut Self {
         const CB_MASK: u64 = 1 << 8;
         if self.mask & CB_MASK == CB_MASK {
             unsafe { self.callback.assume_init_drop() };
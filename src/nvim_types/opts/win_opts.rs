@@ -1,11 +1,14 @@
 use crate::{
     macros::{
-        masked_builder::masked_builder,
-        nv_enum::{nv_obj_ref_enum, nv_str_enum},
- zeroed_default::zeroed_default,
+        masked_builder::{assign_field, masked_builder, masked_field},
+        nv_enum::nv_str_enum,
+        obj_ref_setters::obj_ref_setters,
+        zeroed_default::zeroed_default,
+    },
+    nvim_types::{
+        Array, Boolean, Float, Integer, Window, borrowed::Borrowed, object::ObjectRef,
+        returns::win_config::WinConfigInfo,
     },
-    nvim_types::{object::ObjectRef, Array, Boolean, Float, Integer, Object, Window},
-    th,
 };
 
 nv_str_enum!(
@@ -40,15 +43,15 @@ nv_str_enum!(
     }
 );
 
-nv_obj_ref_enum!(
+nv_str_enum!(
     #[derive(Clone, Copy, Debug)]
     pub enum Border {
-        None = ObjectRef::new_th(th!("none")),
-        Single = ObjectRef::new_th(th!("single")),
-        Double = ObjectRef::new_th(th!("double")),
-        Rounded = ObjectRef::new_th(th!("rounded")),
-        Solid = ObjectRef::new_th(th!("solid")),
-        Shadow = ObjectRef::new_th(th!("shadow")),
+        None = "none",
+        Single = "single",
+        Double = "double",
+        Rounded = "rounded",
+        Solid = "solid",
+        Shadow = "shadow",
     }
 );
 
@@ -79,7 +82,7 @@ nv_str_enum!(
 
 masked_builder!(
     #[repr(C)]
-    pub struct WinConfig {
+    pub struct WinConfig<'a> {
         row: Float,
         col: Float,
         width: Integer,
@@ -92,22 +95,21 @@ masked_builder!(
         split: Split,
         win: Window,
         // TODO: use better type
-        bufpos: Array,
+        #[builder(skip)]
+        bufpos: Borrowed<'a, Array>,
         external: Boolean,
         focusable: Boolean,
         mouse: Boolean,
         vertical: Boolean,
         zindex: Integer,
-        #[builder(nv_obj_ref_enum)]
-        border: Border,
-        // TODO: use better type and support highlighted titles
         #[builder(skip)]
-        title: Object,
+        border: ObjectRef<'a>,
+        #[builder(skip)]
+        title: ObjectRef<'a>,
         #[builder(nv_str_enum)]
         title_pos: TitlePos,
-        // TODO: use better type and support highlighted footers
         #[builder(skip)]
-        footer: Object,
+        footer: ObjectRef<'a>,
         #[builder(nv_str_enum)]
         footer_pos: FooterPos,
         #[builder(nv_str_enum)]
@@ -119,4 +121,119 @@ masked_builder!(
     }
 );
 
-zeroed_default!(WinConfig);
+zeroed_default!(WinConfig<'_>);
+
+impl<'a> WinConfig<'a> {
+    pub fn bufpos(&mut self, bufpos: &'a Array) -> &mut Self {
+        unsafe {
+            assign_field(
+                8,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.bufpos,
+                Borrowed::new(bufpos),
+            )
+        };
+        self
+    }
+
+    pub fn border(&mut self, border: Border) -> &mut Self {
+        unsafe {
+            assign_field(
+                14,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.border,
+                ObjectRef::new_th(border.as_enum_str()),
+            )
+        };
+        self
+    }
+
+    /// Set a custom border
+    ///
+    /// The array should contain the characters (or `[char, hl_group]` pairs) used to draw the
+    /// border starting from the top left corner going clockwise. See `:h nvim_open_win` for the
+    /// accepted formats.
+    pub fn border_chars(&mut self, chars: &'a Array) -> &mut Self {
+        unsafe {
+            assign_field(
+                14,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.border,
+                ObjectRef::from(chars),
+            )
+        };
+        self
+    }
+
+    // the title and footer setters accept plain text
+    obj_ref_setters!(15 => title, 17 => footer);
+
+    /// Set the title of the window as `[text, hl_group]` chunks
+    pub fn title_chunks(&mut self, chunks: &'a Array) -> &mut Self {
+        unsafe {
+            assign_field(
+                15,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.title,
+                ObjectRef::from(chunks),
+            )
+        };
+        self
+    }
+
+    /// Set the footer of the window as `[text, hl_group]` chunks
+    pub fn footer_chunks(&mut self, chunks: &'a Array) -> &mut Self {
+        unsafe {
+            assign_field(
+                17,
+                &mut self.mask,
+                &builder::MASK_OFFSETS,
+                &mut self.footer,
+                ObjectRef::from(chunks),
+            )
+        };
+        self
+    }
+
+    /// Copies the set fields out of a config returned by neovim
+    ///
+    /// The config returned by `nvim_win_get_config` is arena allocated so all values are cloned.
+    pub(crate) fn to_info(&self) -> WinConfigInfo {
+        macro_rules! field {
+            ($idx:literal, $field:ident) => {
+                unsafe { masked_field($idx, self.mask, &builder::MASK_OFFSETS, &self.$field) }
+            };
+        }
+        WinConfigInfo {
+            row: field!(0, row).copied(),
+            col: field!(1, col).copied(),
+            width: field!(2, width).copied(),
+            height: field!(3, height).copied(),
+            anchor: field!(4, anchor).and_then(|s| Anchor::from_enum_str(*s)),
+            relative: field!(5, relative).and_then(|s| Relative::from_enum_str(*s)),
+            split: field!(6, split).and_then(|s| Split::from_enum_str(*s)),
+            win: field!(7, win).copied(),
+            bufpos: field!(8, bufpos).map(|v| v.as_ref().clone()),
+            external: field!(9, external).copied(),
+            focusable: field!(10, focusable).copied(),
+            mouse: field!(11, mouse).copied(),
+            vertical: field!(12, vertical).copied(),
+            zindex: field!(13, zindex).copied(),
+            border: field!(14, border)
+                .and_then(|b| b.as_object().as_array())
+                .cloned(),
+            title: field!(15, title).map(|v| v.as_object().clone()),
+            title_pos: field!(16, title_pos).and_then(|s| TitlePos::from_enum_str(*s)),
+            footer: field!(17, footer).map(|v| v.as_object().clone()),
+            footer_pos: field!(18, footer_pos).and_then(|s| FooterPos::from_enum_str(*s)),
+            style: field!(19, style).and_then(|s| Style::from_enum_str(*s)),
+            noautocmd: field!(20, noautocmd).copied(),
+            fixed: field!(21, fixed).copied(),
+            hide: field!(22, hide).copied(),
+        }
+    }
+}
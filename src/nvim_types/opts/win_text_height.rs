@@ -0,0 +1,17 @@
+use crate::{
+    macros::{masked_builder::masked_builder, zeroed_default::zeroed_default},
+    nvim_types::Integer,
+};
+
+masked_builder! {
+    #[repr(C)]
+    pub struct WinTextHeightOpts {
+        start_row: Integer,
+        end_row: Integer,
+        start_vcol: Integer,
+        end_vcol: Integer,
+        max_height: Integer,
+    }
+}
+
+zeroed_default!(WinTextHeightOpts);
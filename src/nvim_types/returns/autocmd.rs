@@ -0,0 +1,96 @@
+use std::{mem::ManuallyDrop, ops::DerefMut};
+
+use crate::nvim_types::{Array, Boolean, Buffer, Dict, Integer, KVec, Object, OwnedThinString};
+
+use super::utils::skip_drop_remove_keys;
+
+/// An autocommand as returned by [`get_autocmds`](crate::nvim_funcs::autocmd::get_autocmds)
+#[derive(Clone, Debug)]
+pub struct AutocmdInfo {
+    pub id: Option<Integer>,
+    pub group: Option<Integer>,
+    pub group_name: Option<OwnedThinString>,
+    pub desc: Option<OwnedThinString>,
+    pub event: OwnedThinString,
+    /// The command to execute, empty if the autocommand uses a callback.
+    pub command: OwnedThinString,
+    /// A [`LuaRef`](crate::nvim_types::LuaRef) or the name of a vimscript function.
+    pub callback: Option<Object>,
+    pub once: Boolean,
+    pub pattern: OwnedThinString,
+    pub buflocal: Boolean,
+    pub buffer: Option<Buffer>,
+}
+
+impl AutocmdInfo {
+    fn from_c_func_ret(d: &mut Dict) -> Self {
+        let [
+            id,
+            group,
+            group_name,
+            desc,
+            event,
+            command,
+            mut callback,
+            once,
+            pattern,
+            buflocal,
+            buffer,
+        ] = skip_drop_remove_keys(
+            d,
+            &[
+                "id",
+                "group",
+                "group_name",
+                "desc",
+                "event",
+                "command",
+                "callback",
+                "once",
+                "pattern",
+                "buflocal",
+                "buffer",
+            ],
+            Some(|k| match k {
+                "id" | "group" | "group_name" | "desc" | "callback" | "buffer" => {
+                    Some(Object::Null)
+                }
+                "command" => Some(Object::String(OwnedThinString::default())),
+                _ => None,
+            }),
+        )
+        .unwrap();
+
+        let callback = match callback.deref_mut() {
+            Object::Null => None,
+            // neovim creates a new reference for the returned callback, take ownership of it so it
+            // is unreferenced once dropped
+            Object::LuaRef(_) => Some(unsafe { ManuallyDrop::take(&mut callback) }),
+            obj => Some(obj.clone()),
+        };
+
+        Self {
+            id: id.as_int(),
+            group: group.as_int(),
+            group_name: group_name.as_string().cloned(),
+            desc: desc.as_string().cloned(),
+            event: event.as_string().unwrap().clone(),
+            command: command.as_string().unwrap().clone(),
+            callback,
+            once: once.as_bool().unwrap(),
+            pattern: pattern.as_string().unwrap().clone(),
+            buflocal: buflocal.as_bool().unwrap(),
+            buffer: buffer.as_int().map(|b| Buffer::new(b as _)),
+        }
+    }
+
+    pub(crate) fn from_c_func_ret_list(arr: &mut Array) -> KVec<Self> {
+        let mut autocmds = KVec::with_capacity(arr.len());
+        autocmds.extend(arr.iter_mut().map(|obj| match obj {
+            Object::Dict(d) => Self::from_c_func_ret(d),
+            _ => unreachable!("found non dict autocommand"),
+        }));
+
+        autocmds
+    }
+}
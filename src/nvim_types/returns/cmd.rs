@@ -0,0 +1,240 @@
+use crate::nvim_types::{
+    Boolean, Integer, KVec, OwnedThinString,
+    opts::{
+        cmd::Cmd,
+        cmd_magic::CmdMagic,
+        cmd_mods::{CmdMods, CmdSplit},
+        cmd_mods_filter::CmdModsFilter,
+    },
+};
+#[cfg(feature = "rpc")]
+use crate::nvim_types::{Dict, Object};
+
+/// The line range of a command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CmdRange {
+    /// A single line such as `:5d`
+    Line(Integer),
+    /// A start and end line such as `:1,5d`
+    Range(Integer, Integer),
+}
+
+/// A parsed command as returned by [`parse_cmd`](crate::nvim_funcs::command::parse_cmd).
+///
+/// The value can be converted in to a [`Cmd`] to execute it with
+/// [`cmd`](crate::nvim_funcs::command::cmd).
+#[derive(Clone, Debug, Default)]
+pub struct CmdInfo {
+    /// The full name of the command.
+    pub cmd: OwnedThinString,
+    pub range: Option<CmdRange>,
+    pub count: Option<Integer>,
+    pub reg: Option<OwnedThinString>,
+    pub bang: Boolean,
+    pub args: KVec<OwnedThinString>,
+    pub magic: CmdMagicInfo,
+    pub mods: CmdModsInfo,
+    /// The number of arguments the command accepts such as `"0"`, `"1"` or `"*"`.
+    pub nargs: Option<OwnedThinString>,
+    /// The address type of the command such as `"line"` or `"buf"`.
+    pub addr: Option<OwnedThinString>,
+    /// The command that follows a `|`.
+    pub nextcmd: Option<OwnedThinString>,
+}
+
+/// Which characters in the arguments of the command have special meaning
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CmdMagicInfo {
+    /// File name characters such as `%` are expanded.
+    pub file: Boolean,
+    /// `|` is treated as a command separator.
+    pub bar: Boolean,
+}
+
+/// The modifiers of a command
+///
+/// The count modifiers are [`None`] when not provided.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CmdModsInfo {
+    pub silent: Boolean,
+    pub emsg_silent: Boolean,
+    pub unsilent: Boolean,
+    pub filter: Option<CmdFilterInfo>,
+    pub sandbox: Boolean,
+    pub noautocmd: Boolean,
+    pub browse: Boolean,
+    pub confirm: Boolean,
+    pub hide: Boolean,
+    pub horizontal: Boolean,
+    pub keepalt: Boolean,
+    pub keepjumps: Boolean,
+    pub keepmarks: Boolean,
+    pub keeppatterns: Boolean,
+    pub lockmarks: Boolean,
+    pub noswapfile: Boolean,
+    pub tab: Option<Integer>,
+    pub verbose: Option<Integer>,
+    pub vertical: Boolean,
+    pub split: Option<CmdSplit>,
+}
+
+/// The `:filter` modifier of a command
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CmdFilterInfo {
+    pub pattern: OwnedThinString,
+    /// The filter was inverted with a `!`.
+    pub force: Boolean,
+}
+
+impl From<&CmdMagicInfo> for CmdMagic {
+    fn from(value: &CmdMagicInfo) -> Self {
+        let mut magic = CmdMagic::default();
+        magic.file(value.file).bar(value.bar);
+        magic
+    }
+}
+
+impl<'a> From<&'a CmdModsInfo> for CmdMods<'a> {
+    fn from(value: &'a CmdModsInfo) -> Self {
+        let mut mods = CmdMods::default();
+        mods.silent(value.silent)
+            .emsg_silent(value.emsg_silent)
+            .unsilent(value.unsilent)
+            .sandbox(value.sandbox)
+            .noautocmd(value.noautocmd)
+            .browse(value.browse)
+            .confirm(value.confirm)
+            .hide(value.hide)
+            .horizontal(value.horizontal)
+            .keepalt(value.keepalt)
+            .keepjumps(value.keepjumps)
+            .keepmarks(value.keepmarks)
+            .keeppatterns(value.keeppatterns)
+            .lockmarks(value.lockmarks)
+            .noswapfile(value.noswapfile)
+            .vertical(value.vertical);
+        if let Some(filter) = &value.filter {
+            let mut f = CmdModsFilter::default();
+            f.pattern(&filter.pattern).force(filter.force);
+            mods.filter(f);
+        }
+        if let Some(tab) = value.tab {
+            mods.tab(tab);
+        }
+        if let Some(verbose) = value.verbose {
+            mods.verbose(verbose);
+        }
+        if let Some(split) = value.split {
+            mods.split(split);
+        }
+
+        mods
+    }
+}
+
+impl<'a> From<&'a CmdInfo> for Cmd<'a> {
+    fn from(value: &'a CmdInfo) -> Self {
+        let mut cmd = Cmd::default();
+        cmd.cmd(&value.cmd)
+            .bang(value.bang)
+            .args(value.args.as_slice())
+            .magic(CmdMagic::from(&value.magic))
+            .mods(CmdMods::from(&value.mods));
+        if let Some(range) = value.range {
+            cmd.range(range);
+        }
+        if let Some(count) = value.count {
+            cmd.count(count);
+        }
+        if let Some(reg) = &value.reg {
+            cmd.reg(reg);
+        }
+
+        cmd
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl CmdInfo {
+    /// Copies the dictionary returned by `nvim_parse_cmd` over RPC
+    ///
+    /// Missing and empty values are treated the same as in the struct returned by
+    /// [`parse_cmd`](crate::nvim_funcs::command::parse_cmd).
+    pub(crate) fn from_dict(d: &Dict) -> Self {
+        let string = |d: &Dict, key: &str| {
+            d.get(key)
+                .and_then(Object::as_string)
+                .filter(|s| !s.as_thinstr().is_empty())
+                .cloned()
+        };
+        let flag = |d: &Dict, key: &str| d.get(key).and_then(Object::as_bool).unwrap_or_default();
+        // neovim uses -1 for the count modifiers that were not provided
+        let count = |d: &Dict, key: &str| d.get(key).and_then(Object::as_int).filter(|c| *c >= 0);
+
+        let magic = d.get("magic").and_then(Object::as_dict);
+        let mods = d.get("mods").and_then(Object::as_dict);
+        Self {
+            cmd: string(d, "cmd").unwrap_or_default(),
+            range: d
+                .get("range")
+                .and_then(Object::as_array)
+                .and_then(|r| match r.as_slice() {
+                    [start, end] => Some(CmdRange::Range(start.as_int()?, end.as_int()?)),
+                    [line] => Some(CmdRange::Line(line.as_int()?)),
+                    _ => None,
+                }),
+            count: d.get("count").and_then(Object::as_int),
+            reg: string(d, "reg"),
+            bang: flag(d, "bang"),
+            args: d
+                .get("args")
+                .and_then(Object::as_array)
+                .map(|args| args.iter().filter_map(Object::as_string).cloned().collect())
+                .unwrap_or_default(),
+            magic: magic
+                .map(|m| CmdMagicInfo {
+                    file: flag(m, "file"),
+                    bar: flag(m, "bar"),
+                })
+                .unwrap_or_default(),
+            mods: mods
+                .map(|m| CmdModsInfo {
+                    silent: flag(m, "silent"),
+                    emsg_silent: flag(m, "emsg_silent"),
+                    unsilent: flag(m, "unsilent"),
+                    filter: m.get("filter").and_then(Object::as_dict).and_then(|f| {
+                        Some(CmdFilterInfo {
+                            pattern: string(f, "pattern")?,
+                            force: flag(f, "force"),
+                        })
+                    }),
+                    sandbox: flag(m, "sandbox"),
+                    noautocmd: flag(m, "noautocmd"),
+                    browse: flag(m, "browse"),
+                    confirm: flag(m, "confirm"),
+                    hide: flag(m, "hide"),
+                    horizontal: flag(m, "horizontal"),
+                    keepalt: flag(m, "keepalt"),
+                    keepjumps: flag(m, "keepjumps"),
+                    keepmarks: flag(m, "keepmarks"),
+                    keeppatterns: flag(m, "keeppatterns"),
+                    lockmarks: flag(m, "lockmarks"),
+                    noswapfile: flag(m, "noswapfile"),
+                    tab: count(m, "tab"),
+                    verbose: count(m, "verbose"),
+                    vertical: flag(m, "vertical"),
+                    split: m
+                        .get("split")
+                        .and_then(Object::as_string)
+                        .and_then(|s| CmdSplit::from_enum_str(s.as_thinstr())),
+                })
+                .unwrap_or_default(),
+            nargs: d.get("nargs").and_then(|o| match o {
+                Object::Integer(i) => Some(OwnedThinString::from(i.to_string().as_str())),
+                _ => string(d, "nargs"),
+            }),
+            addr: string(d, "addr"),
+            nextcmd: string(d, "nextcmd"),
+        }
+    }
+}
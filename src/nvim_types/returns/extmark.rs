@@ -0,0 +1,163 @@
+use crate::nvim_types::{
+    Array, Boolean, Dict, HandleT, Integer, KVec, NameSpace, Object, OwnedThinString,
+    extmark::ExtMark, opts::set_extmark::VirtTextPos,
+};
+
+use super::utils::skip_drop_remove_keys;
+
+#[derive(Clone, Debug)]
+pub struct ExtmarkInfo {
+    pub id: ExtMark,
+    /// The (0-indexed) row of the extmark
+    pub row: Integer,
+    /// The (0-indexed) column of the extmark
+    pub col: Integer,
+    /// Only present when `details` was set in the options
+    pub details: Option<ExtmarkDetails>,
+}
+
+/// The details of an extmark
+///
+/// Only the commonly used values are typed, any other returned value is left in
+/// [`ExtmarkDetails::other`].
+#[derive(Clone, Debug)]
+pub struct ExtmarkDetails {
+    pub ns_id: NameSpace,
+    pub priority: Option<Integer>,
+    pub right_gravity: Option<Boolean>,
+    pub end_row: Option<Integer>,
+    pub end_col: Option<Integer>,
+    pub end_right_gravity: Option<Boolean>,
+    /// The highlight group name if `hl_name` was set, otherwise its id.
+    pub hl_group: Option<Object>,
+    pub hl_eol: Option<Boolean>,
+    pub virt_text: Option<Array>,
+    pub virt_text_pos: Option<VirtTextPos>,
+    pub virt_lines: Option<Array>,
+    pub sign_text: Option<OwnedThinString>,
+    pub conceal: Option<OwnedThinString>,
+    pub url: Option<OwnedThinString>,
+    pub invalidate: Option<Boolean>,
+    pub invalid: Option<Boolean>,
+    pub other: Dict,
+}
+
+impl ExtmarkDetails {
+    fn from_c_func_ret(d: &mut Dict) -> Self {
+        let [
+            ns_id,
+            priority,
+            right_gravity,
+            end_row,
+            end_col,
+            end_right_gravity,
+            hl_group,
+            hl_eol,
+            virt_text,
+            virt_text_pos,
+            virt_lines,
+            sign_text,
+            conceal,
+            url,
+            invalidate,
+            invalid,
+        ] = skip_drop_remove_keys(
+            d,
+            &[
+                "ns_id",
+                "priority",
+                "right_gravity",
+                "end_row",
+                "end_col",
+                "end_right_gravity",
+                "hl_group",
+                "hl_eol",
+                "virt_text",
+                "virt_text_pos",
+                "virt_lines",
+                "sign_text",
+                "conceal",
+                "url",
+                "invalidate",
+                "invalid",
+            ],
+            Some(|k| (k != "ns_id").then_some(Object::Null)),
+        )
+        .unwrap();
+
+        let opt_obj = |obj: &Object| (!matches!(obj, Object::Null)).then(|| obj.clone());
+
+        Self {
+            ns_id: NameSpace::new(ns_id.as_int().unwrap() as HandleT),
+            priority: priority.as_int(),
+            right_gravity: right_gravity.as_bool(),
+            end_row: end_row.as_int(),
+            end_col: end_col.as_int(),
+            end_right_gravity: end_right_gravity.as_bool(),
+            hl_group: opt_obj(&hl_group),
+            hl_eol: hl_eol.as_bool(),
+            virt_text: virt_text.as_array().cloned(),
+            virt_text_pos: virt_text_pos
+                .as_string()
+                .and_then(|s| VirtTextPos::from_enum_str(s.as_thinstr())),
+            virt_lines: virt_lines.as_array().cloned(),
+            sign_text: sign_text.as_string().cloned(),
+            conceal: conceal.as_string().cloned(),
+            url: url.as_string().cloned(),
+            invalidate: invalidate.as_bool(),
+            invalid: invalid.as_bool(),
+            other: d.clone(),
+        }
+    }
+}
+
+impl ExtmarkInfo {
+    /// Converts a single `[id, row, col, details?]` tuple
+    fn from_c_func_ret_with_id(id: Option<ExtMark>, arr: &mut Array) -> Self {
+        let (id, rest) = match id {
+            Some(id) => (id, arr.as_mut_slice()),
+            None => {
+                let [id, rest @ ..] = arr.as_mut_slice() else {
+                    unreachable!("found empty extmark tuple");
+                };
+                (ExtMark::new(id.as_int().unwrap() as HandleT), rest)
+            }
+        };
+        let [row, col, details @ ..] = rest else {
+            unreachable!("found extmark tuple without a position");
+        };
+        let details = match details {
+            [Object::Dict(d), ..] => Some(ExtmarkDetails::from_c_func_ret(d)),
+            _ => None,
+        };
+
+        Self {
+            id,
+            row: row.as_int().unwrap(),
+            col: col.as_int().unwrap(),
+            details,
+        }
+    }
+
+    /// Converts the value returned by `nvim_buf_get_extmark_by_id`
+    ///
+    /// Returns [`None`] if the extmark does not exist.
+    pub(crate) fn from_c_func_ret_by_id(id: ExtMark, arr: &mut Array) -> Option<Self> {
+        if arr.is_empty() {
+            return None;
+        }
+
+        Some(Self::from_c_func_ret_with_id(Some(id), arr))
+    }
+
+    /// Converts the value returned by `nvim_buf_get_extmarks`
+    pub(crate) fn from_c_func_ret_list(arr: &mut Array) -> KVec<Self> {
+        let mut marks = KVec::with_capacity(arr.len());
+        marks.extend(arr.iter_mut().map(|obj| match obj {
+            Object::Array(arr) => Self::from_c_func_ret_with_id(None, arr),
+            _ => unreachable!("found non array extmark tuple"),
+        }));
+
+        marks
+    }
+}
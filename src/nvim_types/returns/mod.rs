@@ -1,11 +1,16 @@
+pub mod autocmd;
 pub mod channel_info;
+pub mod cmd;
 pub mod color_map;
 pub mod commands;
 pub mod context;
 pub mod eval_statusline;
 pub mod exec2;
+pub mod extmark;
 pub mod get_hl;
 pub mod get_keymap;
 pub mod get_mode;
 pub mod options_info;
+pub mod parse_expression;
 pub mod utils;
+pub mod win_config;
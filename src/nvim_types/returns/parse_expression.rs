@@ -0,0 +1,336 @@
+use crate::{
+    macros::nv_enum::nv_str_enum,
+    nvim_types::{Boolean, Dict, Float, Integer, KVec, Object, OwnedThinString},
+};
+
+nv_str_enum!(
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ExprCmpType {
+        Equal = "Equal",
+        Matches = "Matches",
+        Greater = "Greater",
+        GreaterOrEqual = "GreaterOrEqual",
+        Identical = "Identical",
+    }
+);
+
+nv_str_enum!(
+    /// How a comparison treats case
+    ///
+    /// [`ExprCaseCompare::UseOption`] depends on `'ignorecase'`, the others are set by a `#` or
+    /// `?` suffix on the operator.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ExprCaseCompare {
+        UseOption = "UseOption",
+        MatchCase = "MatchCase",
+        IgnoreCase = "IgnoreCase",
+    }
+);
+
+nv_str_enum!(
+    /// The operator of an augmented assignment such as `+=`, `-=` or `.=`
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ExprAugmentation {
+        Add = "Add",
+        Subtract = "Subtract",
+        Concat = "Concat",
+    }
+);
+
+/// The result of [`parse_expression`](crate::nvim_funcs::vimscript::parse_expression)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedExpr {
+    /// The root node of the expression, [`None`] if nothing could be parsed.
+    pub ast: Option<ExprAst>,
+    /// The number of bytes that were parsed.
+    ///
+    /// This may be shorter than the input when the `m` flag was not provided.
+    pub len: Integer,
+    /// Set if the expression failed to parse.
+    ///
+    /// The AST is still returned in this case, with [`ExprNodeKind::Missing`] and
+    /// [`ExprNodeKind::OpMissing`] nodes where the parser could not proceed.
+    pub error: Option<ExprParseError>,
+    /// The highlight chunks of the expression, empty unless highlighting was requested.
+    pub highlight: KVec<ExprHighlight>,
+}
+
+/// A parse error in an expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExprParseError {
+    /// The error message, with a `%.*s` in the place of the argument.
+    pub message: OwnedThinString,
+    /// The part of the expression that caused the error.
+    pub arg: OwnedThinString,
+}
+
+/// A highlighted chunk of an expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExprHighlight {
+    pub line: Integer,
+    pub start_col: Integer,
+    /// The end column, exclusive.
+    pub end_col: Integer,
+    pub group: OwnedThinString,
+}
+
+/// A node of a parsed expression
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExprAst {
+    pub kind: ExprNodeKind,
+    /// The zero based line and byte column the node starts at.
+    pub start: (Integer, Integer),
+    /// The length of the node in bytes.
+    ///
+    /// Operators only span the operator itself, use the children to get the span of the
+    /// operands.
+    pub len: Integer,
+    pub children: KVec<ExprAst>,
+}
+
+/// The type of an expression node and its type specific values
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprNodeKind {
+    Missing,
+    OpMissing,
+    Ternary,
+    TernaryValue,
+    /// `@r`, the name being the register character.
+    Register {
+        name: Integer,
+    },
+    Subscript,
+    ListLiteral,
+    UnaryPlus,
+    BinaryPlus,
+    Nested,
+    Call,
+    /// The scope is the character before the `:` such as `g` in `g:var`, or 0 if there is no scope.
+    PlainIdentifier {
+        scope: Integer,
+        ident: OwnedThinString,
+    },
+    PlainKey {
+        ident: OwnedThinString,
+    },
+    ComplexIdentifier,
+    UnknownFigure,
+    Lambda,
+    DictLiteral,
+    CurlyBracesIdentifier,
+    Comma,
+    Colon,
+    Arrow,
+    Comparison {
+        cmp_type: ExprCmpType,
+        ccs_strategy: ExprCaseCompare,
+        invert: Boolean,
+    },
+    Concat,
+    ConcatOrSubscript,
+    Integer {
+        value: Integer,
+    },
+    Float {
+        value: Float,
+    },
+    SingleQuotedString {
+        value: OwnedThinString,
+    },
+    DoubleQuotedString {
+        value: OwnedThinString,
+    },
+    Or,
+    And,
+    UnaryMinus,
+    BinaryMinus,
+    Not,
+    Multiplication,
+    Division,
+    Mod,
+    /// `&opt`, the scope being `g`, `l` or 0 if there is no scope.
+    Option {
+        scope: Integer,
+        ident: OwnedThinString,
+    },
+    Environment {
+        ident: OwnedThinString,
+    },
+    /// The augmentation is [`None`] for a plain `=`.
+    Assignment {
+        augmentation: Option<ExprAugmentation>,
+    },
+    /// A node type that is not known to nvimium.
+    Other(OwnedThinString),
+}
+
+fn string(d: &Dict, key: &str) -> OwnedThinString {
+    // the strings are a mix of static and arena allocated strings, always copy them
+    d.get(key)
+        .and_then(Object::as_string)
+        .map(|s| OwnedThinString::from(s.as_thinstr()))
+        .unwrap_or_default()
+}
+
+fn int(d: &Dict, key: &str) -> Integer {
+    d.get(key).and_then(Object::as_int).unwrap_or_default()
+}
+
+impl ParsedExpr {
+    /// Copies the result out of the returned [`Dict`]
+    ///
+    /// The [`Dict`] is allocated in an arena and must not be dropped.
+    pub(crate) fn from_c_func_ret(d: &Dict) -> Self {
+        let error = d
+            .get("error")
+            .and_then(Object::as_dict)
+            .map(|e| ExprParseError {
+                message: string(e, "message"),
+                arg: string(e, "arg"),
+            });
+        let highlight = d
+            .get("highlight")
+            .and_then(Object::as_array)
+            .map(|hl| hl.iter().filter_map(ExprHighlight::from_obj).collect())
+            .unwrap_or_default();
+
+        Self {
+            ast: d
+                .get("ast")
+                .and_then(Object::as_dict)
+                .map(ExprAst::from_dict),
+            len: int(d, "len"),
+            error,
+            highlight,
+        }
+    }
+}
+
+impl ExprHighlight {
+    fn from_obj(obj: &Object) -> Option<Self> {
+        let [line, start_col, end_col, group] = obj.as_array()?.as_slice() else {
+            return None;
+        };
+
+        Some(Self {
+            line: line.as_int()?,
+            start_col: start_col.as_int()?,
+            end_col: end_col.as_int()?,
+            group: OwnedThinString::from(group.as_string()?.as_thinstr()),
+        })
+    }
+}
+
+impl ExprAst {
+    fn from_dict(d: &Dict) -> Self {
+        let start = d
+            .get("start")
+            .and_then(Object::as_array)
+            .map(|a| a.as_slice())
+            .and_then(|s| Some((s.first()?.as_int()?, s.get(1)?.as_int()?)))
+            .unwrap_or_default();
+        let children = d
+            .get("children")
+            .and_then(Object::as_array)
+            .map(|c| {
+                c.iter()
+                    .filter_map(Object::as_dict)
+                    .map(ExprAst::from_dict)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            kind: ExprNodeKind::from_dict(d),
+            start,
+            len: int(d, "len"),
+            children,
+        }
+    }
+}
+
+impl ExprNodeKind {
+    fn from_dict(d: &Dict) -> Self {
+        let ty = string(d, "type");
+        match ty.as_thinstr().as_slice() {
+            b"Missing" => Self::Missing,
+            b"OpMissing" => Self::OpMissing,
+            b"Ternary" => Self::Ternary,
+            b"TernaryValue" => Self::TernaryValue,
+            b"Register" => Self::Register {
+                name: int(d, "name"),
+            },
+            b"Subscript" => Self::Subscript,
+            b"ListLiteral" => Self::ListLiteral,
+            b"UnaryPlus" => Self::UnaryPlus,
+            b"BinaryPlus" => Self::BinaryPlus,
+            b"Nested" => Self::Nested,
+            b"Call" => Self::Call,
+            b"PlainIdentifier" => Self::PlainIdentifier {
+                scope: int(d, "scope"),
+                ident: string(d, "ident"),
+            },
+            b"PlainKey" => Self::PlainKey {
+                ident: string(d, "ident"),
+            },
+            b"ComplexIdentifier" => Self::ComplexIdentifier,
+            b"UnknownFigure" => Self::UnknownFigure,
+            b"Lambda" => Self::Lambda,
+            b"DictLiteral" => Self::DictLiteral,
+            b"CurlyBracesIdentifier" => Self::CurlyBracesIdentifier,
+            b"Comma" => Self::Comma,
+            b"Colon" => Self::Colon,
+            b"Arrow" => Self::Arrow,
+            b"Comparison" => Self::Comparison {
+                cmp_type: ExprCmpType::from_enum_str(string(d, "cmp_type").as_thinstr())
+                    .unwrap_or(ExprCmpType::Equal),
+                ccs_strategy: ExprCaseCompare::from_enum_str(
+                    string(d, "ccs_strategy").as_thinstr(),
+                )
+                .unwrap_or(ExprCaseCompare::UseOption),
+                invert: d
+                    .get("invert")
+                    .and_then(Object::as_bool)
+                    .unwrap_or_default(),
+            },
+            b"Concat" => Self::Concat,
+            b"ConcatOrSubscript" => Self::ConcatOrSubscript,
+            b"Integer" => Self::Integer {
+                value: int(d, "ivalue"),
+            },
+            b"Float" => Self::Float {
+                value: d
+                    .get("fvalue")
+                    .and_then(Object::as_float)
+                    .unwrap_or_default(),
+            },
+            b"SingleQuotedString" => Self::SingleQuotedString {
+                value: string(d, "svalue"),
+            },
+            b"DoubleQuotedString" => Self::DoubleQuotedString {
+                value: string(d, "svalue"),
+            },
+            b"Or" => Self::Or,
+            b"And" => Self::And,
+            b"UnaryMinus" => Self::UnaryMinus,
+            b"BinaryMinus" => Self::BinaryMinus,
+            b"Not" => Self::Not,
+            b"Multiplication" => Self::Multiplication,
+            b"Division" => Self::Division,
+            b"Mod" => Self::Mod,
+            b"Option" => Self::Option {
+                scope: int(d, "scope"),
+                ident: string(d, "ident"),
+            },
+            b"Environment" => Self::Environment {
+                ident: string(d, "ident"),
+            },
+            b"Assignment" => Self::Assignment {
+                augmentation: ExprAugmentation::from_enum_str(
+                    string(d, "augmentation").as_thinstr(),
+                ),
+            },
+            _ => Self::Other(ty),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::nvim_types::{
+    Array, Boolean, Dict, Float, Integer, Object, Window,
+    opts::win_opts::{Anchor, FooterPos, Relative, Split, Style, TitlePos, WinConfig},
+};
+
+use super::utils::skip_drop_remove_keys;
+
+/// The configuration of a window as returned by
+/// [`win_get_config`](crate::nvim_funcs::window::win_get_config).
+///
+/// Neovim only returns the fields that are relevant to the window, any field that was not
+/// returned is [`None`].
+///
+/// The value can be converted back in to a [`WinConfig`] to modify and reapply it via
+/// [`win_set_config`](crate::nvim_funcs::window::win_set_config).
+#[derive(Clone, Debug, Default)]
+pub struct WinConfigInfo {
+    pub row: Option<Float>,
+    pub col: Option<Float>,
+    pub width: Option<Integer>,
+    pub height: Option<Integer>,
+    pub anchor: Option<Anchor>,
+    pub relative: Option<Relative>,
+    pub split: Option<Split>,
+    pub win: Option<Window>,
+    pub bufpos: Option<Array>,
+    pub external: Option<Boolean>,
+    pub focusable: Option<Boolean>,
+    pub mouse: Option<Boolean>,
+    pub vertical: Option<Boolean>,
+    pub zindex: Option<Integer>,
+    pub border: Option<Array>,
+    pub title: Option<Object>,
+    pub title_pos: Option<TitlePos>,
+    pub footer: Option<Object>,
+    pub footer_pos: Option<FooterPos>,
+    pub style: Option<Style>,
+    pub noautocmd: Option<Boolean>,
+    pub fixed: Option<Boolean>,
+    pub hide: Option<Boolean>,
+}
+
+impl<'a> From<&'a WinConfigInfo> for WinConfig<'a> {
+    fn from(value: &'a WinConfigInfo) -> Self {
+        let mut config = WinConfig::default();
+        macro_rules! set {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(v) = value.$field {
+                        config.$field(v);
+                    }
+                )*
+            };
+        }
+        set!(
+            row, col, width, height, anchor, relative, split, win, external, focusable, mouse,
+            vertical, zindex, title_pos, footer_pos, style, noautocmd, fixed, hide
+        );
+        if let Some(bufpos) = &value.bufpos {
+            config.bufpos(bufpos);
+        }
+        if let Some(border) = &value.border {
+            config.border_chars(border);
+        }
+        match &value.title {
+            Some(Object::String(title)) => config.title(title),
+            Some(Object::Array(chunks)) => config.title_chunks(chunks),
+            _ => &mut config,
+        };
+        match &value.footer {
+            Some(Object::String(footer)) => config.footer(footer),
+            Some(Object::Array(chunks)) => config.footer_chunks(chunks),
+            _ => &mut config,
+        };
+
+        config
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl WinConfigInfo {
+    /// Copies the dictionary returned by `nvim_win_get_config` over RPC
+    pub(crate) fn from_dict(d: &Dict) -> Self {
+        let get = |key: &str| d.get(key);
+        let bool = |key: &str| get(key).and_then(Object::as_bool);
+        let int = |key: &str| get(key).and_then(Object::as_int);
+        let str = |key: &str| get(key).and_then(Object::as_string).map(|s| s.as_thinstr());
+        Self {
+            row: get("row").and_then(Object::as_float),
+            col: get("col").and_then(Object::as_float),
+            width: int("width"),
+            height: int("height"),
+            anchor: str("anchor").and_then(Anchor::from_enum_str),
+            relative: str("relative").and_then(Relative::from_enum_str),
+            split: str("split").and_then(Split::from_enum_str),
+            win: get("win").and_then(Object::as_window),
+            bufpos: get("bufpos").and_then(Object::as_array).cloned(),
+            external: bool("external"),
+            focusable: bool("focusable"),
+            mouse: bool("mouse"),
+            vertical: bool("vertical"),
+            zindex: int("zindex"),
+            border: get("border").and_then(Object::as_array).cloned(),
+            title: get("title").cloned(),
+            title_pos: str("title_pos").and_then(TitlePos::from_enum_str),
+            footer: get("footer").cloned(),
+            footer_pos: str("footer_pos").and_then(FooterPos::from_enum_str),
+            style: str("style").and_then(Style::from_enum_str),
+            noautocmd: bool("noautocmd"),
+            fixed: bool("fixed"),
+            hide: bool("hide"),
+        }
+    }
+}
+
+/// The result of [`win_text_height`](crate::nvim_funcs::window::win_text_height)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextHeight {
+    /// The total number of screen lines occupied by the range.
+    pub all: Integer,
+    /// The number of diff filler or virtual lines among them.
+    pub fill: Integer,
+    /// The row where the calculation stopped, only returned when `max_height` was reached.
+    pub end_row: Option<Integer>,
+    /// The virtual column where the calculation stopped, only returned when `max_height` was
+    /// reached.
+    pub end_vcol: Option<Integer>,
+}
+
+impl TextHeight {
+    pub(crate) fn from_c_func_ret(d: &mut Dict) -> Self {
+        let [all, fill, end_row, end_vcol] = skip_drop_remove_keys(
+            d,
+            &["all", "fill", "end_row", "end_vcol"],
+            Some(|k| matches!(k, "end_row" | "end_vcol").then_some(Object::Null)),
+        )
+        .unwrap();
+
+        Self {
+            all: all.as_int().unwrap(),
+            fill: fill.as_int().unwrap(),
+            end_row: end_row.as_int(),
+            end_vcol: end_vcol.as_int(),
+        }
+    }
+}
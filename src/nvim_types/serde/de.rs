@@ -0,0 +1,526 @@
+use std::fmt;
+
+use serde::{
+    Deserialize,
+    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+use crate::nvim_types::{
+    Array, Dict, Integer, KVec, KeyValuePair, Object, OwnedThinString, ThinString,
+};
+
+use super::Error;
+
+/// A deserializer that reads values from an [`Object`]
+///
+/// Usually used through [`from_object`](super::from_object).
+#[derive(Clone, Copy, Debug)]
+pub struct Deserializer<'de> {
+    obj: &'de Object,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(obj: &'de Object) -> Self {
+        Self { obj }
+    }
+}
+
+fn visit_thinstr<'de, V: Visitor<'de>>(s: ThinString<'de>, visitor: V) -> Result<V::Value, Error> {
+    match std::str::from_utf8(s.as_slice()) {
+        Ok(s) => visitor.visit_borrowed_str(s),
+        Err(_) => visitor.visit_borrowed_bytes(s.as_slice()),
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj {
+            Object::Null => visitor.visit_unit(),
+            Object::Bool(b) => visitor.visit_bool(*b),
+            Object::Integer(i) => visitor.visit_i64(*i),
+            Object::Float(f) => visitor.visit_f64(*f),
+            Object::String(s) => visit_thinstr(s.as_thinstr(), visitor),
+            Object::Array(a) => visitor.visit_seq(SeqDeserializer::new(a)),
+            Object::Dict(d) => visitor.visit_map(MapDeserializer::new(d)),
+            Object::Buffer(b) => visitor.visit_i64(b.as_int().into()),
+            Object::Window(w) => visitor.visit_i64(w.as_int().into()),
+            Object::TabPage(t) => visitor.visit_i64(t.as_int().into()),
+            Object::LuaRef(_) => Err(Error::new("lua references cannot be deserialized")),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj {
+            Object::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    // Lua does not have separate integer and float types, a whole number is always read as an
+    // integer
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj {
+            Object::Integer(i) => visitor.visit_f64(*i as f64),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    // an empty table is read as an empty array
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj {
+            Object::Dict(d) if d.is_empty() => visitor.visit_seq(SeqDeserializer::new(&[])),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.obj {
+            Object::Array(a) if a.is_empty() => visitor.visit_map(MapDeserializer::new(&[])),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.obj {
+            Object::String(_) => visitor.visit_enum(EnumDeserializer {
+                variant: self,
+                value: None,
+            }),
+            Object::Dict(d) => match d.0.as_slice() {
+                [KeyValuePair { key, object }] => visitor.visit_enum(EnumDeserializer {
+                    variant: KeyDeserializer(key.as_thinstr()),
+                    value: Some((key.as_thinstr(), object)),
+                }),
+                _ => Err(de::Error::invalid_length(
+                    d.len(),
+                    &"a dictionary with a single key",
+                )),
+            },
+            _ => Err(de::Error::invalid_type(
+                self.unexpected(),
+                &"a string or a dictionary with a single key",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string bytes byte_buf unit
+        unit_struct identifier ignored_any
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    fn unexpected(&self) -> de::Unexpected<'de> {
+        match self.obj {
+            Object::Null => de::Unexpected::Unit,
+            Object::Bool(b) => de::Unexpected::Bool(*b),
+            Object::Integer(i) => de::Unexpected::Signed(*i),
+            Object::Float(f) => de::Unexpected::Float(*f),
+            Object::String(s) => match std::str::from_utf8(s.as_thinstr().as_slice()) {
+                Ok(s) => de::Unexpected::Str(s),
+                Err(_) => de::Unexpected::Bytes(s.as_thinstr().as_slice()),
+            },
+            Object::Array(_) => de::Unexpected::Seq,
+            Object::Dict(_) => de::Unexpected::Map,
+            Object::LuaRef(_) => de::Unexpected::Other("lua reference"),
+            Object::Buffer(_) | Object::Window(_) | Object::TabPage(_) => {
+                de::Unexpected::Other("handle")
+            }
+        }
+    }
+}
+
+struct SeqDeserializer<'de> {
+    iter: std::iter::Enumerate<std::slice::Iter<'de, Object>>,
+}
+
+impl<'de> SeqDeserializer<'de> {
+    fn new(items: &'de [Object]) -> Self {
+        Self {
+            iter: items.iter().enumerate(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        let Some((i, obj)) = self.iter.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(Deserializer::new(obj))
+            .map(Some)
+            .map_err(|err| err.at_index(i))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer<'de> {
+    iter: std::slice::Iter<'de, KeyValuePair>,
+    value: Option<&'de KeyValuePair>,
+}
+
+impl<'de> MapDeserializer<'de> {
+    fn new(items: &'de [KeyValuePair]) -> Self {
+        Self {
+            iter: items.iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(kv) = self.iter.next() else {
+            return Ok(None);
+        };
+        self.value = Some(kv);
+        seed.deserialize(KeyDeserializer(kv.key.as_thinstr()))
+            .map(Some)
+            .map_err(|err| err.at_key(kv.key.as_thinstr().to_str_lossy()))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let kv = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer::new(&kv.object))
+            .map_err(|err| err.at_key(kv.key.as_thinstr().to_str_lossy()))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Reads a dictionary key
+///
+/// Integer keys are parsed from the string so that maps with integer keys can be read.
+struct KeyDeserializer<'de>(ThinString<'de>);
+
+macro_rules! int_key {
+    ($($fn:ident => $visit:ident: $ty:ty),+) => {
+        $(
+            fn $fn<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.to_str().ok().and_then(|s| s.parse::<$ty>().ok()) {
+                    Some(i) => visitor.$visit(i),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+        )+
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visit_thinstr(self.0, visitor)
+    }
+
+    int_key!(
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64
+    );
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(EnumDeserializer {
+            variant: self,
+            value: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The variant name and its value for a variant stored as a dictionary
+struct EnumDeserializer<'de, D> {
+    variant: D,
+    value: Option<(ThinString<'de>, &'de Object)>,
+}
+
+impl<'de, D: de::Deserializer<'de, Error = Error>> EnumAccess<'de> for EnumDeserializer<'de, D> {
+    type Error = Error;
+    type Variant = VariantDeserializer<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer<'de>), Error> {
+        let variant = seed.deserialize(self.variant)?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer<'de> {
+    value: Option<(ThinString<'de>, &'de Object)>,
+}
+
+impl<'de> VariantDeserializer<'de> {
+    fn value<T>(
+        self,
+        expected: &str,
+        f: impl FnOnce(Deserializer<'de>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        match self.value {
+            Some((name, obj)) => {
+                f(Deserializer::new(obj)).map_err(|err| err.at_key(name.to_str_lossy()))
+            }
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &expected,
+            )),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some((_, Object::Null)) => Ok(()),
+            Some((name, obj)) => Err(Error::new(format_args!(
+                "unit variant cannot have a value, found {}",
+                Deserializer::new(obj).unexpected()
+            ))
+            .at_key(name.to_str_lossy())),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        self.value("newtype variant", |de| seed.deserialize(de))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.value("tuple variant", |de| {
+            de::Deserializer::deserialize_seq(de, visitor)
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value("struct variant", |de| {
+            de::Deserializer::deserialize_map(de, visitor)
+        })
+    }
+}
+
+struct ObjectVisitor;
+
+impl<'de> Visitor<'de> for ObjectVisitor {
+    type Value = Object;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value that can be stored in an Object")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Object, E> {
+        Ok(Object::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Object, E> {
+        Ok(Object::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Object, E> {
+        Integer::try_from(v)
+            .map(Object::Integer)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Object, E> {
+        Ok(Object::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Object, E> {
+        Ok(Object::String(OwnedThinString::from(v)))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Object, E> {
+        Ok(Object::String(OwnedThinString::from(v)))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Null)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Object, D::Error> {
+        Object::deserialize(deserializer)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Object, A::Error> {
+        ArrayVisitor.visit_seq(seq).map(Object::Array)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Object, A::Error> {
+        DictVisitor.visit_map(map).map(Object::Dict)
+    }
+}
+
+/// Buffers, windows and tabpages are read as integers as they cannot be told apart from one.
+impl<'de> Deserialize<'de> for Object {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ObjectVisitor)
+    }
+}
+
+struct ArrayVisitor;
+
+impl<'de> Visitor<'de> for ArrayVisitor {
+    type Value = Array;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Array, A::Error> {
+        let mut items = KVec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(obj) = seq.next_element()? {
+            items.push(obj);
+        }
+        Ok(Array(items))
+    }
+}
+
+impl<'de> Deserialize<'de> for Array {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(ArrayVisitor)
+    }
+}
+
+struct DictVisitor;
+
+impl<'de> Visitor<'de> for DictVisitor {
+    type Value = Dict;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map with string keys")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Dict, A::Error> {
+        let mut d = Dict(KVec::with_capacity(map.size_hint().unwrap_or(0)));
+        while let Some((key, object)) = map.next_entry::<OwnedThinString, Object>()? {
+            d.insert(key, object);
+        }
+        Ok(d)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dict {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(DictVisitor)
+    }
+}
+
+struct StringVisitor;
+
+impl<'de> Visitor<'de> for StringVisitor {
+    type Value = OwnedThinString;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<OwnedThinString, E> {
+        Ok(OwnedThinString::from(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<OwnedThinString, E> {
+        Ok(OwnedThinString::from(v))
+    }
+}
+
+impl<'de> Deserialize<'de> for OwnedThinString {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(StringVisitor)
+    }
+}
@@ -0,0 +1,231 @@
+//! Conversions between [`Object`] and types that implement [`serde::Serialize`] or
+//! [`serde::Deserialize`]
+//!
+//! The conversions follow the conventions of Neovim's Lua API:
+//! - [`None`], `()` and unit structs are converted to [`Object::Null`] which is `nil` in Lua.
+//!   `vim.NIL` read from Lua is also [`Object::Null`].
+//! - Structs and maps are converted to a [`Dict`], sequences and tuples to an [`Array`]. An empty
+//!   Lua table cannot be told apart so an empty [`Array`] can be read as an empty map and the other
+//!   way around.
+//! - Enums are externally tagged. A unit variant is converted to its name as a string, other
+//!   variants to a [`Dict`] with the variant name as its only key.
+//!
+//! [`Array`]: crate::nvim_types::Array
+//! [`Dict`]: crate::nvim_types::Dict
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::nvim_types::serde::{from_object, to_object};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Config {
+//!     width: u32,
+//!     border: Option<String>,
+//! }
+//!
+//! let config = Config { width: 80, border: None };
+//! let obj = to_object(&config).unwrap();
+//! assert_eq!(from_object::<Config>(&obj).unwrap(), config);
+//! ```
+
+mod de;
+mod ser;
+
+use std::{error::Error as StdError, fmt::Display};
+
+pub use de::Deserializer;
+pub use ser::Serializer;
+
+use crate::nvim_types::Object;
+
+/// Converts the value to an [`Object`]
+pub fn to_object<T: ?Sized + serde::Serialize>(value: &T) -> Result<Object, Error> {
+    value.serialize(Serializer)
+}
+
+/// Reads the value from an [`Object`]
+///
+/// Strings can be borrowed from the [`Object`] as long as they are valid UTF-8.
+pub fn from_object<'de, T: serde::Deserialize<'de>>(obj: &'de Object) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(obj))
+}
+
+/// A part of the path to the value that failed to convert
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// An error that occurred while converting to or from an [`Object`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    /// The outermost segment is stored last so that segments can be pushed while unwinding
+    path: Vec<PathSegment>,
+}
+
+impl Error {
+    fn new<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            path: Vec::new(),
+        }
+    }
+
+    /// The error message without the path
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The path to the value that caused the error, starting from the outermost value
+    pub fn path(&self) -> impl Iterator<Item = &PathSegment> {
+        self.path.iter().rev()
+    }
+
+    fn at_key<K: Display>(mut self, key: K) -> Self {
+        self.path.push(PathSegment::Key(key.to_string()));
+        self
+    }
+
+    fn at_index(mut self, index: usize) -> Self {
+        self.path.push(PathSegment::Index(index));
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.message);
+        }
+
+        let mut first = true;
+        for segment in self.path() {
+            match segment {
+                PathSegment::Key(key) if first => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(i) => write!(f, "[{}]", i)?,
+            }
+            first = false;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl StdError for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(msg)
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{from_object, to_object};
+    use crate::nvim_types::{Array, Dict, Object, OwnedThinString};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Border {
+        None,
+        Single,
+        Custom(Vec<String>),
+        Padded { top: u8, bottom: u8 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        width: u32,
+        ratio: f64,
+        enabled: bool,
+        title: Option<String>,
+        borders: Vec<Border>,
+        highlights: BTreeMap<String, i64>,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let config = Config {
+            name: String::from("nvimium"),
+            width: 80,
+            ratio: 0.5,
+            enabled: true,
+            title: None,
+            borders: vec![
+                Border::None,
+                Border::Custom(vec![String::from("+")]),
+                Border::Padded { top: 1, bottom: 2 },
+            ],
+            highlights: BTreeMap::new(),
+        };
+
+        let obj = to_object(&config).unwrap();
+        let Object::Dict(d) = &obj else {
+            panic!("struct should be a dict");
+        };
+        assert_eq!(d.get("title"), Some(&Object::Null));
+        assert_eq!(d.get("highlights"), Some(&Object::Dict(Dict::default())));
+        let borders = d.get("borders").and_then(Object::as_array).unwrap();
+        assert_eq!(borders[0], Object::String(OwnedThinString::from("None")));
+
+        assert_eq!(from_object::<Config>(&obj).unwrap(), config);
+    }
+
+    #[test]
+    fn empty_table() {
+        // an empty lua table is read as an empty array
+        let empty = Object::Array(Array::default());
+        assert_eq!(
+            from_object::<BTreeMap<String, i64>>(&empty).unwrap(),
+            BTreeMap::new()
+        );
+        let empty = Object::Dict(Dict::default());
+        assert_eq!(from_object::<Vec<i64>>(&empty).unwrap(), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn error_path() {
+        let obj = Object::Dict(Dict::from_iter([
+            ("name", Object::String(OwnedThinString::from("nvimium"))),
+            ("width", Object::Integer(80)),
+            ("ratio", Object::Float(0.5)),
+            ("enabled", Object::Bool(true)),
+            (
+                "borders",
+                Object::Array(Array::from(
+                    [
+                        Object::String(OwnedThinString::from("Single")),
+                        Object::Dict(Dict::from_iter([(
+                            "Padded",
+                            Object::Dict(Dict::from_iter([
+                                ("top", Object::Integer(1)),
+                                ("bottom", Object::Integer(-1)),
+                            ])),
+                        )])),
+                    ]
+                    .as_slice(),
+                )),
+            ),
+            ("highlights", Object::Array(Array::default())),
+        ]));
+
+        let err = from_object::<Config>(&obj).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "borders[1].Padded.bottom: invalid value: integer `-1`, expected u8"
+        );
+    }
+}
@@ -0,0 +1,541 @@
+use serde::{
+    Serialize,
+    ser::{self, Impossible, SerializeMap as _, SerializeSeq as _},
+};
+
+use crate::nvim_types::{Array, Dict, Integer, KVec, Object, OwnedThinString, ThinString};
+
+use super::Error;
+
+/// A serializer that converts values to an [`Object`]
+///
+/// Usually used through [`to_object`](super::to_object).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Object;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeDict;
+    type SerializeStructVariant = SerializeVariant<SerializeDict>;
+
+    fn serialize_bool(self, v: bool) -> Result<Object, Error> {
+        Ok(Object::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Object, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Object, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Object, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Object, Error> {
+        Ok(Object::Integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Object, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Object, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Object, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Object, Error> {
+        Integer::try_from(v)
+            .map(Object::Integer)
+            .map_err(|_| Error::new(format_args!("integer `{}` does not fit in an i64", v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Object, Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Object, Error> {
+        Ok(Object::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Object, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Object, Error> {
+        Ok(Object::String(OwnedThinString::from(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Object, Error> {
+        Ok(Object::String(OwnedThinString::from(v)))
+    }
+
+    fn serialize_none(self) -> Result<Object, Error> {
+        Ok(Object::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Object, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Object, Error> {
+        Ok(Object::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Object, Error> {
+        Ok(Object::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Object, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Object, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Object, Error> {
+        let value = value.serialize(self).map_err(|err| err.at_key(variant))?;
+        let mut d = Dict::default();
+        d.insert(variant, value);
+        Ok(Object::Dict(d))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray(KVec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeArray>, Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            dict: SerializeDict(Dict(KVec::with_capacity(len.unwrap_or(0)))),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeDict, Error> {
+        Ok(SerializeDict(Dict(KVec::with_capacity(len))))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeDict>, Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_struct(name, len)?,
+        })
+    }
+}
+
+pub struct SerializeArray(KVec<Object>);
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let value = value
+            .serialize(Serializer)
+            .map_err(|err| err.at_index(self.0.len()))?;
+        self.0.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        Ok(Object::Array(Array(self.0)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+pub struct SerializeDict(Dict);
+
+impl ser::SerializeStruct for SerializeDict {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let value = value.serialize(Serializer).map_err(|err| err.at_key(key))?;
+        self.0.insert(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        Ok(Object::Dict(self.0))
+    }
+}
+
+pub struct SerializeMap {
+    dict: SerializeDict,
+    key: Option<OwnedThinString>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        let value = value
+            .serialize(Serializer)
+            .map_err(|err| err.at_key(key.as_thinstr().to_str_lossy()))?;
+        self.dict.0.insert(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        Ok(Object::Dict(self.dict.0))
+    }
+}
+
+pub struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> SerializeVariant<S> {
+    fn end(variant: &'static str, value: Result<Object, Error>) -> Result<Object, Error> {
+        let mut d = Dict::default();
+        d.insert(variant, value?);
+        Ok(Object::Dict(d))
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+            .map_err(|err| err.at_key(self.variant))
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        Self::end(self.variant, ser::SerializeSeq::end(self.inner))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeDict> {
+    type Ok = Object;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+            .map_err(|err| err.at_key(self.variant))
+    }
+
+    fn end(self) -> Result<Object, Error> {
+        Self::end(self.variant, ser::SerializeStruct::end(self.inner))
+    }
+}
+
+/// Serializes the keys of a map, which must be strings in a [`Dict`]
+///
+/// Integers and characters are converted to strings.
+struct KeySerializer;
+
+fn key_err() -> Error {
+    Error::new("dictionary keys must be strings")
+}
+
+macro_rules! int_key {
+    ($($fn:ident: $ty:ty),+) => {
+        $(
+            fn $fn(self, v: $ty) -> Result<OwnedThinString, Error> {
+                Ok(OwnedThinString::from(v.to_string().as_str()))
+            }
+        )+
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = OwnedThinString;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<OwnedThinString, Error>;
+    type SerializeTuple = Impossible<OwnedThinString, Error>;
+    type SerializeTupleStruct = Impossible<OwnedThinString, Error>;
+    type SerializeTupleVariant = Impossible<OwnedThinString, Error>;
+    type SerializeMap = Impossible<OwnedThinString, Error>;
+    type SerializeStruct = Impossible<OwnedThinString, Error>;
+    type SerializeStructVariant = Impossible<OwnedThinString, Error>;
+
+    int_key!(
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64
+    );
+
+    fn serialize_bool(self, _v: bool) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_char(self, v: char) -> Result<OwnedThinString, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<OwnedThinString, Error> {
+        Ok(OwnedThinString::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<OwnedThinString, Error> {
+        Ok(OwnedThinString::from(v))
+    }
+
+    fn serialize_none(self) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_unit(self) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<OwnedThinString, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<OwnedThinString, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<OwnedThinString, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(key_err())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(key_err())
+    }
+}
+
+/// Serializes a string as `str` if it is valid UTF-8, otherwise as bytes
+fn serialize_thinstr<S: ser::Serializer>(
+    s: ThinString<'_>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match s.to_str() {
+        Ok(s) => serializer.serialize_str(s),
+        Err(_) => serializer.serialize_bytes(s.as_slice()),
+    }
+}
+
+impl Serialize for OwnedThinString {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_thinstr(self.as_thinstr(), serializer)
+    }
+}
+
+impl Serialize for Array {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for obj in self.iter() {
+            seq.serialize_element(obj)?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for Dict {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for kv in self.iter() {
+            map.serialize_entry(&kv.key, &kv.object)?;
+        }
+        map.end()
+    }
+}
+
+/// Buffers, windows and tabpages are serialized as their handle, Lua references cannot be
+/// serialized.
+impl Serialize for Object {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::Integer(i) => serializer.serialize_i64(*i),
+            Self::Float(f) => serializer.serialize_f64(*f),
+            Self::String(s) => s.serialize(serializer),
+            Self::Array(a) => a.serialize(serializer),
+            Self::Dict(d) => d.serialize(serializer),
+            Self::Buffer(b) => serializer.serialize_i32(b.as_int()),
+            Self::Window(w) => serializer.serialize_i32(w.as_int()),
+            Self::TabPage(t) => serializer.serialize_i32(t.as_int()),
+            Self::LuaRef(_) => Err(ser::Error::custom("lua references cannot be serialized")),
+        }
+    }
+}
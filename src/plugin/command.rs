@@ -0,0 +1,757 @@
+//! A router for user commands with subcommands
+//!
+//! A single Ex command such as `:MyPlugin` is registered and its arguments are dispatched to the
+//! handler of the matching [`Subcommand`]. Completion is generated from the subcommand names,
+//! flags and the completers of positional arguments.
+//!
+//! Arguments are split into:
+//! - Subcommand names, which are matched before anything else.
+//! - Flags such as `--force` and options such as `--level=2`.
+//! - Positional arguments, which are assigned to the declared arguments in order. Everything after
+//!   a lone `--` is treated as a positional argument.
+//!
+//! Usage errors and errors returned from handlers are printed with
+//! [`echo`](crate::nvim_funcs::global::echo).
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{
+//!     nvim_types::{Error, opts::create_user_command::CreateUserCommandOpts},
+//!     plugin::command::{CommandRouter, Subcommand},
+//! };
+//!
+//! fn register() -> Result<(), Error> {
+//!     let mut add = Subcommand::new("add");
+//!     add.arg("name")
+//!         .optional_arg("count")
+//!         .flag("force")
+//!         .complete_arg("name", |_| vec![String::from("alpha"), String::from("beta")])
+//!         .handler(|args| {
+//!             let name: String = args.get("name")?;
+//!             let count: u32 = args.get_opt("count")?.unwrap_or(1);
+//!             let _ = (name, count, args.flag("force"));
+//!             Ok(())
+//!         });
+//!
+//!     let mut router = CommandRouter::new("MyPlugin");
+//!     router.subcommand(add);
+//!     // `:MyPlugin add alpha 2 --force`
+//!     router.register(&mut CreateUserCommandOpts::default())
+//! }
+//! ```
+
+use std::{cell::RefCell, convert::Infallible, error::Error, fmt::Display, path::PathBuf, rc::Rc};
+
+use crate::{
+    nvim_funcs::{command::create_user_command, global::echo},
+    nvim_types::{
+        Array, Error as NvError, KVec, Object, OwnedThinString,
+        args::{user_command::UserCommandArgs, user_command_complete_cb::UserCommandCompleteArgs},
+        func_types::{create_user_command::UserCommand, echo::Echo},
+        lua::LuaInteger,
+        opts::{
+            create_user_command::{CreateUserCommandOpts, UserCommandNarg},
+            echo::EchoOpts,
+        },
+    },
+};
+
+/// A value that can be parsed from a command argument
+pub trait FromArg: Sized {
+    /// Parses the argument, the error describes what was expected such as "integer"
+    fn from_arg(arg: &str) -> Result<Self, String>;
+}
+
+impl FromArg for String {
+    fn from_arg(arg: &str) -> Result<Self, String> {
+        Ok(arg.to_string())
+    }
+}
+
+impl FromArg for PathBuf {
+    fn from_arg(arg: &str) -> Result<Self, String> {
+        Ok(PathBuf::from(arg))
+    }
+}
+
+impl FromArg for bool {
+    fn from_arg(arg: &str) -> Result<Self, String> {
+        match arg {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(String::from("true or false")),
+        }
+    }
+}
+
+impl FromArg for f64 {
+    fn from_arg(arg: &str) -> Result<Self, String> {
+        arg.parse().map_err(|_| String::from("number"))
+    }
+}
+
+macro_rules! int_from_arg {
+    ($($int:ty),*) => {
+        $(
+            impl FromArg for $int {
+                fn from_arg(arg: &str) -> Result<Self, String> {
+                    arg.parse().map_err(|_| {
+                        format!("integer between {} and {}", <$int>::MIN, <$int>::MAX)
+                    })
+                }
+            }
+        )*
+    };
+}
+
+int_from_arg!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// An error returned from a subcommand handler
+///
+/// Any [`Error`] can be converted with `?`. The error is printed along with the usage of the
+/// subcommand if it is a [`CommandError::Usage`].
+#[derive(Debug)]
+pub enum CommandError {
+    /// The arguments were incorrect
+    Usage(String),
+    /// The handler failed
+    Failed(Box<dyn Error>),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage(msg) => write!(f, "{}", msg),
+            Self::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: 'static + Error> From<E> for CommandError {
+    fn from(value: E) -> Self {
+        Self::Failed(Box::new(value))
+    }
+}
+
+/// The arguments passed to a subcommand handler
+#[derive(Debug, Default)]
+pub struct CommandArgs {
+    positional: Vec<(String, Vec<String>)>,
+    flags: Vec<String>,
+    options: Vec<(String, String)>,
+    pub bang: bool,
+    pub line1: LuaInteger,
+    pub line2: LuaInteger,
+    pub range: LuaInteger,
+    pub count: LuaInteger,
+}
+
+impl CommandArgs {
+    fn values(&self, name: &str) -> &[String] {
+        self.positional
+            .iter()
+            .find(|(arg, _)| arg == name)
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn parse<T: FromArg>(name: &str, value: &str) -> Result<T, CommandError> {
+        T::from_arg(value).map_err(|expected| {
+            CommandError::Usage(format!("{}: expected {}, got {:?}", name, expected, value))
+        })
+    }
+
+    /// Returns a required argument
+    ///
+    /// # Panics
+    ///
+    /// If the subcommand does not declare the argument.
+    pub fn get<T: FromArg>(&self, name: &str) -> Result<T, CommandError> {
+        match self.values(name) {
+            [value, ..] => Self::parse(name, value),
+            [] => panic!("argument {:?} is not declared or is not required", name),
+        }
+    }
+
+    /// Returns an optional argument
+    pub fn get_opt<T: FromArg>(&self, name: &str) -> Result<Option<T>, CommandError> {
+        self.values(name)
+            .first()
+            .map(|value| Self::parse(name, value))
+            .transpose()
+    }
+
+    /// Returns every value of a variadic argument
+    pub fn get_many<T: FromArg>(&self, name: &str) -> Result<Vec<T>, CommandError> {
+        self.values(name)
+            .iter()
+            .map(|value| Self::parse(name, value))
+            .collect()
+    }
+
+    /// Returns true if the flag was passed
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// Returns the value of an option passed as `--name=value`
+    pub fn option<T: FromArg>(&self, name: &str) -> Result<Option<T>, CommandError> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| Self::parse(name, value))
+            .transpose()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArgKind {
+    Required,
+    Optional,
+    Variadic,
+}
+
+type Completer = Box<dyn Fn(&str) -> Vec<String>>;
+type Handler = Box<dyn Fn(&CommandArgs) -> Result<(), CommandError>>;
+
+struct ArgSpec {
+    name: String,
+    kind: ArgKind,
+    complete: Option<Completer>,
+}
+
+/// A subcommand along with its arguments, handler and nested subcommands
+pub struct Subcommand {
+    name: String,
+    desc: Option<String>,
+    args: Vec<ArgSpec>,
+    flags: Vec<String>,
+    options: Vec<(String, Option<Completer>)>,
+    subcommands: Vec<Subcommand>,
+    handler: Option<Handler>,
+}
+
+impl Subcommand {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            desc: None,
+            args: Vec::new(),
+            flags: Vec::new(),
+            options: Vec::new(),
+            subcommands: Vec::new(),
+            handler: None,
+        }
+    }
+
+    /// A description shown in usage errors
+    pub fn desc<S: Into<String>>(&mut self, desc: S) -> &mut Self {
+        self.desc = Some(desc.into());
+        self
+    }
+
+    fn push_arg(&mut self, name: String, kind: ArgKind) -> &mut Self {
+        assert!(
+            self.args
+                .last()
+                .is_none_or(|arg| arg.kind != ArgKind::Variadic),
+            "a variadic argument must be the last argument"
+        );
+        assert!(
+            kind != ArgKind::Required || self.args.iter().all(|arg| arg.kind == ArgKind::Required),
+            "a required argument cannot follow an optional argument"
+        );
+        self.args.push(ArgSpec {
+            name,
+            kind,
+            complete: None,
+        });
+        self
+    }
+
+    /// Declares a required positional argument
+    ///
+    /// # Panics
+    ///
+    /// If it follows an optional or variadic argument.
+    pub fn arg<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.push_arg(name.into(), ArgKind::Required)
+    }
+
+    /// Declares an optional positional argument
+    ///
+    /// # Panics
+    ///
+    /// If it follows a variadic argument.
+    pub fn optional_arg<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.push_arg(name.into(), ArgKind::Optional)
+    }
+
+    /// Declares a positional argument that takes the rest of the arguments, it must be the last
+    /// argument
+    pub fn variadic_arg<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.push_arg(name.into(), ArgKind::Variadic)
+    }
+
+    /// Sets the completer of a declared positional argument
+    ///
+    /// The completer is passed the text being completed and may return every candidate, the
+    /// candidates are filtered by prefix afterwards.
+    ///
+    /// # Panics
+    ///
+    /// If the argument was not declared.
+    pub fn complete_arg<F: 'static + Fn(&str) -> Vec<String>>(
+        &mut self,
+        name: &str,
+        f: F,
+    ) -> &mut Self {
+        let arg = self
+            .args
+            .iter_mut()
+            .find(|arg| arg.name == name)
+            .unwrap_or_else(|| panic!("argument {:?} is not declared", name));
+        arg.complete = Some(Box::new(f));
+        self
+    }
+
+    /// Declares a flag passed as `--name`
+    pub fn flag<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.flags.push(name.into());
+        self
+    }
+
+    /// Declares an option passed as `--name=value`
+    pub fn option<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.options.push((name.into(), None));
+        self
+    }
+
+    /// Declares an option passed as `--name=value` with a completer for its value
+    pub fn option_with_completion<S: Into<String>, F: 'static + Fn(&str) -> Vec<String>>(
+        &mut self,
+        name: S,
+        f: F,
+    ) -> &mut Self {
+        self.options.push((name.into(), Some(Box::new(f))));
+        self
+    }
+
+    /// Adds a nested subcommand, an existing subcommand with the same name is replaced
+    pub fn subcommand(&mut self, sub: Subcommand) -> &mut Self {
+        self.subcommands.retain(|s| s.name != sub.name);
+        self.subcommands.push(sub);
+        self
+    }
+
+    /// Sets the function called when this subcommand is run
+    pub fn handler<F: 'static + Fn(&CommandArgs) -> Result<(), CommandError>>(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.handler = Some(Box::new(f));
+        self
+    }
+
+    fn find(&self, name: &str) -> Option<&Subcommand> {
+        self.subcommands.iter().find(|sub| sub.name == name)
+    }
+
+    /// Descends into the subcommands named by the leading arguments
+    fn walk<'a, 'b>(
+        &'a self,
+        args: &'b [&'b str],
+    ) -> (&'a Subcommand, Vec<&'a str>, &'b [&'b str]) {
+        let mut node = self;
+        let mut path = Vec::new();
+        let mut rest = args;
+        while let [first, tail @ ..] = rest {
+            let Some(sub) = node.find(first) else {
+                break;
+            };
+            path.push(sub.name.as_str());
+            node = sub;
+            rest = tail;
+        }
+        (node, path, rest)
+    }
+
+    fn usage(&self, cmd: &str, path: &[&str]) -> String {
+        let mut usage = format!("Usage: :{}", cmd);
+        for name in path {
+            usage.push(' ');
+            usage.push_str(name);
+        }
+        if !self.subcommands.is_empty() && self.handler.is_none() {
+            let names: Vec<&str> = self.subcommands.iter().map(|s| s.name.as_str()).collect();
+            usage.push_str(&format!(" {{{}}}", names.join("|")));
+        }
+        for arg in &self.args {
+            match arg.kind {
+                ArgKind::Required => usage.push_str(&format!(" <{}>", arg.name)),
+                ArgKind::Optional => usage.push_str(&format!(" [{}]", arg.name)),
+                ArgKind::Variadic => usage.push_str(&format!(" [{}...]", arg.name)),
+            }
+        }
+        for flag in &self.flags {
+            usage.push_str(&format!(" [--{}]", flag));
+        }
+        for (option, _) in &self.options {
+            usage.push_str(&format!(" [--{}=<value>]", option));
+        }
+        if let Some(desc) = &self.desc {
+            usage.push_str("\n    ");
+            usage.push_str(desc);
+        }
+        usage
+    }
+
+    /// Assigns the arguments that follow the subcommand names
+    fn parse(&self, rest: &[&str]) -> Result<CommandArgs, String> {
+        let mut parsed = CommandArgs::default();
+        let mut positional = Vec::new();
+        let mut only_positional = false;
+        for arg in rest {
+            if only_positional || !arg.starts_with("--") {
+                positional.push(*arg);
+                continue;
+            }
+            if *arg == "--" {
+                only_positional = true;
+                continue;
+            }
+            let name = &arg[2..];
+            if let Some((name, value)) = name.split_once('=') {
+                if !self.options.iter().any(|(option, _)| option == name) {
+                    return Err(format!("unknown option '--{}'", name));
+                }
+                parsed.options.push((name.to_string(), value.to_string()));
+            } else if self.flags.iter().any(|flag| flag == name) {
+                parsed.flags.push(name.to_string());
+            } else if self.options.iter().any(|(option, _)| option == name) {
+                return Err(format!("option '--{}' requires a value", name));
+            } else {
+                return Err(format!("unknown flag '--{}'", name));
+            }
+        }
+
+        let mut values = positional.into_iter();
+        for arg in &self.args {
+            let taken: Vec<String> = match arg.kind {
+                ArgKind::Required => match values.next() {
+                    Some(value) => vec![value.to_string()],
+                    None => return Err(format!("missing argument <{}>", arg.name)),
+                },
+                ArgKind::Optional => values.next().map(str::to_string).into_iter().collect(),
+                ArgKind::Variadic => values.by_ref().map(str::to_string).collect(),
+            };
+            parsed.positional.push((arg.name.clone(), taken));
+        }
+        if let Some(extra) = values.next() {
+            return Err(format!("unexpected argument '{}'", extra));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns the completion candidates for a command line
+    ///
+    /// `line` is the command line up to the cursor, including the command name.
+    fn complete(&self, line: &str) -> Vec<String> {
+        let mut words: Vec<&str> = line.split_whitespace().skip(1).collect();
+        let lead = if line.ends_with(char::is_whitespace) || words.is_empty() {
+            ""
+        } else {
+            words.pop().unwrap_or_default()
+        };
+
+        let (node, _, rest) = self.walk(&words);
+        let mut candidates = Vec::new();
+        if let Some(name) = lead.strip_prefix("--") {
+            if let Some((option, value)) = name.split_once('=') {
+                if let Some((_, Some(complete))) =
+                    node.options.iter().find(|(name, _)| name == option)
+                {
+                    candidates.extend(
+                        complete(value)
+                            .into_iter()
+                            .filter(|c| c.starts_with(value))
+                            .map(|c| format!("--{}={}", option, c)),
+                    );
+                }
+                return candidates;
+            }
+            candidates.extend(node.flags.iter().map(|flag| format!("--{}", flag)));
+            candidates.extend(
+                node.options
+                    .iter()
+                    .map(|(option, _)| format!("--{}=", option)),
+            );
+        } else {
+            let position = rest.iter().filter(|arg| !arg.starts_with("--")).count();
+            if position == 0 {
+                candidates.extend(node.subcommands.iter().map(|sub| sub.name.clone()));
+            }
+            let arg = node
+                .args
+                .get(position)
+                .or_else(|| node.args.last().filter(|arg| arg.kind == ArgKind::Variadic));
+            if let Some(complete) = arg.and_then(|arg| arg.complete.as_ref()) {
+                candidates.extend(complete(lead));
+            }
+        }
+
+        candidates.retain(|c| c.starts_with(lead));
+        candidates
+    }
+}
+
+/// Registers a user command that dispatches to subcommands
+///
+/// The router itself is the root [`Subcommand`] so it can also have arguments and a handler that
+/// is called when no subcommand is given.
+pub struct CommandRouter {
+    name: String,
+    root: Subcommand,
+}
+
+impl CommandRouter {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        let name = name.into();
+        Self {
+            root: Subcommand::new(name.clone()),
+            name,
+        }
+    }
+
+    /// Registers the command, `nargs` and `complete` of the provided options are overwritten
+    ///
+    /// Registering a command with the same name again replaces the previous router.
+    pub fn register(self, opts: &mut CreateUserCommandOpts<'_>) -> Result<(), NvError> {
+        let name = self.name.clone();
+
+        let complete_name = name.clone();
+        opts.nargs(UserCommandNarg::ZeroOrMore).complete(
+            move |args: UserCommandCompleteArgs<'_>| {
+                let router = router(&complete_name);
+                let line = args.cmd.to_str_lossy();
+                let end = usize::try_from(args.cursor_pos)
+                    .unwrap_or(usize::MAX)
+                    .min(line.len());
+                let line = line.get(..end).unwrap_or(&line);
+                let candidates = router.root.complete(line);
+                Ok::<_, Infallible>(Array::from(KVec::from_iter(
+                    candidates
+                        .iter()
+                        .map(|c| Object::String(OwnedThinString::from(c.as_str()))),
+                )))
+            },
+        );
+        let dispatch_name = name.clone();
+        create_user_command(
+            OwnedThinString::from(name.as_str()),
+            UserCommand::callback(move |args: UserCommandArgs<'_>| {
+                router(&dispatch_name).dispatch(&args);
+                Ok::<_, Infallible>(())
+            }),
+            opts,
+        )?;
+
+        ROUTERS.with_borrow_mut(|routers| {
+            let router = Rc::new(self);
+            match routers.iter_mut().find(|r| r.name == name) {
+                Some(old) => *old = router,
+                None => routers.push(router),
+            }
+        });
+        Ok(())
+    }
+
+    fn dispatch(&self, args: &UserCommandArgs<'_>) {
+        let fargs: Vec<_> = args.fargs.iter().map(|arg| arg.to_str_lossy()).collect();
+        let fargs: Vec<&str> = fargs.iter().map(|arg| arg.as_ref()).collect();
+        let (node, path, rest) = self.root.walk(&fargs);
+
+        let result = match (&node.handler, rest.first()) {
+            (None, Some(first)) if !node.subcommands.is_empty() => Err(CommandError::Usage(
+                format!("unknown subcommand '{}'", first),
+            )),
+            (None, _) => Err(CommandError::Usage(String::from("missing subcommand"))),
+            (Some(handler), _) => match node.parse(rest) {
+                Ok(mut parsed) => {
+                    parsed.bang = args.bang;
+                    parsed.line1 = args.line1;
+                    parsed.line2 = args.line2;
+                    parsed.range = args.range;
+                    parsed.count = args.count;
+                    handler(&parsed)
+                }
+                Err(msg) => Err(CommandError::Usage(msg)),
+            },
+        };
+
+        let msg = match result {
+            Ok(()) => return,
+            Err(CommandError::Usage(msg)) => {
+                format!("{}: {}\n{}", self.name, msg, node.usage(&self.name, &path))
+            }
+            Err(CommandError::Failed(err)) => format!("{}: {}", self.name, err),
+        };
+        let _ = echo(
+            &Echo::message(msg.as_str()),
+            true,
+            EchoOpts::default().err(true),
+        );
+    }
+}
+
+impl std::ops::Deref for CommandRouter {
+    type Target = Subcommand;
+    fn deref(&self) -> &Self::Target {
+        &self.root
+    }
+}
+
+impl std::ops::DerefMut for CommandRouter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.root
+    }
+}
+
+thread_local! {
+    /// Registered routers, the command callbacks only capture the name so that handlers do not
+    /// have to be `Send` or `Sync`
+    static ROUTERS: RefCell<Vec<Rc<CommandRouter>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn router(name: &str) -> Rc<CommandRouter> {
+    ROUTERS.with_borrow(|routers| {
+        routers
+            .iter()
+            .find(|router| router.name == name)
+            .expect("commands are only called after their router was registered")
+            .clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandRouter, Subcommand};
+    #[cfg(all(not(miri), feature = "testing"))]
+    use crate::{
+        self as nvimium, nvim_funcs::vimscript::command,
+        nvim_types::opts::create_user_command::CreateUserCommandOpts, plugin::command::ROUTERS,
+    };
+
+    fn router() -> CommandRouter {
+        let mut add = Subcommand::new("add");
+        add.arg("name")
+            .optional_arg("count")
+            .flag("force")
+            .option_with_completion("level", |_| vec![String::from("high"), String::from("low")])
+            .complete_arg("name", |_| {
+                vec![String::from("alpha"), String::from("beta")]
+            })
+            .handler(|_| Ok(()));
+        let mut files = Subcommand::new("files");
+        files.variadic_arg("paths").handler(|_| Ok(()));
+        let mut remote = Subcommand::new("remote");
+        remote.subcommand(files);
+
+        let mut router = CommandRouter::new("Test");
+        router.subcommand(add).subcommand(remote);
+        router
+    }
+
+    #[test]
+    fn parse() {
+        let router = router();
+        let (node, path, rest) = router.walk(&["add", "alpha", "--force", "2", "--level=low"]);
+        assert_eq!(path, ["add"]);
+        let args = node.parse(rest).unwrap();
+        assert_eq!(args.get::<String>("name").unwrap(), "alpha");
+        assert_eq!(args.get_opt::<u32>("count").unwrap(), Some(2));
+        assert!(args.flag("force"));
+        assert_eq!(
+            args.option::<String>("level").unwrap().as_deref(),
+            Some("low")
+        );
+
+        let (node, _, rest) = router.walk(&["add", "alpha", "many"]);
+        let args = node.parse(rest).unwrap();
+        assert_eq!(
+            args.get_opt::<u32>("count").unwrap_err().to_string(),
+            "count: expected integer between 0 and 4294967295, got \"many\""
+        );
+
+        let (node, _, rest) = router.walk(&["add"]);
+        assert_eq!(node.parse(rest).unwrap_err(), "missing argument <name>");
+        let (node, _, rest) = router.walk(&["add", "a", "1", "2"]);
+        assert_eq!(node.parse(rest).unwrap_err(), "unexpected argument '2'");
+        let (node, _, rest) = router.walk(&["add", "a", "--nope"]);
+        assert_eq!(node.parse(rest).unwrap_err(), "unknown flag '--nope'");
+
+        let (node, path, rest) = router.walk(&["remote", "files", "a", "b", "--", "--c"]);
+        assert_eq!(path, ["remote", "files"]);
+        let args = node.parse(rest).unwrap();
+        assert_eq!(args.get_many::<String>("paths").unwrap(), ["a", "b", "--c"]);
+    }
+
+    #[test]
+    fn complete() {
+        let router = router();
+        assert_eq!(router.complete("Test "), ["add", "remote"]);
+        assert_eq!(router.complete("Test a"), ["add"]);
+        assert_eq!(router.complete("Test remote "), ["files"]);
+        assert_eq!(router.complete("Test add "), ["alpha", "beta"]);
+        assert_eq!(router.complete("Test add b"), ["beta"]);
+        assert!(router.complete("Test add alpha ").is_empty());
+        assert_eq!(router.complete("Test add --"), ["--force", "--level="]);
+        assert_eq!(router.complete("Test add --level=h"), ["--level=high"]);
+    }
+
+    #[test]
+    fn usage() {
+        let router = router();
+        let (node, path, _) = router.walk(&["add"]);
+        assert_eq!(
+            node.usage("Test", &path),
+            "Usage: :Test add <name> [count] [--force] [--level=<value>]"
+        );
+        assert_eq!(router.usage("Test", &[]), "Usage: :Test {add|remote}");
+    }
+
+    #[cfg(all(not(miri), feature = "testing"))]
+    #[nvim_test::nvim_test]
+    fn register_replaces() {
+        use std::{cell::Cell, rc::Rc};
+
+        let called = Rc::new(Cell::new(0));
+        for value in [1, 2] {
+            let called = called.clone();
+            let mut run = Subcommand::new("run");
+            run.handler(move |_| {
+                called.set(value);
+                Ok(())
+            });
+            let mut router = CommandRouter::new("RouterTest");
+            router.subcommand(run);
+            router
+                .register(&mut CreateUserCommandOpts::default())
+                .unwrap();
+        }
+        command(c"RouterTest run").unwrap();
+        assert_eq!(called.get(), 2);
+        assert_eq!(ROUTERS.with_borrow(Vec::len), 1);
+
+        // user commands must start with an uppercase letter
+        let router = CommandRouter::new("routerTest");
+        assert!(
+            router
+                .register(&mut CreateUserCommandOpts::default())
+                .is_err()
+        );
+        assert_eq!(ROUTERS.with_borrow(Vec::len), 1);
+    }
+}
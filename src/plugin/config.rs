@@ -0,0 +1,572 @@
+//! Decoding of plugin configuration passed to a `setup` function
+//!
+//! A configuration is a Rust type that implements [`FromConfig`]. Tables are read with
+//! [`ConfigTable`] which provides defaults for missing keys, and errors name the full key path and
+//! the expected type the same way `vim.validate` does.
+//!
+//! - Missing keys and `nil` are treated the same, so a missing nested table is read as an empty
+//!   table and all of its keys use their defaults.
+//! - Lists are read into a [`Vec`], and tables with string keys into a [`HashMap`].
+//! - Enums declared with [`nv_str_enum`](crate::nv_str_enum) are read from their string value.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{
+//!     nv_str_enum,
+//!     nvim_types::{Object, lua::Function},
+//!     plugin::config::{ConfigError, ConfigValue, FromConfig, decode},
+//! };
+//!
+//! nv_str_enum!(
+//!     #[derive(Clone, Copy, Debug)]
+//!     pub enum Position {
+//!         Left = "left",
+//!         Right = "right",
+//!     }
+//! );
+//!
+//! struct Window {
+//!     width: u32,
+//!     position: Position,
+//! }
+//!
+//! struct Config {
+//!     window: Window,
+//!     ignore: Vec<String>,
+//! }
+//!
+//! impl FromConfig for Window {
+//!     fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+//!         let t = value.table()?;
+//!         let width = t.get_or("width", 40)?;
+//!         t.ensure("width", width > 0, "number greater than 0")?;
+//!         Ok(Self { width, position: t.get_or("position", Position::Left)? })
+//!     }
+//! }
+//!
+//! impl FromConfig for Config {
+//!     fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+//!         let t = value.table()?;
+//!         Ok(Self { window: t.get("window")?, ignore: t.get_or_default("ignore")? })
+//!     }
+//! }
+//!
+//! // require("my_plugin").setup({ window = { position = "right" } })
+//! let setup = Function::wrap(|opts: Object| {
+//!     let config: Config = decode(&opts)?;
+//!     // start the plugin with the config
+//!     Ok::<_, ConfigError>(())
+//! });
+//! ```
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Write},
+    hash::Hash,
+};
+
+use crate::nvim_types::{
+    Array, Dict, Float, KVec, Object, OwnedThinString, ThinString, lua::Function,
+};
+
+/// Decodes the value passed to a `setup` function
+///
+/// `nil`, such as when `setup()` is called without arguments, is read as an empty table.
+pub fn decode<T: FromConfig>(obj: &Object) -> Result<T, ConfigError> {
+    T::from_config(ConfigValue {
+        obj,
+        path: &KeyPath::Root,
+    })
+}
+
+/// A type that can be read from a configuration value
+pub trait FromConfig: Sized {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError>;
+}
+
+/// The path to a value starting from the table passed to `setup`
+#[derive(Clone, Copy, Debug)]
+enum KeyPath<'a> {
+    Root,
+    Key(&'a KeyPath<'a>, &'a str),
+    /// The index as seen from Lua, starting from 1
+    Index(&'a KeyPath<'a>, usize),
+}
+
+impl Display for KeyPath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Root => Ok(()),
+            Self::Key(KeyPath::Root, key) => write!(f, "{}", key),
+            Self::Key(parent, key) => write!(f, "{}.{}", parent, key),
+            Self::Index(parent, i) => write!(f, "{}[{}]", parent, i),
+        }
+    }
+}
+
+/// The Lua type name of the value as returned by `type()`
+fn type_name(obj: &Object) -> &'static str {
+    match obj {
+        Object::Null => "nil",
+        Object::Bool(_) => "boolean",
+        Object::Integer(_) | Object::Float(_) => "number",
+        Object::Buffer(_) | Object::Window(_) | Object::TabPage(_) => "number",
+        Object::String(_) => "string",
+        Object::Array(_) | Object::Dict(_) => "table",
+        Object::LuaRef(_) => "function",
+    }
+}
+
+/// Describes the value itself for scalar values and its type for the rest
+fn describe(obj: &Object) -> String {
+    match obj {
+        Object::Bool(b) => b.to_string(),
+        Object::Integer(i) => i.to_string(),
+        Object::Float(f) => f.to_string(),
+        Object::String(s) => s.as_thinstr().to_str_lossy().into_owned(),
+        _ => type_name(obj).to_string(),
+    }
+}
+
+/// An error returned when a configuration value has an unexpected type or fails validation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    path: String,
+    expected: String,
+    got: String,
+}
+
+impl ConfigError {
+    /// The path to the value, such as `window.border` or `ignore[2]`
+    ///
+    /// Empty if the error is about the table passed to `setup` itself.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    pub fn got(&self) -> &str {
+        &self.got
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "expected {}, got {}", self.expected, self.got)
+    }
+}
+
+impl Error for ConfigError {}
+
+/// A configuration value along with its path
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigValue<'a> {
+    obj: &'a Object,
+    path: &'a KeyPath<'a>,
+}
+
+impl<'a> ConfigValue<'a> {
+    pub fn object(&self) -> &'a Object {
+        self.obj
+    }
+
+    /// Returns true if the value is `nil` or the key is missing
+    pub fn is_nil(&self) -> bool {
+        matches!(self.obj, Object::Null)
+    }
+
+    /// Creates an error for this value with the expected type or condition
+    pub fn error<E: Display>(&self, expected: E) -> ConfigError {
+        self.error_with(expected, type_name(self.obj))
+    }
+
+    fn error_with<E: Display, G: Display>(&self, expected: E, got: G) -> ConfigError {
+        ConfigError {
+            path: self.path.to_string(),
+            expected: expected.to_string(),
+            got: got.to_string(),
+        }
+    }
+
+    /// Reads the value as a table with string keys, `nil` is read as an empty table
+    pub fn table(&self) -> Result<ConfigTable<'a>, ConfigError> {
+        const EMPTY: &Dict = &Dict(KVec::new());
+        let dict = match self.obj {
+            Object::Null => EMPTY,
+            Object::Dict(d) => d,
+            Object::Array(a) if a.is_empty() => EMPTY,
+            Object::Array(_) => return Err(self.error_with("table", "list")),
+            _ => return Err(self.error("table")),
+        };
+
+        Ok(ConfigTable {
+            dict,
+            path: self.path,
+        })
+    }
+}
+
+/// A table read from the configuration
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigTable<'a> {
+    dict: &'a Dict,
+    path: &'a KeyPath<'a>,
+}
+
+impl<'a> ConfigTable<'a> {
+    fn read<T: FromConfig>(&self, key: &str, obj: &Object) -> Result<T, ConfigError> {
+        T::from_config(ConfigValue {
+            obj,
+            path: &KeyPath::Key(self.path, key),
+        })
+    }
+
+    fn value(&self, key: &str) -> &'a Object {
+        const NIL: &Object = &Object::Null;
+        self.dict.get(key).unwrap_or(NIL)
+    }
+
+    /// Reads a required key, a missing key is passed to `T` as `nil`
+    pub fn get<T: FromConfig>(&self, key: &str) -> Result<T, ConfigError> {
+        self.read(key, self.value(key))
+    }
+
+    /// Reads a key or returns the default if it is missing or `nil`
+    pub fn get_or<T: FromConfig>(&self, key: &str, default: T) -> Result<T, ConfigError> {
+        self.get_or_else(key, || default)
+    }
+
+    /// Reads a key or returns [`Default::default`] if it is missing or `nil`
+    pub fn get_or_default<T: FromConfig + Default>(&self, key: &str) -> Result<T, ConfigError> {
+        self.get_or_else(key, T::default)
+    }
+
+    /// Reads a key or calls `default` if it is missing or `nil`
+    pub fn get_or_else<T: FromConfig, F: FnOnce() -> T>(
+        &self,
+        key: &str,
+        default: F,
+    ) -> Result<T, ConfigError> {
+        match self.value(key) {
+            Object::Null => Ok(default()),
+            obj => self.read(key, obj),
+        }
+    }
+
+    /// Returns an error for the key if `ok` is false
+    ///
+    /// Used to validate a value after it was read, the error shows the value that was passed.
+    pub fn ensure<E: Display>(&self, key: &str, ok: bool, expected: E) -> Result<(), ConfigError> {
+        if ok {
+            return Ok(());
+        }
+        let value = ConfigValue {
+            obj: self.value(key),
+            path: &KeyPath::Key(self.path, key),
+        };
+        Err(value.error_with(expected, describe(value.obj)))
+    }
+
+    /// Iterates over the keys and values of the table
+    pub fn iter(&self) -> impl Iterator<Item = (&'a OwnedThinString, &'a Object)> {
+        self.dict.0.iter().map(|kv| (&kv.key, &kv.object))
+    }
+}
+
+impl FromConfig for Object {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        Ok(value.obj.clone())
+    }
+}
+
+impl FromConfig for bool {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        match value.obj {
+            Object::Bool(b) => Ok(*b),
+            _ => Err(value.error("boolean")),
+        }
+    }
+}
+
+macro_rules! int_from_config {
+    ($($int:ty),*) => {
+        $(
+            impl FromConfig for $int {
+                fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+                    let Object::Integer(i) = value.obj else {
+                        return Err(value.error("integer"));
+                    };
+                    <$int>::try_from(*i).map_err(|_| {
+                        value.error_with(
+                            format_args!("integer between {} and {}", <$int>::MIN, <$int>::MAX),
+                            i,
+                        )
+                    })
+                }
+            }
+        )*
+    };
+}
+
+int_from_config!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromConfig for Float {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        match value.obj {
+            Object::Float(f) => Ok(*f),
+            Object::Integer(i) => Ok(*i as Float),
+            _ => Err(value.error("number")),
+        }
+    }
+}
+
+impl FromConfig for f32 {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        Float::from_config(value).map(|f| f as f32)
+    }
+}
+
+impl FromConfig for OwnedThinString {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        match value.obj {
+            Object::String(s) => Ok(s.clone()),
+            _ => Err(value.error("string")),
+        }
+    }
+}
+
+impl FromConfig for String {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        let Object::String(s) = value.obj else {
+            return Err(value.error("string"));
+        };
+        s.as_thinstr()
+            .to_str()
+            .map(str::to_owned)
+            .map_err(|_| value.error_with("UTF-8 string", "string"))
+    }
+}
+
+impl FromConfig for Function {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        match value.obj {
+            Object::LuaRef(lref) => Ok(Function::from_luaref(lref.clone())),
+            _ => Err(value.error("function")),
+        }
+    }
+}
+
+/// Reads an enum declared with [`nv_str_enum`](crate::nv_str_enum)
+#[doc(hidden)]
+pub fn from_enum_str<T>(
+    value: ConfigValue<'_>,
+    variants: &[ThinString<'static>],
+    from_str: fn(ThinString<'_>) -> Option<T>,
+) -> Result<T, ConfigError> {
+    let expected = || {
+        let mut expected = String::from("one of ");
+        for (i, variant) in variants.iter().enumerate() {
+            if i != 0 {
+                expected.push_str(", ");
+            }
+            write!(expected, "{:?}", variant.to_str_lossy()).unwrap();
+        }
+        expected
+    };
+    match value.obj {
+        Object::String(s) => from_str(s.as_thinstr())
+            .ok_or_else(|| value.error_with(expected(), describe(value.obj))),
+        _ => Err(value.error(expected())),
+    }
+}
+
+/// `nil` is read as [`None`]
+impl<T: FromConfig> FromConfig for Option<T> {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        if value.is_nil() {
+            return Ok(None);
+        }
+        T::from_config(value).map(Some)
+    }
+}
+
+/// Reads a list, an empty table is read as an empty list
+impl<T: FromConfig> FromConfig for Vec<T> {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        const EMPTY: &Array = &Array(KVec::new());
+        let arr = match value.obj {
+            Object::Array(a) => a,
+            Object::Dict(d) if d.0.is_empty() => EMPTY,
+            Object::Dict(_) => return Err(value.error_with("list", "table")),
+            _ => return Err(value.error("list")),
+        };
+
+        arr.iter()
+            .enumerate()
+            .map(|(i, obj)| {
+                T::from_config(ConfigValue {
+                    obj,
+                    path: &KeyPath::Index(value.path, i + 1),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reads a table with string keys
+impl<K: From<String> + Eq + Hash, T: FromConfig> FromConfig for HashMap<K, T> {
+    fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+        let table = value.table()?;
+        table
+            .iter()
+            .map(|(key, obj)| {
+                let key = key.as_thinstr().to_str_lossy().into_owned();
+                let val = table.read(&key, obj)?;
+                Ok((K::from(key), val))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ConfigError, ConfigValue, FromConfig, decode};
+    use crate::{
+        nv_str_enum,
+        nvim_types::{Array, Dict, Object, OwnedThinString},
+    };
+
+    nv_str_enum!(
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        enum Position {
+            Left = "left",
+            Right = "right",
+        }
+    );
+
+    #[derive(Debug, PartialEq)]
+    struct Window {
+        width: u32,
+        position: Position,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        window: Window,
+        ignore: Vec<String>,
+        highlights: HashMap<String, i64>,
+        title: Option<String>,
+    }
+
+    impl FromConfig for Window {
+        fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+            let t = value.table()?;
+            let width = t.get_or("width", 40)?;
+            t.ensure("width", width > 0, "number greater than 0")?;
+            Ok(Self {
+                width,
+                position: t.get_or("position", Position::Left)?,
+            })
+        }
+    }
+
+    impl FromConfig for Config {
+        fn from_config(value: ConfigValue<'_>) -> Result<Self, ConfigError> {
+            let t = value.table()?;
+            Ok(Self {
+                window: t.get("window")?,
+                ignore: t.get_or_default("ignore")?,
+                highlights: t.get_or_default("highlights")?,
+                title: t.get("title")?,
+            })
+        }
+    }
+
+    fn string(s: &str) -> Object {
+        Object::String(OwnedThinString::from(s))
+    }
+
+    #[test]
+    fn defaults() {
+        let config: Config = decode(&Object::Null).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                window: Window {
+                    width: 40,
+                    position: Position::Left
+                },
+                ignore: vec![],
+                highlights: HashMap::new(),
+                title: None,
+            }
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let obj = Object::Dict(Dict::from_iter([
+            (
+                "window",
+                Object::Dict(Dict::from_iter([("position", string("right"))])),
+            ),
+            (
+                "ignore",
+                Object::Array(Array::from([string("a"), string("b")].as_slice())),
+            ),
+            (
+                "highlights",
+                Object::Dict(Dict::from_iter([("Normal", Object::Integer(1))])),
+            ),
+            ("title", string("title")),
+        ]));
+        let config: Config = decode(&obj).unwrap();
+        assert_eq!(config.window.position, Position::Right);
+        assert_eq!(config.ignore, ["a", "b"]);
+        assert_eq!(config.highlights.get("Normal"), Some(&1));
+        assert_eq!(config.title.as_deref(), Some("title"));
+    }
+
+    #[test]
+    fn errors() {
+        let err = decode::<Config>(&string("a")).unwrap_err();
+        assert_eq!(err.to_string(), "expected table, got string");
+
+        let obj = Object::Dict(Dict::from_iter([(
+            "window",
+            Object::Dict(Dict::from_iter([("position", string("top"))])),
+        )]));
+        let err = decode::<Config>(&obj).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "window.position: expected one of \"left\", \"right\", got top"
+        );
+
+        let obj = Object::Dict(Dict::from_iter([(
+            "window",
+            Object::Dict(Dict::from_iter([("width", Object::Integer(0))])),
+        )]));
+        let err = decode::<Config>(&obj).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "window.width: expected number greater than 0, got 0"
+        );
+
+        let obj = Object::Dict(Dict::from_iter([(
+            "ignore",
+            Object::Array(Array::from([string("a"), Object::Bool(true)].as_slice())),
+        )]));
+        let err = decode::<Config>(&obj).unwrap_err();
+        assert_eq!(err.path(), "ignore[2]");
+        assert_eq!(err.to_string(), "ignore[2]: expected string, got boolean");
+    }
+}
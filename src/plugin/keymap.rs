@@ -0,0 +1,363 @@
+//! Keymaps with Rust callbacks that can be removed as a group
+//!
+//! A [`Keymap`] describes the right hand side of a mapping along with its options. It is set with
+//! a [`KeymapRegistry`] which remembers every mapping it defined so that a plugin can remove all
+//! of them at once, for example when it is disabled.
+//!
+//! Callback mappings in normal mode can opt into `.` repeat with [`Keymap::repeatable`]. The
+//! mapping then sets `operatorfunc` and runs `g@l` so that the callback is called again when the
+//! mapping is repeated.
+//!
+//! # Example
+//! ```no_run
+//! use std::convert::Infallible;
+//!
+//! use nvimium::{
+//!     nvim_funcs::vimscript::command,
+//!     nvim_types::{Error, func_types::keymap_mode::KeyMapMode},
+//!     plugin::keymap::{Keymap, KeymapRegistry},
+//! };
+//!
+//! fn setup() -> Result<KeymapRegistry, Error> {
+//!     let mut registry = KeymapRegistry::new();
+//!
+//!     let mut next = Keymap::callback(|| command(c"normal! j"));
+//!     next.desc("Go to the next line").repeatable(true);
+//!     registry.set(&[KeyMapMode::NORMAL], Keymap::plug("my-plugin-next"), &next)?;
+//!     registry.set(&[KeyMapMode::NORMAL], "<leader>j", &Keymap::keys("<Plug>(my-plugin-next)"))?;
+//!
+//!     // expr mappings return the keys to run
+//!     let expr = Keymap::expr(|| Ok::<_, Infallible>(c"<C-a>"));
+//!     registry.set(&[KeyMapMode::NORMAL, KeyMapMode::VISUAL], "<leader>a", &expr)?;
+//!
+//!     Ok(registry)
+//! }
+//! ```
+
+use std::{error::Error, rc::Rc};
+
+use crate::{
+    nvim_funcs::{
+        buffer::{buf_del_keymap, buf_set_keymap},
+        global::{del_keymap, set_keymap},
+    },
+    nvim_types::{
+        Buffer, Error as NvError, LuaRef, OwnedThinString,
+        func_types::keymap_mode::KeyMapMode,
+        lua::{Function, IntoLua},
+        opts::set_keymap::SetKeymapOpts,
+    },
+};
+
+use super::operator::{CallbackError, set_operatorfunc};
+
+/// Returns the left hand side of a `<Plug>` mapping, `<Plug>(name)`
+///
+/// `<Plug>` mappings cannot be typed and are meant to be mapped to by users.
+pub fn plug(name: &str) -> String {
+    format!("<Plug>({})", name)
+}
+
+type Callback = Rc<dyn Fn() -> Result<(), CallbackError>>;
+
+enum Rhs {
+    Keys(OwnedThinString),
+    Callback(Callback),
+    /// A Lua function used as the callback of an `expr` mapping
+    Expr(LuaRef),
+}
+
+/// The right hand side of a mapping and its options
+///
+/// Mappings are non-recursive by default.
+pub struct Keymap {
+    rhs: Rhs,
+    desc: Option<OwnedThinString>,
+    noremap: bool,
+    silent: bool,
+    nowait: bool,
+    unique: bool,
+    replace_keycodes: bool,
+    repeatable: bool,
+}
+
+impl Keymap {
+    fn new(rhs: Rhs) -> Self {
+        Self {
+            rhs,
+            desc: None,
+            noremap: true,
+            silent: false,
+            nowait: false,
+            unique: false,
+            replace_keycodes: true,
+            repeatable: false,
+        }
+    }
+
+    /// Returns the left hand side of a `<Plug>` mapping, see [`plug`]
+    pub fn plug(name: &str) -> String {
+        plug(name)
+    }
+
+    /// A mapping that runs keys
+    pub fn keys<S: AsRef<str>>(keys: S) -> Self {
+        Self::new(Rhs::Keys(OwnedThinString::from(keys.as_ref())))
+    }
+
+    /// A mapping that calls a function
+    ///
+    /// An error returned from the function is reported as an error message.
+    pub fn callback<E: 'static + Error, F: 'static + Fn() -> Result<(), E>>(f: F) -> Self {
+        Self::new(Rhs::Callback(Rc::new(move || {
+            f().map_err(|err| CallbackError(Box::new(err)))
+        })))
+    }
+
+    /// An `expr` mapping that runs the keys returned by the function
+    ///
+    /// Keycodes such as `<CR>` in the returned value are replaced unless disabled with
+    /// [`Keymap::replace_keycodes`]. As with any `expr` mapping, the function cannot change the
+    /// text or move to another window.
+    ///
+    /// # Panics
+    ///
+    /// If called from a thread that does not have access to Neovim.
+    pub fn expr<
+        R: 'static + IntoLua,
+        E: 'static + Error,
+        F: 'static + Fn() -> Result<R, E> + Unpin,
+    >(
+        f: F,
+    ) -> Self {
+        let f = Function::wrap(move |()| f());
+        Self::new(Rhs::Expr(f.into_luaref()))
+    }
+
+    /// The description shown when listing mappings
+    pub fn desc<S: AsRef<str>>(&mut self, desc: S) -> &mut Self {
+        self.desc = Some(OwnedThinString::from(desc.as_ref()));
+        self
+    }
+
+    /// Whether the keys of the right hand side can trigger other mappings, false by default
+    pub fn remap(&mut self, remap: bool) -> &mut Self {
+        self.noremap = !remap;
+        self
+    }
+
+    pub fn silent(&mut self, silent: bool) -> &mut Self {
+        self.silent = silent;
+        self
+    }
+
+    pub fn nowait(&mut self, nowait: bool) -> &mut Self {
+        self.nowait = nowait;
+        self
+    }
+
+    /// Fail instead of replacing an existing mapping
+    pub fn unique(&mut self, unique: bool) -> &mut Self {
+        self.unique = unique;
+        self
+    }
+
+    /// Whether keycodes returned by an `expr` mapping are replaced, true by default
+    pub fn replace_keycodes(&mut self, replace: bool) -> &mut Self {
+        self.replace_keycodes = replace;
+        self
+    }
+
+    /// Whether the mapping can be repeated with `.`, false by default
+    ///
+    /// Only applies to mappings created with [`Keymap::callback`] in [`KeyMapMode::NORMAL`].
+    pub fn repeatable(&mut self, repeatable: bool) -> &mut Self {
+        self.repeatable = repeatable;
+        self
+    }
+
+    /// Sets the mapping with [`set_keymap`] or [`buf_set_keymap`]
+    fn set(&self, buf: Option<Buffer>, mode: &KeyMapMode, lhs: &str) -> Result<(), NvError> {
+        let mut opts = SetKeymapOpts::default();
+        opts.noremap(self.noremap)
+            .silent(self.silent)
+            .noawait(self.nowait)
+            .unique(self.unique);
+        if let Some(desc) = &self.desc {
+            opts.desc(desc);
+        }
+
+        let rhs = match &self.rhs {
+            Rhs::Keys(keys) => keys.clone(),
+            Rhs::Callback(f) if self.repeatable && *mode == KeyMapMode::NORMAL => {
+                let f = f.clone();
+                let f = Function::wrap(move |()| {
+                    let f = f.clone();
+                    set_operatorfunc(Rc::new(move |_| f()))?;
+                    Ok::<_, NvError>(c"g@l")
+                });
+                opts.expr(true)
+                    .replace_keycodes(false)
+                    .set_callback(f.into_luaref());
+                OwnedThinString::from("")
+            }
+            Rhs::Callback(f) => {
+                let f = f.clone();
+                opts.set_callback(Function::wrap(move |()| f()).into_luaref());
+                OwnedThinString::from("")
+            }
+            Rhs::Expr(f) => {
+                opts.expr(true)
+                    .replace_keycodes(self.replace_keycodes)
+                    .set_callback(f.clone());
+                OwnedThinString::from("")
+            }
+        };
+
+        let lhs = OwnedThinString::from(lhs);
+        let (lhs, rhs) = (lhs.as_thinstr(), rhs.as_thinstr());
+        match buf {
+            Some(buf) => buf_set_keymap(buf, mode.clone(), lhs, rhs, &mut opts),
+            None => set_keymap(mode.clone(), lhs, rhs, &mut opts),
+        }
+    }
+}
+
+/// Sets mappings and remembers them so that they can be removed together
+///
+/// The mappings are not removed when the registry is dropped, use [`KeymapRegistry::clear`].
+#[derive(Debug, Default)]
+pub struct KeymapRegistry {
+    maps: Vec<(Option<Buffer>, KeyMapMode, String)>,
+}
+
+impl KeymapRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a global mapping in each mode
+    ///
+    /// Stops at the first mode that fails, mappings set before the error are still registered.
+    pub fn set<S: AsRef<str>>(
+        &mut self,
+        modes: &[KeyMapMode],
+        lhs: S,
+        map: &Keymap,
+    ) -> Result<(), NvError> {
+        self.set_inner(None, modes, lhs.as_ref(), map)
+    }
+
+    /// Sets a buffer local mapping in each mode
+    ///
+    /// Stops at the first mode that fails, mappings set before the error are still registered.
+    pub fn buf_set<S: AsRef<str>>(
+        &mut self,
+        buf: Buffer,
+        modes: &[KeyMapMode],
+        lhs: S,
+        map: &Keymap,
+    ) -> Result<(), NvError> {
+        self.set_inner(Some(buf), modes, lhs.as_ref(), map)
+    }
+
+    fn set_inner(
+        &mut self,
+        buf: Option<Buffer>,
+        modes: &[KeyMapMode],
+        lhs: &str,
+        map: &Keymap,
+    ) -> Result<(), NvError> {
+        for mode in modes {
+            map.set(buf, mode, lhs)?;
+            let entry = (buf, mode.clone(), lhs.to_string());
+            if !self.maps.contains(&entry) {
+                self.maps.push(entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a registered mapping
+    ///
+    /// Returns an error if the mapping no longer exists. The mapping is unregistered either way.
+    pub fn del<S: AsRef<str>>(
+        &mut self,
+        buf: Option<Buffer>,
+        mode: &KeyMapMode,
+        lhs: S,
+    ) -> Result<(), NvError> {
+        let lhs = lhs.as_ref();
+        self.maps
+            .retain(|(b, m, l)| !(*b == buf && m == mode && l == lhs));
+        let lhs = OwnedThinString::from(lhs);
+        match buf {
+            Some(buf) => buf_del_keymap(buf, mode.clone(), lhs.as_thinstr()),
+            None => del_keymap(mode.clone(), lhs.as_thinstr()),
+        }
+    }
+
+    /// Removes every registered mapping
+    ///
+    /// Mappings that were already removed, or whose buffer was deleted, are skipped.
+    pub fn clear(&mut self) {
+        for (buf, mode, lhs) in self.maps.drain(..) {
+            let lhs = OwnedThinString::from(lhs.as_str());
+            let _ = match buf {
+                Some(buf) => buf_del_keymap(buf, mode, lhs.as_thinstr()),
+                None => del_keymap(mode, lhs.as_thinstr()),
+            };
+        }
+    }
+
+    /// Returns true if no mappings are registered
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use std::convert::Infallible;
+
+    use crate::{
+        self as nvimium,
+        nvim_funcs::{buffer::buf_get_keymap, global::get_keymap},
+        nvim_types::{Buffer, OwnedThinString, func_types::keymap_mode::KeyMapMode},
+    };
+
+    use super::{Keymap, KeymapRegistry};
+
+    #[nvim_test::nvim_test]
+    fn registry_set_clear() {
+        let mut registry = KeymapRegistry::new();
+        let mut map = Keymap::callback(|| Ok::<_, Infallible>(()));
+        map.desc("Registry test").repeatable(true);
+        registry
+            .set(&[KeyMapMode::NORMAL, KeyMapMode::VISUAL], "gzz", &map)
+            .unwrap();
+        let expr = Keymap::expr(|| Ok::<_, Infallible>(c"<Nop>"));
+        registry
+            .buf_set(Buffer::new(0), &[KeyMapMode::INSERT], "zz", &expr)
+            .unwrap();
+
+        let desc = Some(OwnedThinString::from("Registry test"));
+        let normal = get_keymap(KeyMapMode::NORMAL);
+        let found = normal.maps.iter().find(|map| &map.lhs == c"gzz").unwrap();
+        // repeatable mappings are expr mappings that run `g@l`
+        assert!(found.expr);
+        assert_eq!(found.desc, desc);
+        let visual = get_keymap(KeyMapMode::VISUAL);
+        let found = visual.maps.iter().find(|map| &map.lhs == c"gzz").unwrap();
+        assert!(!found.expr);
+        let insert = buf_get_keymap(Buffer::new(0), KeyMapMode::INSERT).unwrap();
+        assert!(insert.maps.iter().any(|map| &map.lhs == c"zz" && map.expr));
+
+        registry.clear();
+        assert!(registry.is_empty());
+        let normal = get_keymap(KeyMapMode::NORMAL);
+        assert!(normal.maps.iter().all(|map| map.desc != desc));
+        let insert = buf_get_keymap(Buffer::new(0), KeyMapMode::INSERT).unwrap();
+        assert!(insert.maps.is_empty());
+    }
+}
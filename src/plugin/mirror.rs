@@ -0,0 +1,496 @@
+//! A copy of a buffer's text that is kept in sync from `on_bytes` events
+//!
+//! [`BufferMirror::attach`] reads the lines of a buffer once and then applies each edit reported
+//! by [`buf_attach`] to its own line store. [`BufferMirror::snapshot`] returns the text along with
+//! the `changedtick` it belongs to. Taking a snapshot is cheap, lines are shared with the mirror
+//! until they are edited.
+//!
+//! The mirror reads the buffer again when it is reloaded, when the attachment is lost, or when the
+//! `changedtick` of the buffer does not match the edits that were applied.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{
+//!     nvim_types::{Buffer, Error},
+//!     plugin::mirror::BufferMirror,
+//! };
+//!
+//! fn count_todos(mirror: &BufferMirror) -> Result<usize, Error> {
+//!     let snapshot = mirror.snapshot()?;
+//!     Ok(snapshot
+//!         .lines()
+//!         .filter(|line| line.windows(4).any(|w| w == b"TODO"))
+//!         .count())
+//! }
+//!
+//! fn setup() -> Result<BufferMirror, Error> {
+//!     // the mirror stops following the buffer once it is dropped
+//!     BufferMirror::attach(Buffer::new(0))
+//! }
+//! ```
+
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+use crate::{
+    nvim_funcs::{
+        buffer::{buf_attach, buf_get_changedtick, buf_get_lines, buf_line_count},
+        global::get_current_buf,
+    },
+    nvim_types::{
+        Buffer, Error, Integer, args::buf_attach_cb::BufOnBytesArgs,
+        opts::buf_attach::BufAttachOpts,
+    },
+    th,
+};
+
+type Lines = Rc<Vec<Rc<[u8]>>>;
+
+/// The text of a buffer at a `changedtick`
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    changedtick: Integer,
+    lines: Lines,
+}
+
+impl Snapshot {
+    /// The `changedtick` of the buffer when it had this text
+    pub fn changedtick(&self) -> Integer {
+        self.changedtick
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns a line by its zero based index
+    pub fn line(&self, idx: usize) -> Option<&[u8]> {
+        self.lines.get(idx).map(|line| &**line)
+    }
+
+    pub fn lines(&self) -> impl ExactSizeIterator<Item = &[u8]> + DoubleEndedIterator {
+        self.lines.iter().map(|line| &**line)
+    }
+
+    /// Returns the lines joined by newlines
+    pub fn text(&self) -> Vec<u8> {
+        self.lines.join(&b'\n')
+    }
+}
+
+struct State {
+    lines: Lines,
+    changedtick: Integer,
+    attached: bool,
+    /// The lines no longer match the buffer and must be read again
+    stale: bool,
+}
+
+/// A change to the lines of a mirror, see [`BufferMirror::listen`]
+#[cfg_attr(not(feature = "tree-sitter"), allow(dead_code))]
+pub(crate) enum MirrorEvent<'a, 'b> {
+    /// An edit was applied to the lines
+    Edit(&'a BufOnBytesArgs<'b>),
+    /// The lines were read from the buffer again and may not match the previous edits
+    Resync,
+}
+
+type Listener = Box<dyn for<'a, 'b> Fn(MirrorEvent<'a, 'b>)>;
+
+struct MirrorInner {
+    buf: Buffer,
+    state: RefCell<State>,
+    listeners: RefCell<Vec<Listener>>,
+}
+
+/// Mirrors the text of a buffer
+///
+/// See the [module documentation](self) for details.
+pub struct BufferMirror {
+    inner: Rc<MirrorInner>,
+}
+
+impl BufferMirror {
+    /// Attaches to a buffer and reads its lines
+    ///
+    /// A buffer of 0 attaches to the current buffer.
+    pub fn attach(buf: Buffer) -> Result<Self, Error> {
+        let buf = match buf.as_int() {
+            0 => get_current_buf(),
+            _ => buf,
+        };
+        let inner = Rc::new(MirrorInner {
+            buf,
+            state: RefCell::new(State {
+                lines: Lines::default(),
+                changedtick: 0,
+                attached: false,
+                stale: true,
+            }),
+            listeners: RefCell::default(),
+        });
+        inner.resync()?;
+
+        Ok(Self { inner })
+    }
+
+    pub fn buf(&self) -> Buffer {
+        self.inner.buf
+    }
+
+    /// Calls `f` after an edit is applied to the lines or the lines are read again
+    #[cfg_attr(not(feature = "tree-sitter"), allow(dead_code))]
+    pub(crate) fn listen<F: 'static + for<'a, 'b> Fn(MirrorEvent<'a, 'b>)>(&self, f: F) {
+        self.inner.listeners.borrow_mut().push(Box::new(f));
+    }
+
+    /// Returns the current text of the buffer
+    ///
+    /// The buffer is read again if the mirror could not follow the edits since the last call.
+    /// An error is returned if the buffer is no longer valid or loaded.
+    pub fn snapshot(&self) -> Result<Snapshot, Error> {
+        let changedtick = buf_get_changedtick(self.inner.buf)?;
+        {
+            let state = self.inner.state.borrow();
+            // a tick that was not seen or a different line count means an edit was missed
+            if state.attached
+                && !state.stale
+                && state.changedtick == changedtick
+                && Ok(state.lines.len()) == usize::try_from(buf_line_count(self.inner.buf)?)
+            {
+                return Ok(Snapshot {
+                    changedtick,
+                    lines: state.lines.clone(),
+                });
+            }
+        }
+
+        self.inner.resync()?;
+        let state = self.inner.state.borrow();
+        Ok(Snapshot {
+            changedtick: state.changedtick,
+            lines: state.lines.clone(),
+        })
+    }
+}
+
+impl MirrorInner {
+    /// Attaches again if needed and reads all lines of the buffer
+    fn resync(self: &Rc<Self>) -> Result<(), Error> {
+        if !self.state.borrow().attached {
+            self.attach()?;
+        }
+
+        let lines = buf_get_lines(
+            |lines| lines.map(|line| Rc::from(line.as_slice())).collect(),
+            self.buf,
+            0,
+            -1,
+            true,
+        )?;
+        let changedtick = buf_get_changedtick(self.buf)?;
+        {
+            let mut state = self.state.borrow_mut();
+            state.lines = Rc::new(lines);
+            state.changedtick = changedtick;
+            state.stale = false;
+        }
+        for listener in self.listeners.borrow().iter() {
+            listener(MirrorEvent::Resync);
+        }
+
+        Ok(())
+    }
+
+    fn attach(self: &Rc<Self>) -> Result<(), Error> {
+        let mut opts = BufAttachOpts::default();
+        let weak = Rc::downgrade(self);
+        opts.local_on_bytes(move |args| {
+            let Some(inner) = weak.upgrade() else {
+                return Ok::<_, Infallible>(true);
+            };
+            inner.on_bytes(&args);
+            Ok(false)
+        });
+        let weak = Rc::downgrade(self);
+        opts.local_on_lines(move |args| {
+            let Some(inner) = weak.upgrade() else {
+                return Ok::<_, Infallible>(true);
+            };
+            // sent once an edit is done with the incremented tick
+            let mut state = inner.state.borrow_mut();
+            match args.changedtick >= state.changedtick {
+                true => state.changedtick = args.changedtick,
+                false => state.stale = true,
+            }
+            Ok(false)
+        });
+        let weak = Rc::downgrade(self);
+        opts.local_on_changedtick(move |args| {
+            let Some(inner) = weak.upgrade() else {
+                return Ok::<_, Infallible>(true);
+            };
+            // the tick was incremented without changing the text
+            let mut state = inner.state.borrow_mut();
+            match args.changedtick > state.changedtick {
+                true => state.changedtick = args.changedtick,
+                false => state.stale = true,
+            }
+            Ok(false)
+        });
+        let weak = Rc::downgrade(self);
+        opts.local_on_reload(move |_| {
+            if let Some(inner) = weak.upgrade() {
+                inner.state.borrow_mut().stale = true;
+            }
+            Ok::<_, Infallible>(())
+        });
+        let weak = Rc::downgrade(self);
+        opts.local_on_detach(move |_| {
+            if let Some(inner) = weak.upgrade() {
+                let mut state = inner.state.borrow_mut();
+                state.attached = false;
+                state.stale = true;
+            }
+            Ok::<_, Infallible>(())
+        });
+
+        if !buf_attach(self.buf, false, &mut opts)? {
+            return Err(Error::validation(th!("failed to attach to the buffer")));
+        }
+        self.state.borrow_mut().attached = true;
+
+        Ok(())
+    }
+
+    fn on_bytes(&self, args: &BufOnBytesArgs) {
+        if self.state.borrow().stale {
+            return;
+        }
+        let replacement = self.replacement(args);
+        let mut state = self.state.borrow_mut();
+        // the tick of an edit is reported before it is incremented, any other tick means events
+        // were missed
+        if args.changedtick != state.changedtick {
+            state.stale = true;
+            return;
+        }
+        match replacement.and_then(|new| apply(&mut state.lines, args, new)) {
+            Some(()) => state.changedtick = args.changedtick,
+            None => {
+                state.stale = true;
+                return;
+            }
+        }
+        drop(state);
+        for listener in self.listeners.borrow().iter() {
+            listener(MirrorEvent::Edit(args));
+        }
+    }
+
+    /// Reads the text that was inserted by an edit, split into lines
+    fn replacement(&self, args: &BufOnBytesArgs) -> Option<Vec<Vec<u8>>> {
+        let start_row = args.start_row;
+        let start_col = usize::try_from(args.start_col).ok()?;
+        let (end_row, end_col) = end_pos(
+            args.start_row,
+            args.start_col,
+            args.new_end_row,
+            args.new_end_col,
+        );
+        let end_col = usize::try_from(end_col).ok()?;
+
+        let mut new: Vec<Vec<u8>> = buf_get_lines(
+            |lines| lines.map(|line| line.as_slice().to_vec()).collect(),
+            self.buf,
+            start_row,
+            end_row + 1,
+            false,
+        )
+        .ok()?;
+        let rows = usize::try_from(args.new_end_row + 1).ok()?;
+        // the edit can end after the newline of the last line
+        if new.len() + 1 == rows {
+            new.push(Vec::new());
+        }
+        if new.len() != rows {
+            return None;
+        }
+
+        let last = new.last_mut()?;
+        if end_col > last.len() {
+            return None;
+        }
+        last.truncate(end_col);
+        let first = new.first_mut()?;
+        if start_col > first.len() {
+            return None;
+        }
+        first.drain(..start_col);
+
+        Some(new)
+    }
+}
+
+/// Returns the absolute end of an edit from its start and the relative end reported by `on_bytes`
+pub(crate) fn end_pos(
+    start_row: Integer,
+    start_col: Integer,
+    row: Integer,
+    col: Integer,
+) -> (Integer, Integer) {
+    match row {
+        0 => (start_row, start_col + col),
+        _ => (start_row + row, col),
+    }
+}
+
+/// Replaces the text between the start and old end of an edit with `new`
+///
+/// Returns [`None`] if the edit does not fit the lines, in which case the lines may have been
+/// partially modified.
+fn apply(lines: &mut Lines, args: &BufOnBytesArgs, mut new: Vec<Vec<u8>>) -> Option<()> {
+    let (old_end_row, old_end_col) = end_pos(
+        args.start_row,
+        args.start_col,
+        args.old_end_row,
+        args.old_end_col,
+    );
+    let start_row = usize::try_from(args.start_row).ok()?;
+    let start_col = usize::try_from(args.start_col).ok()?;
+    let old_end_row = usize::try_from(old_end_row).ok()?;
+    let old_end_col = usize::try_from(old_end_col).ok()?;
+
+    if start_row > old_end_row {
+        return None;
+    }
+
+    let lines = Rc::make_mut(lines);
+    // every line ends with a newline, edits can start or end on the empty line after the last one
+    lines.push(Rc::from([]));
+    let prefix = lines.get(start_row)?.get(..start_col)?;
+    new.first_mut()?.splice(0..0, prefix.iter().copied());
+    let suffix = lines.get(old_end_row)?.get(old_end_col..)?;
+    new.last_mut()?.extend_from_slice(suffix);
+    lines.splice(start_row..=old_end_row, new.into_iter().map(Rc::from));
+
+    match lines.pop() {
+        Some(last) if last.is_empty() => Some(()),
+        _ => None,
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use crate::{
+        self as nvimium, array,
+        nvim_funcs::{
+            buffer::{buf_get_changedtick, buf_get_lines, buf_set_lines, buf_set_text},
+            vimscript::command,
+        },
+        nvim_types::{Array, Buffer},
+    };
+
+    use super::BufferMirror;
+
+    #[nvim_test::nvim_test]
+    fn mirror_edits() {
+        /// Checks that the mirror followed the edits without reading the buffer again
+        fn assert_synced(mirror: &BufferMirror) {
+            let state = mirror.inner.state.borrow();
+            assert!(state.attached && !state.stale);
+            assert_eq!(
+                state.changedtick,
+                buf_get_changedtick(mirror.buf()).unwrap()
+            );
+            let lines = buf_get_lines(
+                |lines| {
+                    lines
+                        .map(|line| String::from_utf8(line.as_slice().to_vec()).unwrap())
+                        .collect::<Vec<_>>()
+                },
+                mirror.buf(),
+                0,
+                -1,
+                true,
+            )
+            .unwrap();
+            let mirrored: Vec<_> = state
+                .lines
+                .iter()
+                .map(|line| String::from_utf8(line.to_vec()).unwrap())
+                .collect();
+            assert_eq!(mirrored, lines);
+        }
+
+        let buf = Buffer::new(0);
+        buf_set_lines(buf, 0, -1, true, &array!["first", "second", "third"]).unwrap();
+        let mirror = BufferMirror::attach(buf).unwrap();
+        let before = mirror.snapshot().unwrap();
+        assert_eq!(before.text(), b"first\nsecond\nthird");
+
+        buf_set_text(buf, 0, 5, 0, 5, &array![" line", "inserted"]).unwrap();
+        assert_synced(&mirror);
+        buf_set_text(buf, 1, 2, 2, 3, &array![""]).unwrap();
+        assert_synced(&mirror);
+        buf_set_lines(buf, -1, -1, true, &array!["appended", "lines"]).unwrap();
+        assert_synced(&mirror);
+        buf_set_lines(buf, -2, -1, true, &Array::default()).unwrap();
+        assert_synced(&mirror);
+        buf_set_lines(buf, 0, 1, true, &Array::default()).unwrap();
+        assert_synced(&mirror);
+        command(c"normal! ggofoo").unwrap();
+        assert_synced(&mirror);
+        command(c"normal! ggJx").unwrap();
+        assert_synced(&mirror);
+        command(c"normal! Gdd").unwrap();
+        assert_synced(&mirror);
+        command(c"%s/o/0/g").unwrap();
+        assert_synced(&mirror);
+        command(c"normal! uu").unwrap();
+        assert_synced(&mirror);
+        buf_set_lines(buf, 0, -1, true, &Array::default()).unwrap();
+        assert_synced(&mirror);
+
+        // snapshots are not affected by later edits
+        assert_eq!(before.text(), b"first\nsecond\nthird");
+        let snapshot = mirror.snapshot().unwrap();
+        assert_eq!(snapshot.changedtick(), buf_get_changedtick(buf).unwrap());
+        assert_eq!(snapshot.lines().collect::<Vec<_>>(), [b""]);
+    }
+
+    #[nvim_test::nvim_test]
+    fn mirror_resync() {
+        let buf = Buffer::new(0);
+        buf_set_lines(buf, 0, -1, true, &array!["a", "b"]).unwrap();
+        let mirror = BufferMirror::attach(buf).unwrap();
+
+        // pretend an edit was missed
+        mirror.inner.state.borrow_mut().changedtick -= 1;
+        let snapshot = mirror.snapshot().unwrap();
+        assert_eq!(snapshot.changedtick(), buf_get_changedtick(buf).unwrap());
+        assert_eq!(snapshot.text(), b"a\nb");
+
+        // an edit reporting a tick that was not seen is not applied
+        mirror.inner.state.borrow_mut().changedtick -= 1;
+        buf_set_text(buf, 0, 1, 0, 1, &array!["c"]).unwrap();
+        assert!(mirror.inner.state.borrow().stale);
+        let snapshot = mirror.snapshot().unwrap();
+        assert_eq!(snapshot.changedtick(), buf_get_changedtick(buf).unwrap());
+        assert_eq!(snapshot.text(), b"ac\nb");
+
+        // unloading the buffer detaches the mirror
+        command(c"enew | bunload! #").unwrap();
+        assert!(!mirror.inner.state.borrow().attached);
+        assert!(mirror.snapshot().is_err());
+        command(c"buffer #").unwrap();
+        buf_set_lines(buf, 0, -1, true, &array!["reloaded"]).unwrap();
+        assert_eq!(mirror.snapshot().unwrap().text(), b"reloaded");
+        buf_set_lines(buf, 0, 0, true, &array!["edit"]).unwrap();
+        {
+            let state = mirror.inner.state.borrow();
+            assert!(state.attached && !state.stale);
+            assert_eq!(state.changedtick, buf_get_changedtick(buf).unwrap());
+        }
+        assert_eq!(mirror.snapshot().unwrap().text(), b"edit\nreloaded");
+    }
+}
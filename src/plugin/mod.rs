@@ -5,8 +5,18 @@ use crate::nvim_types::{
     lua::{set_callback_name, utils::handle_callback_err_ret},
 };
 use libc::c_int;
+use mlua_sys::lua_gettop;
 use thread_lock::{init_main_lua_ptr, scoped};
 
+pub mod command;
+pub mod config;
+pub mod keymap;
+pub mod mirror;
+pub mod operator;
+#[cfg(feature = "tree-sitter")]
+pub mod parser;
+pub mod popup;
+
 pub use mlua_sys::lua_State;
 #[doc(hidden)]
 pub use nvim_test::test_pkg;
@@ -27,6 +37,9 @@ pub use nvim_test::test_pkg;
 /// plugin!(luaopen_my_entry_point, my_entry_point);
 /// ```
 ///
+/// The value returned from the entrypoint is what `require` returns in Lua. To expose functions
+/// to Lua return a [`LuaModule`](crate::nvim_types::lua::LuaModule).
+///
 /// # Panics
 ///
 /// You may get confusing compile errors when incorrect identifiers are provided. Make sure
@@ -96,14 +109,18 @@ pub unsafe fn open_plugin<Ret: IntoLua, Err: Sized + Error, F: Fn() -> Result<Re
                 #[allow(static_mut_refs)]
                 TRACKED_ARENA.reset_arena();
                 match ret {
-                    Ok(ret) => ret.push(l),
-                    Err(err) => handle_callback_err_ret(l, &err as &dyn Error),
+                    Ok(ret) => {
+                        let top = lua_gettop(l);
+                        ret.push(l);
+                        lua_gettop(l) - top
+                    }
+                    Err(err) => {
+                        handle_callback_err_ret(l, &err as &dyn Error);
+                        0
+                    }
                 }
             },
             open,
-        );
+        )
     }
-
-    // TODO: actually set ret vals
-    0
 }
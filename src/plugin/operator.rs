@@ -0,0 +1,268 @@
+//! Custom operators driven by `g@` and `operatorfunc`
+//!
+//! [`define_operator`] creates `<Plug>` mappings that set `operatorfunc` and run `g@`. Neovim then
+//! waits for a motion or text object and calls the operator with the range it covered. Counts and
+//! `.` repeat behave the same as with builtin operators.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{
+//!     nvim_types::{Error, func_types::keymap_mode::KeyMapMode},
+//!     plugin::{
+//!         keymap::{Keymap, KeymapRegistry},
+//!         operator::define_operator,
+//!     },
+//! };
+//!
+//! fn setup() -> Result<KeymapRegistry, Error> {
+//!     define_operator("my-plugin-upper", |args| {
+//!         let ((start_row, _), (end_row, _)) = (args.start, args.end);
+//!         let _ = (args.buf, start_row, end_row, args.motion);
+//!         Ok::<_, Error>(())
+//!     })?;
+//!
+//!     // `gu{motion}` runs the operator over the motion, `guu` over the current line
+//!     let mut registry = KeymapRegistry::new();
+//!     let mut map = Keymap::keys("<Plug>(my-plugin-upper)");
+//!     map.remap(true);
+//!     registry.set(&[KeyMapMode::NORMAL, KeyMapMode::VISUAL], "gu", &map)?;
+//!     let mut line = Keymap::keys("<Plug>(my-plugin-upper-line)");
+//!     line.remap(true);
+//!     registry.set(&[KeyMapMode::NORMAL], "guu", &line)?;
+//!     Ok(registry)
+//! }
+//! ```
+
+use std::{cell::RefCell, error::Error, fmt::Display, rc::Rc};
+
+use mlua_sys::{LUA_REGISTRYINDEX, lua_checkstack, lua_rawgeti, lua_setglobal};
+use thread_lock::{call_check, get_lua_ptr};
+
+use crate::{
+    nvim_funcs::{
+        buffer::buf_get_mark,
+        global::{get_current_buf, set_keymap},
+        options::set_option_value,
+    },
+    nvim_types::{
+        Buffer, Error as NvError, Integer, Object, OwnedThinString,
+        func_types::keymap_mode::KeyMapMode,
+        lua::{Function, LuaInteger},
+        opts::{
+            option::{OptionOpt, OptionScope},
+            set_keymap::SetKeymapOpts,
+        },
+    },
+};
+
+use super::keymap::plug;
+
+/// An error returned from a callback after its type is erased
+#[derive(Debug)]
+pub(super) struct CallbackError(pub(super) Box<dyn Error>);
+
+impl Display for CallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for CallbackError {}
+
+/// How the text covered by the motion is selected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MotionType {
+    /// Characterwise, the range starts and ends at the positions of the marks
+    Char,
+    /// Linewise, only the rows of the marks are relevant
+    Line,
+    /// Blockwise, the columns of the marks are the left and right edges of the block
+    Block,
+}
+
+/// The range an operator was called with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperatorArgs {
+    pub motion: MotionType,
+    /// The buffer the operator was called in
+    pub buf: Buffer,
+    /// The position of the `'[` mark, a 1-based row and 0-based column
+    pub start: (Integer, Integer),
+    /// The position of the `']` mark, a 1-based row and 0-based column
+    ///
+    /// The column is inclusive.
+    pub end: (Integer, Integer),
+}
+
+type OperatorFn = Rc<dyn Fn(MotionType) -> Result<(), CallbackError>>;
+
+thread_local! {
+    /// The function called by `operatorfunc`
+    static OPERATOR: RefCell<Option<OperatorFn>> = const { RefCell::new(None) };
+    /// The name of the global Lua function used as `operatorfunc`
+    static OPERATORFUNC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Calls the function set with [`set_operatorfunc`]
+fn run_operator(motion: OwnedThinString) -> Result<(), CallbackError> {
+    let motion = match motion.as_thinstr().as_slice() {
+        b"char" => MotionType::Char,
+        b"line" => MotionType::Line,
+        b"block" => MotionType::Block,
+        _ => {
+            return Err(CallbackError(
+                format!(
+                    "unknown motion type {:?}",
+                    motion.as_thinstr().to_str_lossy()
+                )
+                .into(),
+            ));
+        }
+    };
+    let f = OPERATOR.with_borrow(|f| f.clone());
+    match f {
+        Some(f) => f(motion),
+        None => Ok(()),
+    }
+}
+
+/// Returns the `operatorfunc` value that calls [`run_operator`]
+///
+/// The function is stored in a Lua global as `operatorfunc` only accepts a function name. Each
+/// library that uses nvimium gets its own copy of this module so the address of [`run_operator`]
+/// is used to keep the names unique.
+fn operatorfunc() -> String {
+    OPERATORFUNC.with_borrow_mut(|name| {
+        name.get_or_insert_with(|| {
+            let global = format!(
+                "__nvimium_operatorfunc_{:x}",
+                run_operator as *const () as usize
+            );
+            let f = Function::wrap(run_operator).into_luaref();
+            call_check();
+            let mut l = get_lua_ptr();
+            let l = l.as_ptr();
+            let c_global = std::ffi::CString::new(global.as_str()).unwrap();
+            unsafe {
+                if lua_checkstack(l, 1) == 0 {
+                    panic!("not enough stack space to set operatorfunc");
+                }
+                lua_rawgeti(l, LUA_REGISTRYINDEX, f.as_int() as LuaInteger);
+                lua_setglobal(l, c_global.as_ptr());
+            }
+            format!("v:lua.{}", global)
+        })
+        .clone()
+    })
+}
+
+/// Makes the function the one called by `operatorfunc` until it is set again
+pub(super) fn set_operatorfunc(f: OperatorFn) -> Result<(), NvError> {
+    let func = operatorfunc();
+    OPERATOR.with_borrow_mut(|operator| *operator = Some(f));
+    set_option_value(
+        c"operatorfunc",
+        Object::String(OwnedThinString::from(func.as_str())),
+        OptionOpt::default().scope(OptionScope::Global),
+    )
+}
+
+/// Defines an operator and the `<Plug>` mappings that run it
+///
+/// Two mappings are created:
+/// - `<Plug>(name)` in normal and visual mode waits for a motion, or uses the selection in visual
+///   mode.
+/// - `<Plug>(name-line)` in normal mode runs the operator on `count` lines.
+///
+/// The mappings are not bound to any keys, users are expected to map to them with `remap`. An
+/// error returned from the closure is reported as an error message.
+pub fn define_operator<E: 'static + Error, F: 'static + Fn(&OperatorArgs) -> Result<(), E>>(
+    name: &str,
+    f: F,
+) -> Result<(), NvError> {
+    let f: OperatorFn = Rc::new(move |motion| {
+        let buf = get_current_buf();
+        let mark = |name| buf_get_mark(buf, name).map_err(|err| CallbackError(Box::new(err)));
+        let args = OperatorArgs {
+            motion,
+            buf,
+            start: mark(c"[")?,
+            end: mark(c"]")?,
+        };
+        f(&args).map_err(|err| CallbackError(Box::new(err)))
+    });
+
+    let operator = OwnedThinString::from(plug(name).as_str());
+    let line = OwnedThinString::from(plug(&format!("{}-line", name)).as_str());
+    for (lhs, modes, keys) in [
+        (
+            &operator,
+            &[KeyMapMode::NORMAL, KeyMapMode::VISUAL][..],
+            c"g@",
+        ),
+        (&line, &[KeyMapMode::NORMAL][..], c"g@_"),
+    ] {
+        for mode in modes {
+            let f = f.clone();
+            let callback = Function::wrap(move |()| {
+                set_operatorfunc(f.clone())?;
+                Ok::<_, NvError>(keys)
+            });
+            let mut opts = SetKeymapOpts::default();
+            opts.noremap(true)
+                .silent(true)
+                .expr(true)
+                .set_callback(callback.into_luaref());
+            set_keymap(mode.clone(), lhs.as_thinstr(), c"", &mut opts)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+    use crate::{
+        self as nvimium, array,
+        nvim_funcs::{buffer::buf_set_lines, global::get_keymap, vimscript::command},
+        nvim_types::{Buffer, OwnedThinString, func_types::keymap_mode::KeyMapMode},
+    };
+
+    use super::{MotionType, OperatorArgs, define_operator};
+
+    #[nvim_test::nvim_test]
+    fn define_operator_mappings() {
+        let called: Rc<RefCell<Vec<OperatorArgs>>> = Rc::default();
+        let c = called.clone();
+        define_operator("nvimium-test-op", move |args| {
+            c.borrow_mut().push(args.clone());
+            Ok::<_, Infallible>(())
+        })
+        .unwrap();
+
+        let normal = get_keymap(KeyMapMode::NORMAL);
+        for lhs in ["<Plug>(nvimium-test-op)", "<Plug>(nvimium-test-op-line)"] {
+            let lhs = OwnedThinString::from(lhs);
+            assert!(normal.maps.iter().any(|map| map.lhs == lhs && map.expr));
+        }
+        let visual = get_keymap(KeyMapMode::VISUAL);
+        let lhs = OwnedThinString::from("<Plug>(nvimium-test-op)");
+        assert!(visual.maps.iter().any(|map| map.lhs == lhs));
+
+        buf_set_lines(Buffer::new(0), 0, -1, true, &array!["one", "two", "three"]).unwrap();
+        command(c"execute \"normal gg2\\<Plug>(nvimium-test-op-line)\"").unwrap();
+        command(c"execute \"normal 3G\\<Plug>(nvimium-test-op)e\"").unwrap();
+        // repeats the last operator along with its motion
+        command(c"normal gg.").unwrap();
+
+        let called = called.borrow();
+        assert_eq!(called.len(), 3);
+        assert_eq!(called[0].motion, MotionType::Line);
+        assert_eq!((called[0].start.0, called[0].end.0), (1, 2));
+        assert_eq!(called[1].motion, MotionType::Char);
+        assert_eq!((called[1].start, called[1].end), ((3, 0), (3, 4)));
+        assert_eq!((called[2].start, called[2].end), ((1, 0), (1, 2)));
+    }
+}
@@ -0,0 +1,501 @@
+//! Incremental [`tree_sitter`] parsing of buffers
+//!
+//! [`BufferParser::attach`] follows the edits of a buffer with a [`BufferMirror`]. Each edit is
+//! applied to the last syntax tree as an [`InputEdit`], and the buffer is only parsed again when
+//! [`BufferParser::parse`] is called. The ranges that were edited or whose syntax changed are
+//! returned along with the new tree so that only those parts need to be highlighted again.
+//!
+//! Requires the `tree-sitter` feature.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{
+//!     nvim_types::{Buffer, Error},
+//!     plugin::parser::BufferParser,
+//! };
+//!
+//! fn redraw(parser: &BufferParser) -> Result<(), Error> {
+//!     let parsed = parser.parse()?;
+//!     for range in &parsed.changed_ranges {
+//!         // update extmarks in the range
+//!         let _ = (range.start_point.row, range.end_point.row);
+//!     }
+//!     let text = parsed.snapshot.text();
+//!     let root = parsed.tree.root_node();
+//!     let _ = root.utf8_text(&text);
+//!     Ok(())
+//! }
+//!
+//! fn setup(language: &tree_sitter::Language) -> Result<BufferParser, Error> {
+//!     let parser = BufferParser::attach(Buffer::new(0), language).unwrap();
+//!     redraw(&parser)?;
+//!     Ok(parser)
+//! }
+//! ```
+
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    rc::{Rc, Weak},
+};
+
+use tree_sitter::{InputEdit, Language, LanguageError, Parser, Point, Range, Tree};
+
+use crate::{
+    nvim_types::{Buffer, Error, args::buf_attach_cb::BufOnBytesArgs},
+    plugin::mirror::{BufferMirror, MirrorEvent, Snapshot, end_pos},
+};
+
+#[derive(Debug)]
+pub enum ParserError {
+    /// Attaching to the buffer failed
+    Nvim(Error),
+    /// The language is not compatible with the linked tree-sitter version
+    Language(LanguageError),
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nvim(err) => write!(f, "{}", err),
+            Self::Language(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+impl From<Error> for ParserError {
+    fn from(value: Error) -> Self {
+        Self::Nvim(value)
+    }
+}
+
+impl From<LanguageError> for ParserError {
+    fn from(value: LanguageError) -> Self {
+        Self::Language(value)
+    }
+}
+
+/// The result of [`BufferParser::parse`]
+#[derive(Clone, Debug)]
+pub struct Parsed {
+    /// The syntax tree of the text in `snapshot`
+    pub tree: Tree,
+    /// The text that was parsed
+    ///
+    /// Every line is followed by a newline when parsing, including the last one.
+    pub snapshot: Snapshot,
+    /// The ranges that were edited or whose syntax changed since the previous call to
+    /// [`BufferParser::parse`]
+    ///
+    /// The ranges are sorted and do not overlap. This covers the entire buffer when it was parsed
+    /// from scratch.
+    pub changed_ranges: Vec<Range>,
+}
+
+struct ParserState {
+    parser: Parser,
+    /// The last tree with the edits since it was parsed applied to it
+    tree: Option<Tree>,
+    /// The text inserted by the edits since the last parse, in positions of the current text
+    edits: Vec<Range>,
+}
+
+/// Keeps a syntax tree of a buffer
+///
+/// See the [module documentation](self) for details.
+pub struct BufferParser {
+    mirror: BufferMirror,
+    state: Rc<RefCell<ParserState>>,
+}
+
+impl BufferParser {
+    /// Attaches to a buffer to parse it with `language`
+    ///
+    /// A buffer of 0 attaches to the current buffer. The buffer is not parsed until
+    /// [`BufferParser::parse`] is called.
+    pub fn attach(buf: Buffer, language: &Language) -> Result<Self, ParserError> {
+        let mut parser = Parser::new();
+        parser.set_language(language)?;
+        let mirror = BufferMirror::attach(buf)?;
+        let state = Rc::new(RefCell::new(ParserState {
+            parser,
+            tree: None,
+            edits: Vec::new(),
+        }));
+
+        let weak = Rc::downgrade(&state);
+        mirror.listen(move |event| on_event(&weak, event));
+
+        Ok(Self { mirror, state })
+    }
+
+    pub fn buf(&self) -> Buffer {
+        self.mirror.buf()
+    }
+
+    /// Parses the buffer if it was edited since the last call and returns the syntax tree
+    ///
+    /// An error is returned if the buffer is no longer valid or loaded.
+    pub fn parse(&self) -> Result<Parsed, Error> {
+        // may read the buffer again which resets the tree
+        let snapshot = self.mirror.snapshot()?;
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        if let Some(tree) = &state.tree
+            && state.edits.is_empty()
+        {
+            return Ok(Parsed {
+                tree: tree.clone(),
+                snapshot,
+                changed_ranges: Vec::new(),
+            });
+        }
+
+        let old = state.tree.take();
+        let tree = state
+            .parser
+            .parse_with_options(
+                &mut |_, point| read(snapshot.line(point.row), point.column),
+                old.as_ref(),
+                None,
+            )
+            .expect("a language is set and parsing is never cancelled");
+        let changed_ranges = match &old {
+            Some(old) => merge_ranges(old.changed_ranges(&tree).chain(state.edits.drain(..))),
+            None => vec![full_range(&snapshot)],
+        };
+        state.tree = Some(tree.clone());
+
+        Ok(Parsed {
+            tree,
+            snapshot,
+            changed_ranges,
+        })
+    }
+}
+
+fn on_event(state: &Weak<RefCell<ParserState>>, event: MirrorEvent) {
+    let Some(state) = state.upgrade() else {
+        return;
+    };
+    let mut state = state.borrow_mut();
+    match event {
+        MirrorEvent::Edit(args) => match (&mut state.tree, input_edit(args)) {
+            (Some(tree), Some(edit)) => {
+                tree.edit(&edit);
+                for range in &mut state.edits {
+                    edit_range(range, &edit);
+                }
+                state.edits.push(Range {
+                    start_byte: edit.start_byte,
+                    end_byte: edit.new_end_byte,
+                    start_point: edit.start_position,
+                    end_point: edit.new_end_position,
+                });
+            }
+            (tree, _) => *tree = None,
+        },
+        MirrorEvent::Resync => state.tree = None,
+    }
+    if state.tree.is_none() {
+        state.edits.clear();
+    }
+}
+
+/// Converts the arguments of an `on_bytes` event to an edit of a tree
+fn input_edit(args: &BufOnBytesArgs) -> Option<InputEdit> {
+    let point = |(row, col)| {
+        Some(Point::new(
+            usize::try_from(row).ok()?,
+            usize::try_from(col).ok()?,
+        ))
+    };
+    let start_byte = usize::try_from(args.start_byte).ok()?;
+
+    Some(InputEdit {
+        start_byte,
+        old_end_byte: start_byte + usize::try_from(args.old_end_byte).ok()?,
+        new_end_byte: start_byte + usize::try_from(args.new_end_byte).ok()?,
+        start_position: point((args.start_row, args.start_col))?,
+        old_end_position: point(end_pos(
+            args.start_row,
+            args.start_col,
+            args.old_end_row,
+            args.old_end_col,
+        ))?,
+        new_end_position: point(end_pos(
+            args.start_row,
+            args.start_col,
+            args.new_end_row,
+            args.new_end_col,
+        ))?,
+    })
+}
+
+/// Moves a range of the text before `edit` to the text after it
+///
+/// Positions inside the replaced text move to its end, the same as the nodes of [`Tree::edit`].
+fn edit_range(range: &mut Range, edit: &InputEdit) {
+    let edit_pos = |byte: &mut usize, point: &mut Point| {
+        if *byte >= edit.old_end_byte {
+            *byte = edit.new_end_byte + (*byte - edit.old_end_byte);
+            *point = if point.row > edit.old_end_position.row {
+                Point::new(
+                    edit.new_end_position.row + (point.row - edit.old_end_position.row),
+                    point.column,
+                )
+            } else {
+                Point::new(
+                    edit.new_end_position.row,
+                    edit.new_end_position.column + (point.column - edit.old_end_position.column),
+                )
+            };
+        } else if *byte > edit.start_byte {
+            *byte = edit.new_end_byte;
+            *point = edit.new_end_position;
+        }
+    };
+    edit_pos(&mut range.start_byte, &mut range.start_point);
+    edit_pos(&mut range.end_byte, &mut range.end_point);
+}
+
+/// Sorts the ranges and joins the ones that overlap or touch
+fn merge_ranges(ranges: impl Iterator<Item = Range>) -> Vec<Range> {
+    let mut ranges: Vec<_> = ranges.collect();
+    ranges.sort_unstable_by_key(|range| (range.start_byte, range.end_byte));
+    let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start_byte <= last.end_byte => {
+                if range.end_byte > last.end_byte {
+                    last.end_byte = range.end_byte;
+                    last.end_point = range.end_point;
+                }
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Returns the text of a line starting at `column` followed by a newline
+fn read(line: Option<&[u8]>, column: usize) -> &[u8] {
+    match line {
+        Some(line) if column < line.len() => &line[column..],
+        Some(_) => b"\n",
+        None => &[],
+    }
+}
+
+fn full_range(snapshot: &Snapshot) -> Range {
+    Range {
+        start_byte: 0,
+        end_byte: snapshot.lines().map(|line| line.len() + 1).sum(),
+        start_point: Point::new(0, 0),
+        end_point: Point::new(snapshot.line_count(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::{InputEdit, Parser, Point, Range};
+
+    use crate::{
+        nvim_types::{Buffer, args::buf_attach_cb::BufOnBytesArgs},
+        th,
+    };
+
+    use super::{edit_range, input_edit, merge_ranges, read};
+    #[cfg(all(not(miri), feature = "testing"))]
+    use crate::{
+        self as nvimium, array,
+        nvim_funcs::{
+            buffer::{buf_set_lines, buf_set_text},
+            vimscript::command,
+        },
+        plugin::parser::{BufferParser, Parsed},
+    };
+
+    fn parse(
+        parser: &mut Parser,
+        lines: &[&str],
+        old: Option<&tree_sitter::Tree>,
+    ) -> tree_sitter::Tree {
+        parser
+            .parse_with_options(
+                &mut |_, point| read(lines.get(point.row).map(|l| l.as_bytes()), point.column),
+                old,
+                None,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn incremental_parse() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .unwrap();
+        let before = ["fn main() {", "    let a = 1;", "}"];
+        let mut tree = parse(&mut parser, &before, None);
+
+        // `1;` replaced with `foo(\n        2,\n    );`
+        let args = BufOnBytesArgs {
+            source: th!("bytes"),
+            bufnr: Buffer::new(1),
+            changedtick: 3,
+            start_row: 1,
+            start_col: 12,
+            start_byte: 24,
+            old_end_row: 0,
+            old_end_col: 1,
+            old_end_byte: 1,
+            new_end_row: 2,
+            new_end_col: 5,
+            new_end_byte: 21,
+        };
+        let edit = input_edit(&args).unwrap();
+        assert_eq!(edit.old_end_byte, 25);
+        assert_eq!(edit.new_end_byte, 45);
+        assert_eq!(edit.old_end_position, Point::new(1, 13));
+        assert_eq!(edit.new_end_position, Point::new(3, 5));
+        tree.edit(&edit);
+
+        let after = [
+            "fn main() {",
+            "    let a = foo(",
+            "        2,",
+            "    );",
+            "}",
+        ];
+        let new = parse(&mut parser, &after, Some(&tree));
+        let fresh = parse(&mut parser, &after, None);
+        assert_eq!(new.root_node().to_sexp(), fresh.root_node().to_sexp());
+        assert!(!new.root_node().has_error());
+
+        let changed: Vec<_> = tree.changed_ranges(&new).collect();
+        assert!(!changed.is_empty());
+        assert!(
+            changed
+                .iter()
+                .all(|r| r.start_point.row >= 1 && r.end_point.row <= 3)
+        );
+    }
+
+    #[cfg(all(not(miri), feature = "testing"))]
+    #[nvim_test::nvim_test]
+    fn parser_edits() {
+        /// Checks that the tree matches a parse of the snapshot from scratch and returns the rows
+        /// that changed
+        fn assert_fresh(parsed: &Parsed) -> Vec<(usize, usize)> {
+            let mut parser = Parser::new();
+            parser
+                .set_language(&tree_sitter_rust::LANGUAGE.into())
+                .unwrap();
+            let fresh = parser
+                .parse_with_options(
+                    &mut |_, point| read(parsed.snapshot.line(point.row), point.column),
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                parsed.tree.root_node().to_sexp(),
+                fresh.root_node().to_sexp()
+            );
+            parsed
+                .changed_ranges
+                .iter()
+                .map(|range| (range.start_point.row, range.end_point.row))
+                .collect()
+        }
+
+        let buf = Buffer::new(0);
+        buf_set_lines(
+            buf,
+            0,
+            -1,
+            true,
+            &array!["fn main() {", "    let a = 1;", "}"],
+        )
+        .unwrap();
+        let parser = BufferParser::attach(buf, &tree_sitter_rust::LANGUAGE.into()).unwrap();
+        assert_eq!(assert_fresh(&parser.parse().unwrap()), [(0, 3)]);
+        assert!(parser.parse().unwrap().changed_ranges.is_empty());
+
+        // `1` replaced with `foo(2)`
+        buf_set_text(buf, 1, 12, 1, 13, &array!["foo(2)"]).unwrap();
+        let changed = assert_fresh(&parser.parse().unwrap());
+        assert!(!changed.is_empty());
+        assert!(changed.iter().any(|&(start, end)| start <= 1 && end >= 1));
+        assert!(parser.parse().unwrap().changed_ranges.is_empty());
+
+        // renaming `a` to `b` keeps the structure of the tree
+        buf_set_text(buf, 1, 8, 1, 9, &array!["b"]).unwrap();
+        assert_eq!(assert_fresh(&parser.parse().unwrap()), [(1, 1)]);
+
+        command(c"normal! GOlet b = 2;").unwrap();
+        let parsed = parser.parse().unwrap();
+        assert_eq!(parsed.snapshot.line(2), Some(&b"let b = 2;"[..]));
+        let changed = assert_fresh(&parsed);
+        assert!(!changed.is_empty());
+        assert!(changed.iter().any(|&(start, end)| start <= 2 && end >= 2));
+        assert!(parser.parse().unwrap().changed_ranges.is_empty());
+    }
+
+    #[test]
+    fn edited_ranges() {
+        let range = |start: (usize, usize, usize), end: (usize, usize, usize)| Range {
+            start_byte: start.0,
+            end_byte: end.0,
+            start_point: Point::new(start.1, start.2),
+            end_point: Point::new(end.1, end.2),
+        };
+        // `ab\ncd\nef` with `c` replaced by `x\ny`
+        let edit = InputEdit {
+            start_byte: 3,
+            old_end_byte: 4,
+            new_end_byte: 6,
+            start_position: Point::new(1, 0),
+            old_end_position: Point::new(1, 1),
+            new_end_position: Point::new(2, 1),
+        };
+
+        let mut before = range((0, 0, 0), (2, 0, 2));
+        edit_range(&mut before, &edit);
+        assert_eq!(before, range((0, 0, 0), (2, 0, 2)));
+        let mut same_line = range((4, 1, 1), (5, 1, 2));
+        edit_range(&mut same_line, &edit);
+        assert_eq!(same_line, range((6, 2, 1), (7, 2, 2)));
+        let mut after = range((6, 2, 0), (8, 2, 2));
+        edit_range(&mut after, &edit);
+        assert_eq!(after, range((8, 3, 0), (10, 3, 2)));
+        let mut inside = range((3, 1, 0), (4, 1, 1));
+        edit_range(&mut inside, &edit);
+        assert_eq!(inside, range((3, 1, 0), (6, 2, 1)));
+
+        let merged = merge_ranges(
+            [
+                range((8, 3, 0), (10, 3, 2)),
+                range((0, 0, 0), (2, 0, 2)),
+                range((1, 0, 1), (3, 1, 0)),
+                range((3, 1, 0), (3, 1, 0)),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(
+            merged,
+            [range((0, 0, 0), (3, 1, 0)), range((8, 3, 0), (10, 3, 2))]
+        );
+    }
+
+    #[test]
+    fn read_lines() {
+        assert_eq!(read(Some(b"abc"), 1), b"bc");
+        assert_eq!(read(Some(b"abc"), 3), b"\n");
+        assert_eq!(read(Some(b""), 0), b"\n");
+        assert_eq!(read(None, 0), b"");
+    }
+}
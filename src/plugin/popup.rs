@@ -0,0 +1,568 @@
+//! Floating windows that show text
+//!
+//! [`Popup::open`] creates a scratch buffer with the provided lines and shows it in a floating
+//! window sized to fit them. The window is placed again when the editor is resized or a window is
+//! scrolled, and can be closed automatically when the cursor moves or the buffer is left.
+//!
+//! The window and buffer are removed when the [`Popup`] is dropped.
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{
+//!     nvim_types::{Error, opts::win_opts::Border},
+//!     plugin::popup::{CloseEvent, Popup, PopupOpts, PopupPosition},
+//! };
+//!
+//! fn hover() -> Result<Popup, Error> {
+//!     let popup = Popup::open(
+//!         &["fn main()", "", "The entry point"],
+//!         PopupOpts::default()
+//!             .position(PopupPosition::Cursor { row: 1, col: 0 })
+//!             .border(Border::Rounded)
+//!             .close_on(&[CloseEvent::CursorMoved, CloseEvent::BufLeave]),
+//!     )?;
+//!     popup.highlight(0, 0, None, "Function")?;
+//!     // the popup must be kept alive for it to stay open
+//!     Ok(popup)
+//! }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    convert::Infallible,
+    ffi::CStr,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    nvim_funcs::{
+        autocmd::{create_augroup, create_autocmd, del_augroup_by_id},
+        buffer::{buf_delete, buf_is_valid, buf_set_lines},
+        extmark::{buf_clear_namespace, buf_set_extmark, create_namespace},
+        global::{create_buf, get_current_buf, strwidth},
+        options::{get_option_value, set_option_value},
+        win_config::open_win,
+        window::{win_close, win_is_valid, win_set_config},
+    },
+    nvim_types::{
+        Array, Buffer, Error, Float, Integer, KVec, NameSpace, Object, OwnedThinString, Window,
+        args::autocmd::AutocmdArgs,
+        lua::{Function, utils::vim_function},
+        opts::{
+            buf_delete::BufDeleteOpts,
+            create_augroup::CreateAugroupOpts,
+            create_autocmd::CreateAutocmdOpts,
+            option::OptionOpt,
+            set_extmark::SetExtmarkOpts,
+            win_opts::{Border, Relative, Style, WinConfig},
+        },
+    },
+};
+
+/// Where a popup is placed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PopupPosition {
+    /// Offset from the cursor position in the current window
+    Cursor { row: Integer, col: Integer },
+    /// Centered in the editor
+    Center,
+    /// At a row and column of the editor grid
+    Editor { row: Integer, col: Integer },
+    /// At a row and column of a window
+    Window {
+        win: Window,
+        row: Integer,
+        col: Integer,
+    },
+}
+
+/// An event that closes a popup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseEvent {
+    /// The cursor moved in normal mode in the buffer the popup was opened from
+    CursorMoved,
+    /// The cursor moved in insert mode in the buffer the popup was opened from
+    CursorMovedI,
+    /// Insert mode was entered in the buffer the popup was opened from
+    InsertEnter,
+    /// The buffer the popup was opened from was left, or the popup buffer if it was entered
+    BufLeave,
+}
+
+impl CloseEvent {
+    fn event(self) -> &'static CStr {
+        match self {
+            Self::CursorMoved => c"CursorMoved",
+            Self::CursorMovedI => c"CursorMovedI",
+            Self::InsertEnter => c"InsertEnter",
+            Self::BufLeave => c"BufLeave",
+        }
+    }
+}
+
+/// Options for [`Popup::open`]
+///
+/// By default the popup is placed below the cursor without a border, is not entered and is not
+/// closed by any event.
+#[derive(Clone, Debug)]
+pub struct PopupOpts {
+    position: PopupPosition,
+    width: Option<Integer>,
+    height: Option<Integer>,
+    max_width: Option<Integer>,
+    max_height: Option<Integer>,
+    border: Option<Border>,
+    title: Option<OwnedThinString>,
+    enter: bool,
+    focusable: bool,
+    zindex: Option<Integer>,
+    close_on: Vec<CloseEvent>,
+}
+
+impl Default for PopupOpts {
+    fn default() -> Self {
+        Self {
+            position: PopupPosition::Cursor { row: 1, col: 0 },
+            width: None,
+            height: None,
+            max_width: None,
+            max_height: None,
+            border: None,
+            title: None,
+            enter: false,
+            focusable: true,
+            zindex: None,
+            close_on: Vec::new(),
+        }
+    }
+}
+
+impl PopupOpts {
+    pub fn position(&mut self, position: PopupPosition) -> &mut Self {
+        self.position = position;
+        self
+    }
+
+    /// A fixed width, by default the width of the longest line is used
+    pub fn width(&mut self, width: Integer) -> &mut Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// A fixed height, by default the number of lines is used
+    pub fn height(&mut self, height: Integer) -> &mut Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn max_width(&mut self, max_width: Integer) -> &mut Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn max_height(&mut self, max_height: Integer) -> &mut Self {
+        self.max_height = Some(max_height);
+        self
+    }
+
+    pub fn border(&mut self, border: Border) -> &mut Self {
+        self.border = Some(border);
+        self
+    }
+
+    /// The title shown in the border, requires a border to be set
+    pub fn title<S: AsRef<str>>(&mut self, title: S) -> &mut Self {
+        self.title = Some(OwnedThinString::from(title.as_ref()));
+        self
+    }
+
+    /// Whether the popup window becomes the current window
+    pub fn enter(&mut self, enter: bool) -> &mut Self {
+        self.enter = enter;
+        self
+    }
+
+    pub fn focusable(&mut self, focusable: bool) -> &mut Self {
+        self.focusable = focusable;
+        self
+    }
+
+    pub fn zindex(&mut self, zindex: Integer) -> &mut Self {
+        self.zindex = Some(zindex);
+        self
+    }
+
+    /// Events that close the popup
+    pub fn close_on(&mut self, events: &[CloseEvent]) -> &mut Self {
+        self.close_on = events.to_vec();
+        self
+    }
+}
+
+struct PopupInner {
+    buf: Buffer,
+    win: Cell<Option<Window>>,
+    augroup: Integer,
+    ns: NameSpace,
+    lines: RefCell<Vec<String>>,
+    opts: PopupOpts,
+}
+
+/// A floating window showing a scratch buffer
+///
+/// Closing the window by other means, such as `:close`, is detected the next time the popup is
+/// placed again. The window and buffer are removed when dropped.
+pub struct Popup {
+    inner: Rc<PopupInner>,
+}
+
+fn editor_size() -> Result<(Integer, Integer), Error> {
+    let get = |name: &CStr| -> Result<Integer, Error> {
+        Ok(get_option_value(name, &mut OptionOpt::default())?
+            .as_int()
+            .unwrap_or(0))
+    };
+    Ok((get(c"columns")?, get(c"lines")?))
+}
+
+fn lines_to_array(lines: &[String]) -> Array {
+    Array::from(KVec::from_iter(
+        lines
+            .iter()
+            .map(|line| Object::String(OwnedThinString::from(line.as_str()))),
+    ))
+}
+
+impl Popup {
+    /// Creates the buffer and opens the window
+    ///
+    /// The lines cannot contain newlines.
+    ///
+    /// # Panics
+    ///
+    /// If called from a thread that does not have access to Neovim.
+    pub fn open<S: AsRef<str>>(lines: &[S], opts: &PopupOpts) -> Result<Self, Error> {
+        let origin = get_current_buf();
+        let buf = create_buf(false, true)?;
+        set_option_value(
+            c"bufhidden",
+            Object::from("wipe"),
+            OptionOpt::default().buf(buf),
+        )?;
+
+        let augroup = create_augroup(
+            OwnedThinString::from(format!("nvimium_popup_{}", buf.as_int()).as_str()),
+            CreateAugroupOpts::default().clear(true),
+        );
+        let augroup = match augroup {
+            Ok(augroup) => augroup,
+            Err(err) => {
+                let _ = buf_delete(buf, BufDeleteOpts::default().force(true));
+                return Err(err);
+            }
+        };
+
+        let popup = Self {
+            inner: Rc::new(PopupInner {
+                buf,
+                win: Cell::new(None),
+                augroup,
+                ns: create_namespace(c"nvimium_popup"),
+                lines: RefCell::new(Vec::new()),
+                opts: opts.clone(),
+            }),
+        };
+        // dropping the popup on error cleans up the buffer and group
+        popup.set_lines(lines)?;
+        let win = open_win(buf, opts.enter, &popup.inner.config()?)?;
+        popup.inner.win.set(Some(win));
+        popup.inner.autocmds(origin)?;
+
+        Ok(popup)
+    }
+
+    pub fn buf(&self) -> Buffer {
+        self.inner.buf
+    }
+
+    /// Returns the window if the popup is open
+    pub fn win(&self) -> Option<Window> {
+        self.inner.win.get().filter(|win| win_is_valid(*win))
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.win().is_some()
+    }
+
+    /// Replaces the lines of the popup and resizes it to fit them
+    ///
+    /// Highlights are cleared.
+    pub fn set_lines<S: AsRef<str>>(&self, lines: &[S]) -> Result<(), Error> {
+        let inner = &self.inner;
+        let lines: Vec<String> = lines.iter().map(|line| line.as_ref().to_string()).collect();
+        let buf_opt = || {
+            let mut opts = OptionOpt::default();
+            opts.buf(inner.buf);
+            opts
+        };
+        set_option_value(c"modifiable", Object::Bool(true), &mut buf_opt())?;
+        buf_clear_namespace(inner.buf, inner.ns, 0, -1)?;
+        buf_set_lines(inner.buf, 0, -1, false, &lines_to_array(&lines))?;
+        set_option_value(c"modifiable", Object::Bool(false), &mut buf_opt())?;
+        *inner.lines.borrow_mut() = lines;
+
+        if inner.win.get().is_some() {
+            self.reposition()?;
+        }
+        Ok(())
+    }
+
+    /// Highlights a line from `start_col` to `end_col`, or to the end of the line if it is [`None`]
+    ///
+    /// The line is 0-based and columns are byte offsets.
+    pub fn highlight(
+        &self,
+        line: Integer,
+        start_col: Integer,
+        end_col: Option<Integer>,
+        hl_group: &str,
+    ) -> Result<(), Error> {
+        let end_col = match end_col {
+            Some(end_col) => end_col,
+            None => {
+                let lines = self.inner.lines.borrow();
+                let len = usize::try_from(line)
+                    .ok()
+                    .and_then(|line| lines.get(line))
+                    .map_or(0, String::len);
+                Integer::try_from(len).unwrap_or(Integer::MAX)
+            }
+        };
+        let mut opts = SetExtmarkOpts::default();
+        opts.end_row(line)
+            .end_col(end_col)
+            .hl_group_name(OwnedThinString::from(hl_group));
+        buf_set_extmark(self.inner.buf, self.inner.ns, line, start_col, &opts)?;
+        Ok(())
+    }
+
+    /// Places the window again using the current size of the editor
+    ///
+    /// Called automatically on `VimResized` and `WinScrolled`.
+    pub fn reposition(&self) -> Result<(), Error> {
+        self.inner.reposition()
+    }
+
+    /// Closes the window and deletes the buffer
+    pub fn close(&self) {
+        self.inner.close();
+    }
+}
+
+impl PopupInner {
+    /// Sets the size and position of the window
+    ///
+    /// Only these fields change when the window is placed again, the rest of the configuration is
+    /// kept by Neovim.
+    fn layout(&self, config: &mut WinConfig<'_>) -> Result<(), Error> {
+        let opts = &self.opts;
+        let (columns, rows) = editor_size()?;
+        // leave space for the border and the command line
+        let border = if opts.border.is_some() { 2 } else { 0 };
+
+        let lines = self.lines.borrow();
+        let mut content_width = 1;
+        for line in lines.iter() {
+            content_width = content_width.max(strwidth(OwnedThinString::from(line.as_str()))?);
+        }
+        let width = opts
+            .width
+            .unwrap_or(content_width)
+            .min(opts.max_width.unwrap_or(Integer::MAX))
+            .min(columns - border)
+            .max(1);
+        let height = opts
+            .height
+            .unwrap_or(Integer::try_from(lines.len()).unwrap_or(Integer::MAX))
+            .min(opts.max_height.unwrap_or(Integer::MAX))
+            .min(rows - border - 2)
+            .max(1);
+
+        config.width(width).height(height);
+        match opts.position {
+            PopupPosition::Cursor { row, col } => {
+                config
+                    .relative(Relative::Cursor)
+                    .row(row as Float)
+                    .col(col as Float);
+            }
+            PopupPosition::Center => {
+                config
+                    .relative(Relative::Editor)
+                    .row(((rows - height - border) / 2).max(0) as Float)
+                    .col(((columns - width - border) / 2).max(0) as Float);
+            }
+            PopupPosition::Editor { row, col } => {
+                config
+                    .relative(Relative::Editor)
+                    .row(row as Float)
+                    .col(col as Float);
+            }
+            PopupPosition::Window { win, row, col } => {
+                config
+                    .relative(Relative::Win)
+                    .win(win)
+                    .row(row as Float)
+                    .col(col as Float);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The configuration used to open the window
+    fn config(&self) -> Result<WinConfig<'_>, Error> {
+        let opts = &self.opts;
+        let mut config = WinConfig::default();
+        self.layout(&mut config)?;
+        config.style(Style::Minimal).focusable(opts.focusable);
+        if let Some(border) = opts.border {
+            config.border(border);
+        }
+        if let Some(title) = &opts.title {
+            config.title(title);
+        }
+        if let Some(zindex) = opts.zindex {
+            config.zindex(zindex);
+        }
+
+        Ok(config)
+    }
+
+    fn reposition(&self) -> Result<(), Error> {
+        match self.win.get() {
+            Some(win) if win_is_valid(win) => {
+                let mut config = WinConfig::default();
+                self.layout(&mut config)?;
+                win_set_config(win, &config)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn close(&self) {
+        if let Some(win) = self.win.take()
+            && win_is_valid(win)
+        {
+            let _ = win_close(win, true);
+        }
+        if buf_is_valid(self.buf) {
+            let _ = buf_delete(self.buf, BufDeleteOpts::default().force(true));
+        }
+        let _ = del_augroup_by_id(self.augroup);
+    }
+
+    /// Creates the autocommands that place the window again and close it
+    fn autocmds(self: &Rc<Self>, origin: Buffer) -> Result<(), Error> {
+        let weak = Rc::downgrade(self);
+        let mut opts = CreateAutocmdOpts::default();
+        opts.group(self.augroup)
+            .local_callback(move |_: AutocmdArgs| {
+                let Some(inner) = weak.upgrade() else {
+                    return Ok::<_, Error>(true);
+                };
+                match inner.win.get() {
+                    Some(win) if win_is_valid(win) => inner.reposition().map(|()| false),
+                    // closed by other means, clean up the rest
+                    _ => {
+                        schedule_close(Rc::downgrade(&inner));
+                        Ok(true)
+                    }
+                }
+            });
+        create_autocmd(&[c"VimResized", c"WinScrolled"], &opts)?;
+
+        for event in &self.opts.close_on {
+            let buf = match event {
+                CloseEvent::BufLeave if self.opts.enter => self.buf,
+                _ => origin,
+            };
+            let weak = Rc::downgrade(self);
+            let mut opts = CreateAutocmdOpts::default();
+            opts.group(self.augroup)
+                .buffer(buf)
+                .local_callback(move |_: AutocmdArgs| {
+                    schedule_close(weak.clone());
+                    Ok::<_, Infallible>(true)
+                });
+            create_autocmd(&[event.event()], &opts)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Closes the popup once Neovim processes its event queue
+///
+/// Windows cannot always be closed while an autocommand is running, such as during `BufLeave`.
+fn schedule_close(weak: Weak<PopupInner>) {
+    let f = Function::wrap(move |()| {
+        if let Some(inner) = weak.upgrade() {
+            inner.close();
+        }
+        Ok::<_, Infallible>(())
+    });
+    let _ = vim_function(&[c"schedule"]).call::<_, ()>(f.into_luaref());
+}
+
+impl Drop for Popup {
+    fn drop(&mut self) {
+        self.inner.close();
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use crate::{
+        self as nvimium,
+        nvim_funcs::{
+            buffer::{buf_get_lines, buf_is_valid},
+            window::{win_get_config, win_is_valid},
+        },
+        nvim_types::opts::win_opts::{Border, Relative},
+    };
+
+    use super::{Popup, PopupOpts, PopupPosition};
+
+    #[nvim_test::nvim_test]
+    fn popup_open_close() {
+        let popup = Popup::open(
+            &["first", "a longer second line"],
+            PopupOpts::default()
+                .position(PopupPosition::Center)
+                .border(Border::Single)
+                .title("Title"),
+        )
+        .unwrap();
+        let win = popup.win().unwrap();
+        let buf = popup.buf();
+        let config = win_get_config(win).unwrap();
+        assert!(matches!(config.relative, Some(Relative::Editor)));
+        assert_eq!(config.width, Some(20));
+        assert_eq!(config.height, Some(2));
+        popup.highlight(1, 2, None, "Comment").unwrap();
+
+        popup.set_lines(&["short"]).unwrap();
+        let config = win_get_config(win).unwrap();
+        assert_eq!((config.width, config.height), (Some(5), Some(1)));
+        // placing the window again keeps the border and title
+        assert!(config.border.is_some());
+        assert!(config.title.is_some());
+        let count = buf_get_lines(|lines| lines.count(), buf, 0, -1, true).unwrap();
+        assert_eq!(count, 1);
+
+        drop(popup);
+        assert!(!win_is_valid(win));
+        assert!(!buf_is_valid(buf));
+    }
+}
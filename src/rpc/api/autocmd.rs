@@ -0,0 +1,88 @@
+use crate::{
+    nvim_types::{
+        Array, AsThinString, Integer, KVec, Object,
+        opts::{
+            clear_autocmds::ClearAutocmdsOpts, create_augroup::CreateAugroupOpts,
+            create_autocmd::CreateAutocmdOpts, exec_autocmds::ExecAutocmdsOpts,
+            get_autocmds::GetAutocmdsOpts,
+        },
+        returns::autocmd::AutocmdInfo,
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret, string, strings};
+
+impl Client {
+    /// Clears the autocommands matching the provided options
+    pub fn clear_autocmds(&mut self, opts: &ClearAutocmdsOpts) -> Result<(), RpcError> {
+        self.call(c"nvim_clear_autocmds", args([opts.to_object()?]))
+            .map(drop)
+    }
+
+    /// Creates or gets an autocommand group
+    ///
+    /// Returns the id of the group.
+    pub fn create_augroup<TH: AsThinString>(
+        &mut self,
+        name: TH,
+        opts: &CreateAugroupOpts,
+    ) -> Result<Integer, RpcError> {
+        ret(self.call(
+            c"nvim_create_augroup",
+            args([string(name), opts.to_object()?]),
+        )?)
+    }
+
+    /// Creates an autocommand for the provided events
+    ///
+    /// Returns the id of the autocommand.
+    pub fn create_autocmd<TH: AsThinString>(
+        &mut self,
+        events: &[TH],
+        opts: &CreateAutocmdOpts,
+    ) -> Result<Integer, RpcError> {
+        ret(self.call(
+            c"nvim_create_autocmd",
+            args([strings(events), opts.to_object()?]),
+        )?)
+    }
+
+    /// Deletes an autocommand group and the autocommands it contains by its id
+    pub fn del_augroup_by_id(&mut self, id: Integer) -> Result<(), RpcError> {
+        self.call(c"nvim_del_augroup_by_id", args([Object::Integer(id)]))
+            .map(drop)
+    }
+
+    /// Deletes an autocommand group and the autocommands it contains by its name
+    pub fn del_augroup_by_name<TH: AsThinString>(&mut self, name: TH) -> Result<(), RpcError> {
+        self.call(c"nvim_del_augroup_by_name", args([string(name)]))
+            .map(drop)
+    }
+
+    pub fn del_autocmd(&mut self, id: Integer) -> Result<(), RpcError> {
+        self.call(c"nvim_del_autocmd", args([Object::Integer(id)]))
+            .map(drop)
+    }
+
+    /// Executes the autocommands matching the events and options
+    pub fn exec_autocmds<TH: AsThinString>(
+        &mut self,
+        events: &[TH],
+        opts: &ExecAutocmdsOpts,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_exec_autocmds",
+            args([strings(events), opts.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    /// Gets the autocommands matching the provided options
+    pub fn get_autocmds(&mut self, opts: &GetAutocmdsOpts) -> Result<KVec<AutocmdInfo>, RpcError> {
+        let autocmds = self.call(c"nvim_get_autocmds", args([opts.to_object()?]))?;
+        Ok(AutocmdInfo::from_c_func_ret_list(&mut ret::<Array>(
+            autocmds,
+        )?))
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    nvim_types::{
+        Array, AsThinString, Boolean, Buffer, Integer, KVec, Object, OwnedThinString,
+        func_types::keymap_mode::KeyMapMode,
+        opts::{
+            buf_attach::BufAttachOpts, buf_delete::BufDeleteOpts, get_text::GetTextOpts,
+            set_keymap::SetKeymapOpts, set_mark::SetMarkOpts,
+        },
+        returns::get_keymap::Keymaps,
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret, ret_list, ret_pos, string};
+
+impl Client {
+    /// Attaches to the buffer's events
+    ///
+    /// Unlike [`buf_attach`](crate::nvim_funcs::buffer::buf_attach) the events are sent as
+    /// notifications such as `nvim_buf_lines_event`, see `:h api-buffer-updates`.
+    pub fn buf_attach(
+        &mut self,
+        buf: Buffer,
+        send_buffer: Boolean,
+        opts: &BufAttachOpts,
+    ) -> Result<Boolean, RpcError> {
+        ret(self.call(
+            c"nvim_buf_attach",
+            args([
+                Object::Buffer(buf),
+                Object::Bool(send_buffer),
+                opts.to_object()?,
+            ]),
+        )?)
+    }
+
+    pub fn buf_del_mark<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+    ) -> Result<Boolean, RpcError> {
+        ret(self.call(
+            c"nvim_buf_del_mark",
+            args([Object::Buffer(buf), string(name)]),
+        )?)
+    }
+
+    pub fn buf_del_var<TH: AsThinString>(&mut self, buf: Buffer, name: TH) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_del_var",
+            args([Object::Buffer(buf), string(name)]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_delete(&mut self, buf: Buffer, opts: &BufDeleteOpts) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_delete",
+            args([Object::Buffer(buf), opts.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    /// Stops sending the buffer's events
+    pub fn buf_detach(&mut self, buf: Buffer) -> Result<Boolean, RpcError> {
+        ret(self.call(c"nvim_buf_detach", args([Object::Buffer(buf)]))?)
+    }
+
+    pub fn buf_get_changedtick(&mut self, buf: Buffer) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_buf_get_changedtick", args([Object::Buffer(buf)]))?)
+    }
+
+    pub fn buf_get_keymap(&mut self, buf: Buffer, mode: KeyMapMode) -> Result<Keymaps, RpcError> {
+        let maps = self.call(
+            c"nvim_buf_get_keymap",
+            args([Object::Buffer(buf), mode.to_object()?]),
+        )?;
+        Ok(Keymaps::from_c_func_ret(&mut ret::<Array>(maps)?))
+    }
+
+    /// Gets a line range from the buffer
+    ///
+    /// Unlike [`buf_get_lines`](crate::nvim_funcs::buffer::buf_get_lines) the lines are returned
+    /// rather than passed to a closure.
+    pub fn buf_get_lines(
+        &mut self,
+        buf: Buffer,
+        start: Integer,
+        end: Integer,
+        strict_indexing: Boolean,
+    ) -> Result<KVec<OwnedThinString>, RpcError> {
+        ret_list(self.call(
+            c"nvim_buf_get_lines",
+            args([
+                Object::Buffer(buf),
+                Object::Integer(start),
+                Object::Integer(end),
+                Object::Bool(strict_indexing),
+            ]),
+        )?)
+    }
+
+    pub fn buf_get_mark<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+    ) -> Result<(Integer, Integer), RpcError> {
+        ret_pos(self.call(
+            c"nvim_buf_get_mark",
+            args([Object::Buffer(buf), string(name)]),
+        )?)
+    }
+
+    pub fn buf_get_name(&mut self, buf: Buffer) -> Result<OwnedThinString, RpcError> {
+        ret(self.call(c"nvim_buf_get_name", args([Object::Buffer(buf)]))?)
+    }
+
+    pub fn buf_get_offset(&mut self, buf: Buffer, index: Integer) -> Result<Integer, RpcError> {
+        ret(self.call(
+            c"nvim_buf_get_offset",
+            args([Object::Buffer(buf), Object::Integer(index)]),
+        )?)
+    }
+
+    /// Gets a range from the buffer
+    ///
+    /// Unlike [`buf_get_text`](crate::nvim_funcs::buffer::buf_get_text) the lines are returned
+    /// rather than passed to a closure.
+    pub fn buf_get_text(
+        &mut self,
+        buf: Buffer,
+        start_row: Integer,
+        start_col: Integer,
+        end_row: Integer,
+        end_col: Integer,
+        opts: &GetTextOpts,
+    ) -> Result<KVec<OwnedThinString>, RpcError> {
+        ret_list(self.call(
+            c"nvim_buf_get_text",
+            args([
+                Object::Buffer(buf),
+                Object::Integer(start_row),
+                Object::Integer(start_col),
+                Object::Integer(end_row),
+                Object::Integer(end_col),
+                opts.to_object()?,
+            ]),
+        )?)
+    }
+
+    pub fn buf_get_var<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+    ) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_buf_get_var",
+            args([Object::Buffer(buf), string(name)]),
+        )
+    }
+
+    pub fn buf_is_loaded(&mut self, buf: Buffer) -> Result<Boolean, RpcError> {
+        ret(self.call(c"nvim_buf_is_loaded", args([Object::Buffer(buf)]))?)
+    }
+
+    pub fn buf_is_valid(&mut self, buf: Buffer) -> Result<Boolean, RpcError> {
+        ret(self.call(c"nvim_buf_is_valid", args([Object::Buffer(buf)]))?)
+    }
+
+    pub fn buf_line_count(&mut self, buf: Buffer) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_buf_line_count", args([Object::Buffer(buf)]))?)
+    }
+
+    pub fn buf_del_keymap<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        mode: KeyMapMode,
+        lhs: TH,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_del_keymap",
+            args([Object::Buffer(buf), mode.to_object()?, string(lhs)]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_set_keymap<TH: AsThinString, TH2: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        mode: KeyMapMode,
+        lhs: TH,
+        rhs: TH2,
+        opts: &SetKeymapOpts,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_set_keymap",
+            args([
+                Object::Buffer(buf),
+                mode.to_object()?,
+                string(lhs),
+                string(rhs),
+                opts.to_object()?,
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_set_lines(
+        &mut self,
+        buf: Buffer,
+        start: Integer,
+        end: Integer,
+        strict_indexing: Boolean,
+        replacement: &Array,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_set_lines",
+            args([
+                Object::Buffer(buf),
+                Object::Integer(start),
+                Object::Integer(end),
+                Object::Bool(strict_indexing),
+                Object::Array(replacement.clone()),
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_set_mark<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+        line: Integer,
+        col: Integer,
+        opts: &SetMarkOpts,
+    ) -> Result<Boolean, RpcError> {
+        ret(self.call(
+            c"nvim_buf_set_mark",
+            args([
+                Object::Buffer(buf),
+                string(name),
+                Object::Integer(line),
+                Object::Integer(col),
+                opts.to_object()?,
+            ]),
+        )?)
+    }
+
+    pub fn buf_set_name<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_set_name",
+            args([Object::Buffer(buf), string(name)]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_set_text(
+        &mut self,
+        buf: Buffer,
+        start_row: Integer,
+        start_col: Integer,
+        end_row: Integer,
+        end_col: Integer,
+        replacement: &Array,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_set_text",
+            args([
+                Object::Buffer(buf),
+                Object::Integer(start_row),
+                Object::Integer(start_col),
+                Object::Integer(end_row),
+                Object::Integer(end_col),
+                Object::Array(replacement.clone()),
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_set_var<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+        val: &Object,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_set_var",
+            args([Object::Buffer(buf), string(name), val.to_object()?]),
+        )
+        .map(drop)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    nvim_types::{
+        AsThinString, Buffer, Dict, Object, OwnedThinString,
+        func_types::create_user_command::UserCommand,
+        opts::{
+            cmd::Cmd, cmd_opts::CmdOpts, create_user_command::CreateUserCommandOpts,
+            get_commands::GetCommandOpts, parse_cmd::ParseCmdOpts,
+        },
+        returns::{cmd::CmdInfo, commands::CommandsInfos},
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret, string};
+
+impl Client {
+    pub fn buf_create_user_command<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+        command: UserCommand<'_>,
+        opts: &CreateUserCommandOpts<'_>,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_create_user_command",
+            args([
+                Object::Buffer(buf),
+                string(name),
+                command.to_object()?,
+                opts.to_object()?,
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_del_user_command<TH: AsThinString>(
+        &mut self,
+        buf: Buffer,
+        name: TH,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_del_user_command",
+            args([Object::Buffer(buf), string(name)]),
+        )
+        .map(drop)
+    }
+
+    pub fn buf_get_commands(
+        &mut self,
+        buf: Buffer,
+        opts: &GetCommandOpts,
+    ) -> Result<CommandsInfos, RpcError> {
+        let commands = self.call(
+            c"nvim_buf_get_commands",
+            args([Object::Buffer(buf), opts.to_object()?]),
+        )?;
+        Ok(CommandsInfos::from_c_func_ret(&mut ret::<Dict>(commands)?))
+    }
+
+    /// Executes an Ex command
+    ///
+    /// See [`cmd`](crate::nvim_funcs::command::cmd).
+    pub fn cmd(&mut self, cmd: &Cmd, opts: &CmdOpts) -> Result<OwnedThinString, RpcError> {
+        ret(self.call(c"nvim_cmd", args([cmd.to_object()?, opts.to_object()?]))?)
+    }
+
+    pub fn create_user_command<TH: AsThinString>(
+        &mut self,
+        name: TH,
+        command: UserCommand<'_>,
+        opts: &CreateUserCommandOpts<'_>,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_create_user_command",
+            args([string(name), command.to_object()?, opts.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    pub fn del_user_command<TH: AsThinString>(&mut self, name: TH) -> Result<(), RpcError> {
+        self.call(c"nvim_del_user_command", args([string(name)]))
+            .map(drop)
+    }
+
+    pub fn get_commands(&mut self, opts: &GetCommandOpts) -> Result<CommandsInfos, RpcError> {
+        let commands = self.call(c"nvim_get_commands", args([opts.to_object()?]))?;
+        Ok(CommandsInfos::from_c_func_ret(&mut ret::<Dict>(commands)?))
+    }
+
+    /// Parses a command line without executing it
+    ///
+    /// See [`parse_cmd`](crate::nvim_funcs::command::parse_cmd).
+    pub fn parse_cmd<TH: AsThinString>(&mut self, s: TH) -> Result<CmdInfo, RpcError> {
+        let cmd = self.call(
+            c"nvim_parse_cmd",
+            args([string(s), ParseCmdOpts::default().to_object()?]),
+        )?;
+        Ok(CmdInfo::from_dict(&ret::<Dict>(cmd)?))
+    }
+}
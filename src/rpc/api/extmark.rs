@@ -0,0 +1,152 @@
+use crate::{
+    nvim_types::{
+        Array, AsThinString, Boolean, Buffer, Dict, Integer, KVec, NameSpace, Object,
+        OwnedThinString,
+        extmark::ExtMark,
+        opts::{
+            get_extmark::GetExtmarkOpts,
+            get_extmarks::{ExtmarkPos, GetExtmarksOpts},
+            set_extmark::SetExtmarkOpts,
+        },
+        returns::extmark::ExtmarkInfo,
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ns, ret, ret_handle, string};
+
+fn id(id: ExtMark) -> Object {
+    Object::Integer(id.as_int().into())
+}
+
+impl Client {
+    /// Clears extmarks and highlights of a namespace in the provided line range
+    ///
+    /// See [`buf_clear_namespace`](crate::nvim_funcs::extmark::buf_clear_namespace).
+    pub fn buf_clear_namespace(
+        &mut self,
+        buf: Buffer,
+        ns: NameSpace,
+        line_start: Integer,
+        line_end: Integer,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_buf_clear_namespace",
+            args([
+                Object::Buffer(buf),
+                self::ns(ns),
+                Object::Integer(line_start),
+                Object::Integer(line_end),
+            ]),
+        )
+        .map(drop)
+    }
+
+    /// Removes an extmark
+    ///
+    /// Returns true if the extmark was found.
+    pub fn buf_del_extmark(
+        &mut self,
+        buf: Buffer,
+        ns: NameSpace,
+        id: ExtMark,
+    ) -> Result<Boolean, RpcError> {
+        ret(self.call(
+            c"nvim_buf_del_extmark",
+            args([Object::Buffer(buf), self::ns(ns), self::id(id)]),
+        )?)
+    }
+
+    /// Gets the position and optionally the details of an extmark
+    ///
+    /// Returns [`None`] if no extmark with the id exists in the namespace.
+    pub fn buf_get_extmark_by_id(
+        &mut self,
+        buf: Buffer,
+        ns: NameSpace,
+        id: ExtMark,
+        opts: &GetExtmarkOpts,
+    ) -> Result<Option<ExtmarkInfo>, RpcError> {
+        let mark = self.call(
+            c"nvim_buf_get_extmark_by_id",
+            args([
+                Object::Buffer(buf),
+                self::ns(ns),
+                self::id(id),
+                opts.to_object()?,
+            ]),
+        )?;
+        Ok(ExtmarkInfo::from_c_func_ret_by_id(
+            id,
+            &mut ret::<Array>(mark)?,
+        ))
+    }
+
+    /// Gets the extmarks in the range of `start` and `end` (inclusive)
+    ///
+    /// If `end` is before `start` the extmarks are returned in reverse order.
+    pub fn buf_get_extmarks(
+        &mut self,
+        buf: Buffer,
+        ns: NameSpace,
+        start: ExtmarkPos,
+        end: ExtmarkPos,
+        opts: &GetExtmarksOpts,
+    ) -> Result<KVec<ExtmarkInfo>, RpcError> {
+        let marks = self.call(
+            c"nvim_buf_get_extmarks",
+            args([
+                Object::Buffer(buf),
+                self::ns(ns),
+                Object::from(start),
+                Object::from(end),
+                opts.to_object()?,
+            ]),
+        )?;
+        Ok(ExtmarkInfo::from_c_func_ret_list(&mut ret::<Array>(marks)?))
+    }
+
+    /// Creates or updates an extmark at the (0,0)-indexed position
+    ///
+    /// Returns the id of the extmark.
+    pub fn buf_set_extmark(
+        &mut self,
+        buf: Buffer,
+        ns: NameSpace,
+        line: Integer,
+        col: Integer,
+        opts: &SetExtmarkOpts,
+    ) -> Result<ExtMark, RpcError> {
+        ret_handle(self.call(
+            c"nvim_buf_set_extmark",
+            args([
+                Object::Buffer(buf),
+                self::ns(ns),
+                Object::Integer(line),
+                Object::Integer(col),
+                opts.to_object()?,
+            ]),
+        )?)
+        .map(ExtMark::new)
+    }
+
+    /// Creates a new namespace or gets the existing one with the same name
+    ///
+    /// An empty name creates an anonymous namespace.
+    pub fn create_namespace<TH: AsThinString>(&mut self, name: TH) -> Result<NameSpace, RpcError> {
+        ret_handle(self.call(c"nvim_create_namespace", args([string(name)]))?).map(NameSpace::new)
+    }
+
+    /// Gets all named namespaces
+    pub fn get_namespaces(&mut self) -> Result<KVec<(OwnedThinString, NameSpace)>, RpcError> {
+        ret::<Dict>(self.call(c"nvim_get_namespaces", args([]))?)?
+            .iter()
+            .map(|kv| {
+                Ok((
+                    kv.key.clone(),
+                    NameSpace::new(ret_handle(kv.object.clone())?),
+                ))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,458 @@
+use crate::{
+    nvim_types::{
+        Array, AsThinString, Boolean, Buffer, Channel, Dict, Integer, KVec, NameSpace, Object,
+        OwnedThinString, TabPage, Window,
+        func_types::{echo::Echo, feedkeys::FeedKeysMode, keymap_mode::KeyMapMode},
+        opts::{
+            context::ContextOpts, echo::EchoOpts, eval_statusline::EvalStatusLineOpts,
+            get_hl::GetHlOpts, get_hl_ns::GetHlNsOpts, get_mark::GetMarkOpts,
+            open_term::OpenTermOpts, paste::PastePhase, select_popupmenu_item::SelectPopupMenuOpts,
+            set_hl::SetHlOpts, set_keymap::SetKeymapOpts,
+        },
+        returns::{
+            channel_info::ChannelInfo, color_map::ColorMap, context::Context,
+            eval_statusline::EvalStatusLine, get_hl::HighlightGroups, get_keymap::Keymaps,
+            get_mode::Mode,
+        },
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ns, ret, ret_handle, ret_list, string};
+
+impl Client {
+    pub fn chan_send<S: AsThinString>(&mut self, chan: Channel, bytes: S) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_chan_send",
+            args([Object::Integer(chan.as_int()), string(bytes)]),
+        )
+        .map(drop)
+    }
+
+    pub fn create_buf(&mut self, listed: Boolean, scratch: Boolean) -> Result<Buffer, RpcError> {
+        ret(self.call(
+            c"nvim_create_buf",
+            args([Object::Bool(listed), Object::Bool(scratch)]),
+        )?)
+    }
+
+    pub fn del_current_line(&mut self) -> Result<(), RpcError> {
+        self.call(c"nvim_del_current_line", args([])).map(drop)
+    }
+
+    pub fn del_keymap<S: AsThinString>(
+        &mut self,
+        map_mode: KeyMapMode,
+        lhs: S,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_del_keymap",
+            args([map_mode.to_object()?, string(lhs)]),
+        )
+        .map(drop)
+    }
+
+    pub fn del_mark<S: AsThinString>(&mut self, name: S) -> Result<Boolean, RpcError> {
+        ret(self.call(c"nvim_del_mark", args([string(name)]))?)
+    }
+
+    pub fn del_var<S: AsThinString>(&mut self, var: S) -> Result<(), RpcError> {
+        self.call(c"nvim_del_var", args([string(var)])).map(drop)
+    }
+
+    pub fn echo(
+        &mut self,
+        chunks: &Echo,
+        history: Boolean,
+        opts: &EchoOpts,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_echo",
+            args([
+                chunks.to_object()?,
+                Object::Bool(history),
+                opts.to_object()?,
+            ]),
+        )
+        .map(drop)
+    }
+
+    #[deprecated]
+    pub fn err_write<S: AsThinString>(&mut self, s: S) -> Result<(), RpcError> {
+        self.call(c"nvim_err_write", args([string(s)])).map(drop)
+    }
+
+    #[deprecated]
+    pub fn err_writeln<S: AsThinString>(&mut self, s: S) -> Result<(), RpcError> {
+        self.call(c"nvim_err_writeln", args([string(s)])).map(drop)
+    }
+
+    pub fn eval_statusline<S: AsThinString>(
+        &mut self,
+        s: S,
+        opts: &EvalStatusLineOpts<'_>,
+    ) -> Result<EvalStatusLine, RpcError> {
+        let line = self.call(
+            c"nvim_eval_statusline",
+            args([string(s), opts.to_object()?]),
+        )?;
+        Ok(EvalStatusLine::from_c_func_ret(&mut ret::<Dict>(line)?))
+    }
+
+    pub fn exec_lua<S: AsThinString>(&mut self, code: S, args: &Array) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_exec_lua",
+            self::args([string(code), Object::Array(args.clone())]),
+        )
+    }
+
+    pub fn feedkeys<S: AsThinString>(
+        &mut self,
+        keys: S,
+        mode: &FeedKeysMode,
+        escape_ks: Boolean,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_feedkeys",
+            args([
+                string(keys),
+                string(mode.as_thinstr()),
+                Object::Bool(escape_ks),
+            ]),
+        )
+        .map(drop)
+    }
+
+    /// Gets information about a channel
+    ///
+    /// Unlike [`get_chan_info`](crate::nvim_funcs::global::get_chan_info) the calling channel
+    /// is always the client, so only the channel to look up is passed.
+    pub fn get_chan_info(&mut self, chan: Integer) -> Result<ChannelInfo, RpcError> {
+        let info = self.call(c"nvim_get_chan_info", args([Object::Integer(chan)]))?;
+        Ok(ChannelInfo::from_c_func_ret(&mut ret::<Dict>(info)?))
+    }
+
+    pub fn get_color_by_name<S: AsThinString>(
+        &mut self,
+        name: S,
+    ) -> Result<Option<Integer>, RpcError> {
+        let color = ret::<Integer>(self.call(c"nvim_get_color_by_name", args([string(name)]))?)?;
+        Ok(Some(color).filter(|c| *c != -1))
+    }
+
+    /// Gets the full color map from Neovim
+    ///
+    /// The [`ColorMap`] is shared with [`get_color_map`](crate::nvim_funcs::global::get_color_map)
+    /// so it is only requested once.
+    pub fn get_color_map(&mut self) -> Result<ColorMap, RpcError> {
+        if ColorMap::is_loaded() {
+            return Ok(ColorMap::initialized());
+        }
+        let mut colors = ret::<Dict>(self.call(c"nvim_get_color_map", args([]))?)?;
+        let map = ColorMap::from_c_func_ret(&mut colors);
+        // the map keeps pointing to the color names
+        core::mem::forget(colors);
+        Ok(map)
+    }
+
+    pub fn get_context(&mut self, opts: &ContextOpts) -> Result<Context, RpcError> {
+        let ctx = self.call(c"nvim_get_context", args([opts.to_object()?]))?;
+        Ok(Context::from_c_func_ret(&mut ret::<Dict>(ctx)?))
+    }
+
+    pub fn get_current_buf(&mut self) -> Result<Buffer, RpcError> {
+        ret(self.call(c"nvim_get_current_buf", args([]))?)
+    }
+
+    pub fn get_current_line(&mut self) -> Result<OwnedThinString, RpcError> {
+        ret(self.call(c"nvim_get_current_line", args([]))?)
+    }
+
+    pub fn get_current_tabpage(&mut self) -> Result<TabPage, RpcError> {
+        ret(self.call(c"nvim_get_current_tabpage", args([]))?)
+    }
+
+    pub fn get_current_win(&mut self) -> Result<Window, RpcError> {
+        ret(self.call(c"nvim_get_current_win", args([]))?)
+    }
+
+    pub fn get_hl(
+        &mut self,
+        ns: NameSpace,
+        opts: &GetHlOpts<'_>,
+    ) -> Result<HighlightGroups, RpcError> {
+        let hl = self.call(c"nvim_get_hl", args([self::ns(ns), opts.to_object()?]))?;
+        Ok(HighlightGroups::from_c_func_ret(&ret::<Dict>(hl)?))
+    }
+
+    pub fn get_hl_id_by_name<S: AsThinString>(&mut self, name: S) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_get_hl_id_by_name", args([string(name)]))?)
+    }
+
+    pub fn get_hl_ns(&mut self, opts: &GetHlNsOpts) -> Result<NameSpace, RpcError> {
+        ret_handle(self.call(c"nvim_get_hl_ns", args([opts.to_object()?]))?).map(NameSpace::new)
+    }
+
+    pub fn get_keymap(&mut self, mode: KeyMapMode) -> Result<Keymaps, RpcError> {
+        let maps = self.call(c"nvim_get_keymap", args([mode.to_object()?]))?;
+        Ok(Keymaps::from_c_func_ret(&mut ret::<Array>(maps)?))
+    }
+
+    pub fn get_mark<S: AsThinString>(&mut self, name: S) -> Result<Array, RpcError> {
+        ret(self.call(
+            c"nvim_get_mark",
+            args([string(name), GetMarkOpts::default().to_object()?]),
+        )?)
+    }
+
+    pub fn get_mode(&mut self) -> Result<Mode, RpcError> {
+        let mode = self.call(c"nvim_get_mode", args([]))?;
+        Ok(Mode::from_c_func_ret(&mut ret::<Dict>(mode)?))
+    }
+
+    pub fn get_proc(&mut self, pid: Integer) -> Result<Option<Dict>, RpcError> {
+        Ok(self
+            .call(c"nvim_get_proc", args([Object::Integer(pid)]))?
+            .into_dict())
+    }
+
+    pub fn get_proc_children(&mut self, pid: Integer) -> Result<Array, RpcError> {
+        ret(self.call(c"nvim_get_proc_children", args([Object::Integer(pid)]))?)
+    }
+
+    pub fn get_runtime_file<S: AsThinString>(
+        &mut self,
+        name: S,
+        all: Boolean,
+    ) -> Result<Array, RpcError> {
+        ret(self.call(
+            c"nvim_get_runtime_file",
+            args([string(name), Object::Bool(all)]),
+        )?)
+    }
+
+    pub fn get_var<S: AsThinString>(&mut self, name: S) -> Result<Object, RpcError> {
+        self.call(c"nvim_get_var", args([string(name)]))
+    }
+
+    pub fn get_vvar<S: AsThinString>(&mut self, name: S) -> Result<Object, RpcError> {
+        self.call(c"nvim_get_vvar", args([string(name)]))
+    }
+
+    pub fn input<S: AsThinString>(&mut self, keys: S) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_input", args([string(keys)]))?)
+    }
+
+    pub fn input_mouse<S: AsThinString, S1: AsThinString, S2: AsThinString>(
+        &mut self,
+        button: S,
+        action: S1,
+        modifier: S2,
+        grid: Integer,
+        row: Integer,
+        col: Integer,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_input_mouse",
+            args([
+                string(button),
+                string(action),
+                string(modifier),
+                Object::Integer(grid),
+                Object::Integer(row),
+                Object::Integer(col),
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn list_bufs(&mut self) -> Result<KVec<Buffer>, RpcError> {
+        ret_list(self.call(c"nvim_list_bufs", args([]))?)
+    }
+
+    pub fn list_chans(&mut self) -> Result<KVec<Channel>, RpcError> {
+        let chans = ret::<Array>(self.call(c"nvim_list_chans", args([]))?)?;
+        chans
+            .iter()
+            .map(|chan| {
+                chan.as_dict()
+                    .and_then(|chan| chan.get("id"))
+                    .and_then(Object::as_int)
+                    .map(Channel::new)
+                    .ok_or(RpcError::Protocol("expected a channel"))
+            })
+            .collect()
+    }
+
+    pub fn list_runtime_paths(&mut self) -> Result<Array, RpcError> {
+        ret(self.call(c"nvim_list_runtime_paths", args([]))?)
+    }
+
+    pub fn list_tabpages(&mut self) -> Result<KVec<TabPage>, RpcError> {
+        ret_list(self.call(c"nvim_list_tabpages", args([]))?)
+    }
+
+    pub fn list_uis(&mut self) -> Result<Array, RpcError> {
+        ret(self.call(c"nvim_list_uis", args([]))?)
+    }
+
+    pub fn list_wins(&mut self) -> Result<KVec<Window>, RpcError> {
+        ret_list(self.call(c"nvim_list_wins", args([]))?)
+    }
+
+    pub fn open_term(&mut self, buf: Buffer, opts: &OpenTermOpts) -> Result<Channel, RpcError> {
+        ret(self.call(
+            c"nvim_open_term",
+            args([Object::Buffer(buf), opts.to_object()?]),
+        )?)
+        .map(Channel::new)
+    }
+
+    pub fn paste<S: AsThinString>(
+        &mut self,
+        src: S,
+        crlf: Boolean,
+        phase: PastePhase,
+    ) -> Result<Boolean, RpcError> {
+        ret(self.call(
+            c"nvim_paste",
+            args([string(src), Object::Bool(crlf), phase.to_object()?]),
+        )?)
+    }
+
+    pub fn put<S: AsThinString>(
+        &mut self,
+        arr: &Array,
+        r#type: S,
+        after: Boolean,
+        follow: Boolean,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_put",
+            args([
+                Object::Array(arr.clone()),
+                string(r#type),
+                Object::Bool(after),
+                Object::Bool(follow),
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn replace_termcodes<S: AsThinString>(
+        &mut self,
+        s: S,
+        from_part: Boolean,
+        do_lt: Boolean,
+        special: Boolean,
+    ) -> Result<OwnedThinString, RpcError> {
+        ret(self.call(
+            c"nvim_replace_termcodes",
+            args([
+                string(s),
+                Object::Bool(from_part),
+                Object::Bool(do_lt),
+                Object::Bool(special),
+            ]),
+        )?)
+    }
+
+    pub fn select_popupmenu_item(
+        &mut self,
+        item: Integer,
+        insert: Boolean,
+        finish: Boolean,
+        opts: &SelectPopupMenuOpts,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_select_popupmenu_item",
+            args([
+                Object::Integer(item),
+                Object::Bool(insert),
+                Object::Bool(finish),
+                opts.to_object()?,
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn set_current_buf(&mut self, buf: Buffer) -> Result<(), RpcError> {
+        self.call(c"nvim_set_current_buf", args([Object::Buffer(buf)]))
+            .map(drop)
+    }
+
+    pub fn set_current_dir<S: AsThinString>(&mut self, dir: S) -> Result<(), RpcError> {
+        self.call(c"nvim_set_current_dir", args([string(dir)]))
+            .map(drop)
+    }
+
+    pub fn set_current_line<S: AsThinString>(&mut self, line: S) -> Result<(), RpcError> {
+        self.call(c"nvim_set_current_line", args([string(line)]))
+            .map(drop)
+    }
+
+    pub fn set_current_tabpage(&mut self, page: TabPage) -> Result<(), RpcError> {
+        self.call(c"nvim_set_current_tabpage", args([Object::TabPage(page)]))
+            .map(drop)
+    }
+
+    pub fn set_current_win(&mut self, win: Window) -> Result<(), RpcError> {
+        self.call(c"nvim_set_current_win", args([Object::Window(win)]))
+            .map(drop)
+    }
+
+    pub fn set_hl<S: AsThinString>(
+        &mut self,
+        ns: NameSpace,
+        name: S,
+        opts: &SetHlOpts,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_set_hl",
+            args([self::ns(ns), string(name), opts.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    pub fn set_hl_ns(&mut self, ns: NameSpace) -> Result<(), RpcError> {
+        self.call(c"nvim_set_hl_ns", args([self::ns(ns)])).map(drop)
+    }
+
+    pub fn set_hl_ns_fast(&mut self, ns: NameSpace) -> Result<(), RpcError> {
+        self.call(c"nvim_set_hl_ns_fast", args([self::ns(ns)]))
+            .map(drop)
+    }
+
+    pub fn set_keymap<S: AsThinString, S1: AsThinString>(
+        &mut self,
+        mode: KeyMapMode,
+        lhs: S,
+        rhs: S1,
+        opts: &SetKeymapOpts<'_>,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_set_keymap",
+            args([
+                mode.to_object()?,
+                string(lhs),
+                string(rhs),
+                opts.to_object()?,
+            ]),
+        )
+        .map(drop)
+    }
+
+    pub fn set_var<S: AsThinString>(&mut self, s: S, obj: &Object) -> Result<(), RpcError> {
+        self.call(c"nvim_set_var", args([string(s), obj.to_object()?]))
+            .map(drop)
+    }
+
+    pub fn set_vvar<S: AsThinString>(&mut self, s: S, obj: &Object) -> Result<(), RpcError> {
+        self.call(c"nvim_set_vvar", args([string(s), obj.to_object()?]))
+            .map(drop)
+    }
+
+    pub fn strwidth<S: AsThinString>(&mut self, s: S) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_strwidth", args([string(s)]))?)
+    }
+}
@@ -0,0 +1,184 @@
+//! Typed wrappers for the API functions
+//!
+//! Every function in [`nvim_funcs`](crate::nvim_funcs) has a method of the same name and
+//! arguments, grouped in the same modules. The differences are:
+//!
+//! - Functions that hand out borrowed lines such as
+//!   [`buf_get_lines`](crate::nvim_funcs::buffer::buf_get_lines) return the lines instead of
+//!   passing them to a closure.
+//! - Options are only read, so they are taken by shared reference.
+//! - Rust callbacks only exist inside of Neovim. Setting one on the options passed to a method,
+//!   such as [`CreateAutocmdOpts::callback`], fails with [`MsgpackError::LuaRef`]. `buf_call`,
+//!   `win_call` and `set_decoration_provider` only accept callbacks so they are not provided, use
+//!   [`Client::exec_lua`] instead.
+//! - Buffer updates from [`Client::buf_attach`] are sent as notifications such as
+//!   `nvim_buf_lines_event`, see `:h api-buffer-updates`.
+//!
+//! Any other function can be called with [`Client::call`].
+//!
+//! [`CreateAutocmdOpts::callback`]: crate::nvim_types::opts::create_autocmd::CreateAutocmdOpts::callback
+//! [`MsgpackError::LuaRef`]: crate::nvim_types::msgpack::MsgpackError::LuaRef
+
+mod autocmd;
+mod buffer;
+mod command;
+mod extmark;
+mod global;
+mod options;
+mod tabpage;
+mod vimscript;
+mod win_config;
+mod window;
+
+use crate::nvim_types::{
+    Array, AsThinString, HandleT, Integer, KVec, NameSpace, Object, OwnedThinString,
+};
+
+use super::RpcError;
+
+fn string<S: AsThinString>(s: S) -> Object {
+    Object::String(OwnedThinString::from(s.as_thinstr()))
+}
+
+fn strings<S: AsThinString>(s: &[S]) -> Object {
+    Object::Array(Array::from(KVec::from_iter(
+        s.iter().map(|s| string(s.as_thinstr())),
+    )))
+}
+
+fn args<const N: usize>(args: [Object; N]) -> Array {
+    Array::from(KVec::from_iter(args))
+}
+
+fn ret<T: TryFrom<Object>>(obj: Object) -> Result<T, RpcError> {
+    T::try_from(obj).map_err(|_| RpcError::Protocol("unexpected return type"))
+}
+
+fn ret_list<T: TryFrom<Object>>(obj: Object) -> Result<KVec<T>, RpcError> {
+    ret::<Array>(obj)?
+        .into_kvec()
+        .into_iter()
+        .map(ret)
+        .collect()
+}
+
+fn ret_pos(obj: Object) -> Result<(Integer, Integer), RpcError> {
+    match ret_list::<Integer>(obj)?.as_slice() {
+        [row, col] => Ok((*row, *col)),
+        _ => Err(RpcError::Protocol("expected a position")),
+    }
+}
+
+fn ns(ns: NameSpace) -> Object {
+    Object::Integer(ns.as_int().into())
+}
+
+/// Reads the id of a namespace, extmark or similar handle
+fn ret_handle(obj: Object) -> Result<HandleT, RpcError> {
+    HandleT::try_from(ret::<Integer>(obj)?).map_err(|_| RpcError::Protocol("invalid handle"))
+}
+
+#[cfg(all(test, not(miri), feature = "testing"))]
+mod tests {
+    use crate::{
+        array,
+        nvim_types::{
+            Buffer, Object, OwnedThinString,
+            opts::{
+                exec::ExecOpts,
+                get_extmark::GetExtmarkOpts,
+                option::{OptionOpt, OptionScope},
+                set_extmark::SetExtmarkOpts,
+                win_opts::{Relative, WinConfig},
+            },
+            returns::cmd::CmdRange,
+        },
+        rpc::{Client, RpcError},
+    };
+
+    /// Spawns `nvim --embed`, like the other `testing` tests this requires Neovim to be installed
+    fn embed() -> Client {
+        Client::embed(["--clean", "-n"]).expect("failed to spawn nvim --embed")
+    }
+
+    #[test]
+    fn embedded() {
+        let mut nvim = embed();
+
+        let buf = nvim.get_current_buf().unwrap();
+        nvim.buf_set_lines(buf, 0, -1, true, &array!["one", "two"])
+            .unwrap();
+        assert_eq!(nvim.buf_line_count(Buffer::new(0)).unwrap(), 2);
+        let lines = nvim.buf_get_lines(buf, 0, -1, true).unwrap();
+        assert_eq!(lines.as_slice(), ["one", "two"]);
+
+        let mut opts = ExecOpts::default();
+        opts.output(true);
+        let out = nvim.exec2(c"echo 1 + 1", &opts).unwrap();
+        assert_eq!(out.output.unwrap(), "2");
+        assert_eq!(nvim.eval(c"1 + 1").unwrap(), Object::Integer(2));
+        assert!(matches!(
+            nvim.command(c"notacommand"),
+            Err(RpcError::Nvim(_))
+        ));
+
+        let mut opts = OptionOpt::default();
+        opts.scope(OptionScope::Global);
+        nvim.set_option_value(c"shiftwidth", Object::Integer(7), &opts)
+            .unwrap();
+        assert_eq!(
+            nvim.get_option_value(c"shiftwidth", &opts).unwrap(),
+            Object::Integer(7)
+        );
+
+        let scratch = nvim.create_buf(false, true).unwrap();
+        let mut config = WinConfig::default();
+        config
+            .relative(Relative::Editor)
+            .row(1.)
+            .col(1.)
+            .width(10)
+            .height(2);
+        let win = nvim.open_win(scratch, false, &config).unwrap();
+        assert_eq!(nvim.win_get_buf(win).unwrap(), scratch);
+        assert_eq!(nvim.win_get_width(win).unwrap(), 10);
+        let info = nvim.win_get_config(win).unwrap();
+        assert!(matches!(info.relative, Some(Relative::Editor)));
+        assert_eq!(info.width, Some(10));
+        nvim.win_close(win, true).unwrap();
+        assert!(!nvim.win_is_valid(win).unwrap());
+
+        let cmd = nvim.parse_cmd(c"2,3delete").unwrap();
+        assert_eq!(cmd.cmd, "delete");
+        assert!(matches!(cmd.range, Some(CmdRange::Range(2, 3))));
+
+        let ns = nvim.create_namespace(c"nvimium").unwrap();
+        let mark = nvim
+            .buf_set_extmark(buf, ns, 1, 0, &SetExtmarkOpts::default())
+            .unwrap();
+        let info = nvim
+            .buf_get_extmark_by_id(buf, ns, mark, &GetExtmarkOpts::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!((info.row, info.col), (1, 0));
+        assert!(
+            nvim.get_namespaces()
+                .unwrap()
+                .iter()
+                .any(|(name, id)| name == "nvimium" && id.as_int() == ns.as_int())
+        );
+
+        // channel 0 broadcasts to every channel without subscribing
+        nvim.command(c"call rpcnotify(0, 'nvimium', 1, 'two')")
+            .unwrap();
+        let notification = nvim.next_notification().unwrap();
+        assert_eq!(notification.method, "nvimium");
+        assert_eq!(
+            notification.args.as_slice(),
+            [
+                Object::Integer(1),
+                Object::String(OwnedThinString::from("two"))
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    nvim_types::{
+        AsThinString, Dict, Object,
+        opts::option::OptionOpt,
+        returns::options_info::{OptionInfo, OptionsInfo},
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret, string};
+
+impl Client {
+    pub fn get_all_options(&mut self) -> Result<OptionsInfo, RpcError> {
+        let options = self.call(c"nvim_get_all_options_info", args([]))?;
+        Ok(OptionsInfo::from_c_func_ret(&mut ret::<Dict>(options)?))
+    }
+
+    pub fn get_options_info2<TH: AsThinString>(
+        &mut self,
+        name: TH,
+        opts: &OptionOpt<'_>,
+    ) -> Result<OptionInfo, RpcError> {
+        let info = self.call(
+            c"nvim_get_option_info2",
+            args([string(name), opts.to_object()?]),
+        )?;
+        Ok(OptionInfo::from_c_func_ret(&mut ret::<Dict>(info)?))
+    }
+
+    pub fn get_option_value<TH: AsThinString>(
+        &mut self,
+        name: TH,
+        opts: &OptionOpt<'_>,
+    ) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_get_option_value",
+            args([string(name), opts.to_object()?]),
+        )
+    }
+
+    pub fn set_option_value<TH: AsThinString>(
+        &mut self,
+        name: TH,
+        value: Object,
+        opts: &OptionOpt<'_>,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_set_option_value",
+            args([string(name), value.to_object()?, opts.to_object()?]),
+        )
+        .map(drop)
+    }
+}
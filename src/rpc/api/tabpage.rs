@@ -0,0 +1,68 @@
+use crate::{
+    nvim_types::{AsThinString, Boolean, Integer, KVec, Object, TabPage, Window},
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret, ret_list, string};
+
+impl Client {
+    pub fn tabpage_del_var<TH: AsThinString>(
+        &mut self,
+        tp: TabPage,
+        name: TH,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_tabpage_del_var",
+            args([Object::TabPage(tp), string(name)]),
+        )
+        .map(drop)
+    }
+
+    pub fn tabpage_get_number(&mut self, tp: TabPage) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_tabpage_get_number", args([Object::TabPage(tp)]))?)
+    }
+
+    pub fn tabpage_get_var<TH: AsThinString>(
+        &mut self,
+        tp: TabPage,
+        name: TH,
+    ) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_tabpage_get_var",
+            args([Object::TabPage(tp), string(name)]),
+        )
+    }
+
+    pub fn tabpage_get_win(&mut self, tp: TabPage) -> Result<Window, RpcError> {
+        ret(self.call(c"nvim_tabpage_get_win", args([Object::TabPage(tp)]))?)
+    }
+
+    pub fn tabpage_is_valid(&mut self, tp: TabPage) -> Result<Boolean, RpcError> {
+        ret(self.call(c"nvim_tabpage_is_valid", args([Object::TabPage(tp)]))?)
+    }
+
+    pub fn tabpage_list_wins(&mut self, tp: TabPage) -> Result<KVec<Window>, RpcError> {
+        ret_list(self.call(c"nvim_tabpage_list_wins", args([Object::TabPage(tp)]))?)
+    }
+
+    pub fn tabpage_set_var<TH: AsThinString>(
+        &mut self,
+        tp: TabPage,
+        name: TH,
+        value: &Object,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_tabpage_set_var",
+            args([Object::TabPage(tp), string(name), value.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    pub fn tabpage_set_win(&mut self, tp: TabPage, win: Window) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_tabpage_set_win",
+            args([Object::TabPage(tp), Object::Window(win)]),
+        )
+        .map(drop)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{
+    nvim_types::{
+        Array, AsThinString, Boolean, Dict, Object,
+        opts::exec::ExecOpts,
+        returns::{exec2::Exec2, parse_expression::ParsedExpr},
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret, string};
+
+impl Client {
+    pub fn call_dict_function<S1: AsThinString, S2: AsThinString>(
+        &mut self,
+        dict: S1,
+        func: S2,
+        args: &Array,
+    ) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_call_dict_function",
+            self::args([string(dict), string(func), Object::Array(args.clone())]),
+        )
+    }
+
+    pub fn call_function<S: AsThinString>(
+        &mut self,
+        func: S,
+        args: &Array,
+    ) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_call_function",
+            self::args([string(func), Object::Array(args.clone())]),
+        )
+    }
+
+    pub fn command<S: AsThinString>(&mut self, command: S) -> Result<(), RpcError> {
+        self.call(c"nvim_command", args([string(command)]))
+            .map(drop)
+    }
+
+    pub fn eval<S: AsThinString>(&mut self, eval: S) -> Result<Object, RpcError> {
+        self.call(c"nvim_eval", args([string(eval)]))
+    }
+
+    pub fn exec2<S: AsThinString>(&mut self, exec: S, opts: &ExecOpts) -> Result<Exec2, RpcError> {
+        let out = self.call(c"nvim_exec2", args([string(exec), opts.to_object()?]))?;
+        Ok(Exec2::from_c_func_ret(&mut ret::<Dict>(out)?))
+    }
+
+    pub fn parse_expression<S: AsThinString, S1: AsThinString>(
+        &mut self,
+        expr: S,
+        flags: S1,
+        highlight: Boolean,
+    ) -> Result<ParsedExpr, RpcError> {
+        let parsed = self.call(
+            c"nvim_parse_expression",
+            args([string(expr), string(flags), Object::Bool(highlight)]),
+        )?;
+        Ok(ParsedExpr::from_c_func_ret(&ret::<Dict>(parsed)?))
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{
+    nvim_types::{Boolean, Buffer, Object, Window, opts::win_opts::WinConfig},
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ret};
+
+impl Client {
+    pub fn open_win(
+        &mut self,
+        buf: Buffer,
+        enter: Boolean,
+        config: &WinConfig,
+    ) -> Result<Window, RpcError> {
+        ret(self.call(
+            c"nvim_open_win",
+            args([
+                Object::Buffer(buf),
+                Object::Bool(enter),
+                config.to_object()?,
+            ]),
+        )?)
+    }
+}
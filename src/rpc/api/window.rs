@@ -0,0 +1,170 @@
+use crate::{
+    nvim_types::{
+        AsThinString, Boolean, Buffer, Dict, Integer, NameSpace, Object, TabPage, Window,
+        opts::{win_opts::WinConfig, win_text_height::WinTextHeightOpts},
+        returns::win_config::{TextHeight, WinConfigInfo},
+    },
+    rpc::{Client, RpcArg, RpcError},
+};
+
+use super::{args, ns, ret, ret_pos, string};
+
+impl Client {
+    /// Closes the window
+    ///
+    /// If `force` is true, the window is closed even if the buffer has unsaved changes (the buffer
+    /// becomes hidden).
+    pub fn win_close(&mut self, win: Window, force: Boolean) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_close",
+            args([Object::Window(win), Object::Bool(force)]),
+        )
+        .map(drop)
+    }
+
+    pub fn win_del_var<TH: AsThinString>(&mut self, win: Window, name: TH) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_del_var",
+            args([Object::Window(win), string(name)]),
+        )
+        .map(drop)
+    }
+
+    pub fn win_get_buf(&mut self, win: Window) -> Result<Buffer, RpcError> {
+        ret(self.call(c"nvim_win_get_buf", args([Object::Window(win)]))?)
+    }
+
+    /// Gets the configuration of a window
+    ///
+    /// The returned value can be converted in to a [`WinConfig`] and passed to
+    /// [`win_set_config`](Client::win_set_config).
+    pub fn win_get_config(&mut self, win: Window) -> Result<WinConfigInfo, RpcError> {
+        let config = self.call(c"nvim_win_get_config", args([Object::Window(win)]))?;
+        Ok(WinConfigInfo::from_dict(&ret::<Dict>(config)?))
+    }
+
+    /// Gets the (1,0)-indexed cursor position of the window
+    pub fn win_get_cursor(&mut self, win: Window) -> Result<(Integer, Integer), RpcError> {
+        ret_pos(self.call(c"nvim_win_get_cursor", args([Object::Window(win)]))?)
+    }
+
+    pub fn win_get_height(&mut self, win: Window) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_win_get_height", args([Object::Window(win)]))?)
+    }
+
+    /// Gets the window number as seen by `winnr()`
+    pub fn win_get_number(&mut self, win: Window) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_win_get_number", args([Object::Window(win)]))?)
+    }
+
+    /// Gets the (0,0)-indexed position of the window in display cells
+    pub fn win_get_position(&mut self, win: Window) -> Result<(Integer, Integer), RpcError> {
+        ret_pos(self.call(c"nvim_win_get_position", args([Object::Window(win)]))?)
+    }
+
+    pub fn win_get_tabpage(&mut self, win: Window) -> Result<TabPage, RpcError> {
+        ret(self.call(c"nvim_win_get_tabpage", args([Object::Window(win)]))?)
+    }
+
+    pub fn win_get_var<TH: AsThinString>(
+        &mut self,
+        win: Window,
+        name: TH,
+    ) -> Result<Object, RpcError> {
+        self.call(
+            c"nvim_win_get_var",
+            args([Object::Window(win), string(name)]),
+        )
+    }
+
+    pub fn win_get_width(&mut self, win: Window) -> Result<Integer, RpcError> {
+        ret(self.call(c"nvim_win_get_width", args([Object::Window(win)]))?)
+    }
+
+    /// Closes the window and hides the buffer it contains
+    pub fn win_hide(&mut self, win: Window) -> Result<(), RpcError> {
+        self.call(c"nvim_win_hide", args([Object::Window(win)]))
+            .map(drop)
+    }
+
+    pub fn win_is_valid(&mut self, win: Window) -> Result<Boolean, RpcError> {
+        ret(self.call(c"nvim_win_is_valid", args([Object::Window(win)]))?)
+    }
+
+    pub fn win_set_buf(&mut self, win: Window, buf: Buffer) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_set_buf",
+            args([Object::Window(win), Object::Buffer(buf)]),
+        )
+        .map(drop)
+    }
+
+    pub fn win_set_config(&mut self, win: Window, config: &WinConfig) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_set_config",
+            args([Object::Window(win), config.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    /// Sets the (1,0)-indexed cursor position of the window
+    pub fn win_set_cursor(&mut self, win: Window, pos: (Integer, Integer)) -> Result<(), RpcError> {
+        let pos = args([Object::Integer(pos.0), Object::Integer(pos.1)]);
+        self.call(
+            c"nvim_win_set_cursor",
+            args([Object::Window(win), Object::Array(pos)]),
+        )
+        .map(drop)
+    }
+
+    pub fn win_set_height(&mut self, win: Window, height: Integer) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_set_height",
+            args([Object::Window(win), Object::Integer(height)]),
+        )
+        .map(drop)
+    }
+
+    /// Sets the highlight namespace for the window
+    pub fn win_set_hl_ns(&mut self, win: Window, ns_id: NameSpace) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_set_hl_ns",
+            args([Object::Window(win), ns(ns_id)]),
+        )
+        .map(drop)
+    }
+
+    pub fn win_set_var<TH: AsThinString>(
+        &mut self,
+        win: Window,
+        name: TH,
+        value: &Object,
+    ) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_set_var",
+            args([Object::Window(win), string(name), value.to_object()?]),
+        )
+        .map(drop)
+    }
+
+    pub fn win_set_width(&mut self, win: Window, width: Integer) -> Result<(), RpcError> {
+        self.call(
+            c"nvim_win_set_width",
+            args([Object::Window(win), Object::Integer(width)]),
+        )
+        .map(drop)
+    }
+
+    /// Computes the number of screen lines occupied by a range of text in the window
+    pub fn win_text_height(
+        &mut self,
+        win: Window,
+        opts: &WinTextHeightOpts,
+    ) -> Result<TextHeight, RpcError> {
+        let height = self.call(
+            c"nvim_win_text_height",
+            args([Object::Window(win), opts.to_object()?]),
+        )?;
+        Ok(TextHeight::from_c_func_ret(&mut ret::<Dict>(height)?))
+    }
+}
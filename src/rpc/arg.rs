@@ -0,0 +1,103 @@
+use crate::nvim_types::{
+    Array, Boolean, Buffer, Float, HlGroupId, Integer, LuaRef, Object, OwnedThinString, ThinString,
+    Window,
+    borrowed::Borrowed,
+    msgpack::MsgpackError,
+    object::ObjectRef,
+    object_subs::{BoolOrInteger, StringOrInt},
+};
+
+use super::RpcError;
+
+/// Converts a value stored in an options struct to the object sent over RPC
+///
+/// Structs created with `masked_builder!` implement this by converting every field that is set
+/// in to a [`Dict`](crate::nvim_types::Dict) keyed by the field names Neovim expects.
+pub(crate) trait RpcArg {
+    fn to_object(&self) -> Result<Object, RpcError>;
+}
+
+macro_rules! copied {
+    ($($ty:ty => $variant:ident),+ $(,)?) => {
+        $(
+            impl RpcArg for $ty {
+                fn to_object(&self) -> Result<Object, RpcError> {
+                    Ok(Object::$variant(*self))
+                }
+            }
+        )+
+    };
+}
+
+copied!(
+    Boolean => Bool,
+    Integer => Integer,
+    Float => Float,
+    Buffer => Buffer,
+    Window => Window,
+);
+
+impl RpcArg for ThinString<'_> {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        Ok(Object::String(OwnedThinString::from(*self)))
+    }
+}
+
+impl RpcArg for OwnedThinString {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        Ok(Object::String(self.clone()))
+    }
+}
+
+impl RpcArg for HlGroupId {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        Ok(Object::Integer(self.as_int()))
+    }
+}
+
+// callbacks only exist inside of Neovim so they can not be sent to it
+impl RpcArg for LuaRef {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        Err(RpcError::Msgpack(MsgpackError::LuaRef))
+    }
+}
+
+impl RpcArg for Object {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        match self {
+            // cloning a LuaRef requires access to the Lua state
+            Object::LuaRef(_) => Err(RpcError::Msgpack(MsgpackError::LuaRef)),
+            obj => Ok(obj.clone()),
+        }
+    }
+}
+
+impl RpcArg for Array {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        Ok(Object::Array(self.clone()))
+    }
+}
+
+impl RpcArg for Borrowed<'_, Array> {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        self.as_ref().to_object()
+    }
+}
+
+impl RpcArg for ObjectRef<'_> {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        self.as_object().to_object()
+    }
+}
+
+impl RpcArg for StringOrInt {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        self.as_ref().to_object()
+    }
+}
+
+impl RpcArg for BoolOrInteger {
+    fn to_object(&self) -> Result<Object, RpcError> {
+        self.as_ref().to_object()
+    }
+}
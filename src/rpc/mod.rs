@@ -0,0 +1,542 @@
+//! A msgpack-RPC client for Neovim
+//!
+//! The functions in [`nvim_funcs`](crate::nvim_funcs) call into Neovim directly and can only be
+//! used from a plugin loaded by Neovim. [`Client`] provides the same API to other processes by
+//! talking to an embedded Neovim instance or to a socket created with `--listen` or
+//! `serverstart()`.
+//!
+//! The client is blocking and single threaded. Requests sent by Neovim through `rpcrequest()`
+//! are answered with an error and notifications are queued until they are read with
+//! [`Client::next_notification`] or [`Client::try_notification`].
+//!
+//! # Example
+//! ```no_run
+//! use nvimium::{nvim_types::Buffer, rpc::{Client, RpcError}};
+//!
+//! fn lines() -> Result<(), RpcError> {
+//!     let mut nvim = Client::embed(["--clean"])?;
+//!     nvim.command(c"edit Cargo.toml")?;
+//!     let lines = nvim.buf_get_lines(Buffer::new(0), 0, -1, true)?;
+//!     println!("{} lines", lines.len());
+//!     Ok(())
+//! }
+//! ```
+
+mod api;
+mod arg;
+
+use std::{
+    collections::VecDeque,
+    ffi::OsStr,
+    fmt::Display,
+    io::{self, BufWriter, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    process::{Child, Command, Stdio},
+};
+
+use crate::nvim_types::{
+    Array, AsThinString, Error, Object, OwnedThinString,
+    msgpack::{self, DecodeLimits, MsgpackError},
+};
+
+pub(crate) use arg::RpcArg;
+
+/// Neovim is trusted so messages of any size are accepted
+const LIMITS: DecodeLimits = DecodeLimits {
+    max_depth: 128,
+    max_size: usize::MAX,
+};
+
+/// The most a single read of a pipe returns on Linux
+const READ_SIZE: usize = 64 * 1024;
+
+const REQUEST: i64 = 0;
+const RESPONSE: i64 = 1;
+const NOTIFICATION: i64 = 2;
+
+/// An error returned by a [`Client`]
+#[derive(Debug)]
+pub enum RpcError {
+    /// Reading from or writing to the connection failed
+    Io(io::Error),
+    /// A message could not be encoded or decoded
+    Msgpack(MsgpackError),
+    /// A message or return value does not have the expected shape
+    Protocol(&'static str),
+    /// Neovim returned an error for the request
+    Nvim(Error),
+    /// The connection was closed
+    Closed,
+}
+
+impl Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Msgpack(err) => write!(f, "{}", err),
+            Self::Protocol(reason) => write!(f, "unexpected message: {}", reason),
+            Self::Nvim(err) => write!(f, "{}", err),
+            Self::Closed => f.write_str("the connection to neovim was closed"),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<io::Error> for RpcError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<MsgpackError> for RpcError {
+    fn from(value: MsgpackError) -> Self {
+        Self::Msgpack(value)
+    }
+}
+
+/// A notification sent by Neovim with `rpcnotify()` or by an event such as `nvim_buf_attach`
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub method: OwnedThinString,
+    pub args: Array,
+}
+
+/// A connection to a Neovim instance
+///
+/// See the [module documentation](self) for details.
+pub struct Client {
+    reader: Box<dyn Read + Send>,
+    writer: BufWriter<Box<dyn Write + Send>>,
+    /// Bytes read from the connection that are not yet decoded
+    buf: Vec<u8>,
+    framer: Framer,
+    next_id: u32,
+    notifications: VecDeque<Notification>,
+    child: Option<Child>,
+}
+
+impl Client {
+    /// Spawns `nvim --embed --headless` with the extra arguments and connects to it
+    pub fn embed<I, S>(args: I) -> Result<Self, RpcError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        Self::spawn(
+            Command::new("nvim")
+                .args(["--embed", "--headless"])
+                .args(args),
+        )
+    }
+
+    /// Spawns the command and connects to its standard input and output
+    ///
+    /// The command is expected to start Neovim with `--embed`. The process is killed once the
+    /// client is dropped.
+    pub fn spawn(cmd: &mut Command) -> Result<Self, RpcError> {
+        let mut child = cmd.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            unreachable!("stdin and stdout are always piped");
+        };
+        let mut client = Self::from_stream(stdout, stdin);
+        client.child = Some(child);
+        Ok(client)
+    }
+
+    /// Connects to a Unix domain socket or a named pipe such as the one in `v:servername`
+    #[cfg(unix)]
+    pub fn connect_unix<P: AsRef<std::path::Path>>(path: P) -> Result<Self, RpcError> {
+        let stream = std::os::unix::net::UnixStream::connect(path)?;
+        Ok(Self::from_stream(stream.try_clone()?, stream))
+    }
+
+    /// Connects to an address passed to `--listen`
+    pub fn connect_tcp<A: ToSocketAddrs>(addr: A) -> Result<Self, RpcError> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::from_stream(stream.try_clone()?, stream))
+    }
+
+    /// Uses an existing connection
+    pub fn from_stream<R, W>(reader: R, writer: W) -> Self
+    where
+        R: 'static + Read + Send,
+        W: 'static + Write + Send,
+    {
+        Self {
+            reader: Box::new(reader),
+            writer: BufWriter::new(Box::new(writer)),
+            buf: Vec::new(),
+            framer: Framer::default(),
+            next_id: 0,
+            notifications: VecDeque::new(),
+            child: None,
+        }
+    }
+
+    /// Calls an API function and waits for its result
+    ///
+    /// Notifications received while waiting are queued.
+    pub fn call<S: AsThinString>(&mut self, method: S, args: Array) -> Result<Object, RpcError> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.send(&[
+            Object::Integer(REQUEST),
+            Object::Integer(id.into()),
+            Object::String(OwnedThinString::from(method.as_thinstr())),
+            Object::Array(args),
+        ])?;
+
+        loop {
+            let mut msg = self.read_message()?;
+            if msg.first().and_then(Object::as_int) != Some(RESPONSE) {
+                self.handle_message(msg)?;
+                continue;
+            }
+            if msg.len() != 4 {
+                return Err(RpcError::Protocol("a response must have 4 elements"));
+            }
+            if msg[1].as_int() != Some(id.into()) {
+                // a response to a request that is no longer waited on
+                continue;
+            }
+
+            let result = core::mem::take(&mut msg[3]);
+            return match core::mem::take(&mut msg[2]) {
+                Object::Null => Ok(result),
+                err => Err(RpcError::Nvim(nvim_error(err))),
+            };
+        }
+    }
+
+    /// Sends a notification, Neovim does not reply to it even if an error occurs
+    pub fn notify<S: AsThinString>(&mut self, method: S, args: Array) -> Result<(), RpcError> {
+        self.send(&[
+            Object::Integer(NOTIFICATION),
+            Object::String(OwnedThinString::from(method.as_thinstr())),
+            Object::Array(args),
+        ])
+    }
+
+    /// Returns the next notification, waiting for one if none are queued
+    pub fn next_notification(&mut self) -> Result<Notification, RpcError> {
+        loop {
+            if let Some(notification) = self.notifications.pop_front() {
+                return Ok(notification);
+            }
+            let msg = self.read_message()?;
+            self.handle_message(msg)?;
+        }
+    }
+
+    /// Returns a queued notification without waiting for one
+    pub fn try_notification(&mut self) -> Option<Notification> {
+        self.notifications.pop_front()
+    }
+
+    fn send(&mut self, msg: &[Object]) -> Result<(), RpcError> {
+        let mut bytes = Vec::new();
+        Object::Array(Array::from(msg)).to_msgpack(&mut bytes)?;
+        self.writer.write_all(&bytes)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn read_message(&mut self) -> Result<Array, RpcError> {
+        loop {
+            if let Some(end) = self.framer.advance(&self.buf) {
+                self.framer = Framer::default();
+                let (obj, _) = msgpack::decode_with_limits(&self.buf[..end], &LIMITS)?;
+                self.buf.drain(..end);
+                return obj
+                    .into_array()
+                    .ok_or(RpcError::Protocol("a message must be an array"));
+            }
+
+            let len = self.buf.len();
+            self.buf.resize(len + READ_SIZE, 0);
+            let read = loop {
+                match self.reader.read(&mut self.buf[len..]) {
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    res => break res,
+                }
+            };
+            self.buf.truncate(len + *read.as_ref().unwrap_or(&0));
+            match read? {
+                0 => return Err(RpcError::Closed),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Handles a request or notification from Neovim
+    fn handle_message(&mut self, msg: Array) -> Result<(), RpcError> {
+        let mut msg = msg.into_kvec();
+        match msg.first().and_then(Object::as_int) {
+            Some(REQUEST) if msg.len() == 4 => {
+                let err = Object::Array(Array::from(
+                    [
+                        Object::Integer(0),
+                        Object::String(OwnedThinString::from("requests are not supported")),
+                    ]
+                    .as_slice(),
+                ));
+                self.send(&[
+                    Object::Integer(RESPONSE),
+                    core::mem::take(&mut msg[1]),
+                    err,
+                    Object::Null,
+                ])
+            }
+            Some(NOTIFICATION) if msg.len() == 3 => {
+                let (Some(args), Some(method)) = (
+                    core::mem::take(&mut msg[2]).into_array(),
+                    core::mem::take(&mut msg[1]).into_string(),
+                ) else {
+                    return Err(RpcError::Protocol("malformed notification"));
+                };
+                self.notifications.push_back(Notification { method, args });
+                Ok(())
+            }
+            _ => Err(RpcError::Protocol("unknown message type")),
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Finds the end of a msgpack value as its bytes arrive so that it is only decoded once
+///
+/// Decoding the buffer after every read would take quadratic time for messages that span many
+/// reads, such as the lines of a large buffer. Each byte of the headers is only scanned once and
+/// the value itself is not checked, that is left to the decoder.
+#[derive(Default)]
+struct Framer {
+    /// The start of the next value, may be past the end of the buffer when skipping a payload
+    pos: usize,
+    /// The number of values left in each array or map that was started
+    open: Vec<usize>,
+    done: bool,
+}
+
+impl Framer {
+    /// Scans the bytes that were added to `buf`, returns the length of the value once all of it
+    /// was read
+    fn advance(&mut self, buf: &[u8]) -> Option<usize> {
+        while !self.done {
+            let pos = self.pos;
+            let marker = *buf.get(pos)?;
+            // the big endian integer of `n` bytes after the marker
+            let be = |n: usize| {
+                let bytes = buf.get(pos + 1..pos + 1 + n)?;
+                Some(bytes.iter().fold(0, |acc, &b| acc << 8 | b as usize))
+            };
+            // the size of the marker, header and payload and the number of values it contains
+            let (size, values) = match marker {
+                0xcc | 0xd0 => (2, 0),
+                0xcd | 0xd1 => (3, 0),
+                0xce | 0xd2 | 0xca => (5, 0),
+                0xcf | 0xd3 | 0xcb => (9, 0),
+                0xa0..=0xbf => (1 + (marker & 0x1f) as usize, 0),
+                0xd9 | 0xc4 => (2 + be(1)?, 0),
+                0xda | 0xc5 => (3 + be(2)?, 0),
+                0xdb | 0xc6 => (5 + be(4)?, 0),
+                0xd4..=0xd8 => (2 + (1 << (marker - 0xd4)), 0),
+                0xc7 => (3 + be(1)?, 0),
+                0xc8 => (4 + be(2)?, 0),
+                0xc9 => (6 + be(4)?, 0),
+                0x90..=0x9f => (1, (marker & 0x0f) as usize),
+                0xdc => (3, be(2)?),
+                0xdd => (5, be(4)?),
+                0x80..=0x8f => (1, 2 * (marker & 0x0f) as usize),
+                0xde => (3, 2 * be(2)?),
+                0xdf => (5, 2 * be(4)?),
+                _ => (1, 0),
+            };
+            self.pos += size;
+            if values > 0 {
+                self.open.push(values);
+                continue;
+            }
+
+            // a complete value may also complete the containers it is in
+            loop {
+                match self.open.last_mut() {
+                    None => {
+                        self.done = true;
+                        break;
+                    }
+                    Some(left) if *left > 1 => {
+                        *left -= 1;
+                        break;
+                    }
+                    Some(_) => {
+                        self.open.pop();
+                    }
+                }
+            }
+        }
+
+        (self.pos <= buf.len()).then_some(self.pos)
+    }
+}
+
+/// Converts the error of a response in to an [`Error`]
+///
+/// Neovim sends errors as an array of the error type and message.
+fn nvim_error(err: Object) -> Error {
+    let (kind, msg) = match err {
+        Object::Array(arr) => {
+            let mut arr = arr.into_kvec();
+            let msg = match arr.len() {
+                2 => arr.swap_remove(1).into_string(),
+                _ => None,
+            };
+            (arr.first().and_then(Object::as_int), msg)
+        }
+        Object::String(s) => (None, Some(s)),
+        _ => (None, None),
+    };
+    let msg = msg.unwrap_or_else(|| OwnedThinString::from("unknown error"));
+    match kind {
+        Some(1) => Error::validation(msg.as_thinstr()),
+        _ => Error::exception(msg.as_thinstr()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Cursor, Read},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        array, dict,
+        nvim_types::{Array, Buffer, Object, OwnedThinString, Window, msgpack},
+    };
+
+    use super::{Client, Framer, RpcError};
+
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn messages(msgs: &[Array]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for msg in msgs {
+            Object::Array(msg.clone()).to_msgpack(&mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn call_queues_notifications() {
+        let input = messages(&[
+            array![2, "event", [1, 2]],
+            array![0, 7, "request", []],
+            array![1, 0, (Object::Null), "result"],
+            array![1, 1, [0, "failed"], (Object::Null)],
+        ]);
+        let out = Sink::default();
+        // read one byte at a time to test partial messages
+        let reader = Cursor::new(input).bytes().map(|b| b.unwrap());
+        let reader = ByteReader(Box::new(reader));
+        let mut client = Client::from_stream(reader, out.clone());
+
+        let ret = client.call(c"nvim_eval", array!["1"]).unwrap();
+        assert_eq!(ret, Object::String(OwnedThinString::from("result")));
+        let err = client.call(c"nvim_eval", array!["1"]).unwrap_err();
+        assert!(matches!(err, RpcError::Nvim(_)));
+        assert!(matches!(
+            client.call(c"nvim_eval", array!["1"]),
+            Err(RpcError::Closed)
+        ));
+
+        let notification = client.try_notification().unwrap();
+        assert_eq!(notification.method, "event");
+        assert_eq!(notification.args, array![1, 2]);
+        assert!(client.try_notification().is_none());
+
+        let out = out.0.lock().unwrap();
+        let (first, read) = msgpack::decode(&out).unwrap();
+        assert_eq!(first, Object::Array(array![0, 0, "nvim_eval", ["1"]]));
+        // the request from neovim is answered with an error
+        let (response, _) = msgpack::decode(&out[read..]).unwrap();
+        let response = response.into_array().unwrap();
+        assert_eq!(response[..2], [Object::Integer(1), Object::Integer(7)]);
+        assert_eq!(response[3], Object::Null);
+    }
+
+    #[test]
+    fn framer() {
+        let long = "a".repeat(300);
+        let d = Object::Dict(dict! { "key" = [2, 3], "empty" = [] });
+        let msg = messages(&[array![
+            (-1),
+            200,
+            (-200),
+            70000,
+            (i64::MAX),
+            1.5,
+            (Object::Null),
+            true,
+            "fix",
+            (long.as_str()),
+            (Object::Buffer(Buffer::new(1))),
+            (Object::Window(Window::new(70000))),
+            [],
+            [[1], (d)]
+        ]]);
+
+        // only complete once the last byte is read, whichever way the input is split
+        let mut framer = Framer::default();
+        for end in 0..msg.len() {
+            assert_eq!(framer.advance(&msg[..end]), None);
+        }
+        assert_eq!(framer.advance(&msg), Some(msg.len()));
+        let mut framer = Framer::default();
+        let mut twice = msg.clone();
+        twice.extend_from_slice(&msg);
+        assert_eq!(framer.advance(&twice), Some(msg.len()));
+        // a string is skipped without reading its payload
+        let mut framer = Framer::default();
+        let mut buf = vec![0x92, 0xda, 0x01, 0x00];
+        assert_eq!(framer.advance(&buf), None);
+        assert_eq!(framer.pos, 0x104);
+        buf.resize(0x105, 0xc0);
+        assert_eq!(framer.advance(&buf), Some(0x105));
+    }
+
+    struct ByteReader(Box<dyn Iterator<Item = u8> + Send>);
+
+    impl Read for ByteReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.next() {
+                Some(b) if !buf.is_empty() => {
+                    buf[0] = b;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+}
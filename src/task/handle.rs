@@ -0,0 +1,215 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    future::Future,
+    marker::PhantomData,
+    panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use thread_lock::call_check;
+
+use crate::{
+    nvim_funcs::global::echo,
+    nvim_types::{func_types::echo::Echo, opts::echo::EchoOpts},
+};
+
+type Scheduled = Box<dyn FnOnce() + Send>;
+
+/// Closures scheduled from any thread that are waiting to run on the main thread
+static SCHEDULED: Mutex<Vec<Scheduled>> = Mutex::new(Vec::new());
+
+/// A handle that schedules closures to run on the main thread with access to Neovim
+///
+/// The handle can only be created on the main thread but can be cloned and sent to other threads.
+/// Scheduled closures run in the order they were scheduled once Neovim processes its event queue,
+/// the same way tasks spawned with [`spawn_local`](super::spawn_local) are polled.
+///
+/// A panic in a scheduled closure is reported as an error message and does not affect other
+/// closures.
+///
+/// # Example
+/// ```no_run
+/// use nvimium::{nvim_funcs::global::set_var, nvim_types::Object, task::MainThreadHandle};
+///
+/// let handle = MainThreadHandle::new();
+/// std::thread::spawn(move || {
+///     let count = (0..1000).filter(|i| i % 3 == 0).count();
+///     handle.schedule(move || {
+///         set_var(c"count", &Object::Integer(count as i64)).unwrap();
+///     });
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct MainThreadHandle {
+    // only constructed through new so that the executor is always initialized
+    _priv: PhantomData<()>,
+}
+
+impl MainThreadHandle {
+    /// Creates a handle to the main thread
+    ///
+    /// # Panics
+    ///
+    /// If called from a thread that does not have access to Neovim.
+    pub fn new() -> Self {
+        call_check();
+        super::init();
+        Self { _priv: PhantomData }
+    }
+
+    /// Schedules the closure to run on the main thread
+    pub fn schedule<F: 'static + FnOnce() + Send>(&self, f: F) {
+        SCHEDULED
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(Box::new(f));
+        super::signal();
+    }
+
+    /// Schedules the closure to run on the main thread and returns a receiver for its return value
+    ///
+    /// The receiver returns [`Canceled`] if the closure panics.
+    pub fn schedule_with_result<R: 'static + Send, F: 'static + FnOnce() -> R + Send>(
+        &self,
+        f: F,
+    ) -> Receiver<R> {
+        let shared = Arc::new(Oneshot {
+            state: Mutex::new(OneshotState {
+                value: None,
+                closed: false,
+                waker: None,
+            }),
+            cond: Condvar::new(),
+        });
+        let sender = Sender(shared.clone());
+        self.schedule(move || sender.send(f()));
+
+        Receiver(shared)
+    }
+}
+
+impl Default for MainThreadHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs every closure that was scheduled before the call
+pub(super) fn run_scheduled() {
+    let scheduled = std::mem::take(&mut *SCHEDULED.lock().unwrap_or_else(|err| err.into_inner()));
+    for f in scheduled {
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(f)) {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic payload");
+            let _ = echo(
+                &Echo::message(format!("Error: scheduled closure panicked: {}", reason).as_str()),
+                true,
+                EchoOpts::default().err(true),
+            );
+        }
+    }
+}
+
+struct OneshotState<T> {
+    value: Option<T>,
+    /// Set once the sender is dropped with or without sending a value
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+struct Oneshot<T> {
+    state: Mutex<OneshotState<T>>,
+    cond: Condvar,
+}
+
+impl<T> Oneshot<T> {
+    fn lock(&self) -> std::sync::MutexGuard<'_, OneshotState<T>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+struct Sender<T>(Arc<Oneshot<T>>);
+
+impl<T> Sender<T> {
+    fn send(self, value: T) {
+        self.0.lock().value = Some(value);
+        // dropping the sender notifies the receiver
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.closed = true;
+        let waker = state.waker.take();
+        drop(state);
+        self.0.cond.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// The error returned by a [`Receiver`] when the scheduled closure panicked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Canceled;
+
+impl Display for Canceled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "scheduled closure did not return a value")
+    }
+}
+
+impl Error for Canceled {}
+
+/// Receives the value returned by a closure scheduled with
+/// [`MainThreadHandle::schedule_with_result`]
+///
+/// The value can be received by blocking the current thread with [`Receiver::recv`] or by
+/// awaiting the receiver.
+///
+/// Blocking the main thread with [`Receiver::recv`] never returns as the closure can only run
+/// after control is returned to Neovim.
+pub struct Receiver<T>(Arc<Oneshot<T>>);
+
+impl<T> Receiver<T> {
+    /// Blocks the current thread until the closure has run and returns its value
+    pub fn recv(self) -> Result<T, Canceled> {
+        let mut state = self.0.lock();
+        while !state.closed {
+            state = self
+                .0
+                .cond
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+        state.value.take().ok_or(Canceled)
+    }
+
+    /// Returns the value if the closure has run, or [`None`] if it has not run yet
+    pub fn try_recv(&mut self) -> Option<Result<T, Canceled>> {
+        let mut state = self.0.lock();
+        if !state.closed {
+            return None;
+        }
+        Some(state.value.take().ok_or(Canceled))
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, Canceled>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock();
+        if state.closed {
+            return Poll::Ready(state.value.take().ok_or(Canceled));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
@@ -0,0 +1,401 @@
+//! A single threaded executor that runs [`Future`]s on Neovim's event loop
+//!
+//! Futures are spawned with [`spawn_local`] and are only ever polled on the main thread with
+//! access to Neovim, so they can call any Neovim function. Wakers can be sent to and woken from
+//! any thread.
+//!
+//! Threads that need to call Neovim functions can schedule closures on the main thread with a
+//! [`MainThreadHandle`].
+//!
+//! Waking a task queues it and signals a libuv async handle. The handle's callback runs on the main
+//! loop where Neovim functions may not be called, so it only schedules the queued tasks with
+//! `vim.schedule` and they are polled once Neovim gets to them. As a result every wake is deferred
+//! until Neovim processes its event queue and a task that keeps yielding does not block Neovim.
+//!
+//! # Example
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use nvimium::task::{sleep, spawn_local, yield_now};
+//!
+//! spawn_local(async {
+//!     sleep(Duration::from_millis(100)).await;
+//!     // let Neovim redraw before continuing
+//!     yield_now().await;
+//! });
+//! ```
+
+mod handle;
+mod sleep;
+mod timer;
+
+use std::{
+    cell::{Cell, RefCell},
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    ptr::null_mut,
+    rc::Rc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicPtr, Ordering},
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use thread_lock::call_check;
+
+pub use handle::{Canceled, MainThreadHandle, Receiver};
+pub use sleep::{Sleep, sleep};
+pub use timer::{Debounce, Throttle, Timer};
+
+use crate::{
+    nvim_types::{LuaRef, lua::Function},
+    uv::{HandleType, uv_async_init, uv_async_send, uv_handle_t},
+};
+
+type LocalFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Task ID's that were woken and should be polled
+///
+/// Wakers can be called from any thread so this lives outside of the main thread's executor.
+static WOKEN: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+/// The async handle that is signaled after a task is woken, null until the executor is initialized
+static ASYNC_HANDLE: AtomicPtr<uv_handle_t> = AtomicPtr::new(null_mut());
+
+struct TaskWaker(usize);
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        WOKEN
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(self.0);
+        signal();
+    }
+}
+
+/// Signals the async handle so that pending work is run on the main thread
+///
+/// Wakers and [`MainThreadHandle`]s only exist after [`init`] so the handle is always set.
+fn signal() {
+    let handle = ASYNC_HANDLE.load(Ordering::Acquire);
+    if !handle.is_null() {
+        unsafe { uv_async_send(handle) };
+    }
+}
+
+struct Task {
+    future: Option<LocalFuture>,
+    waker: Waker,
+}
+
+#[derive(Default)]
+struct Executor {
+    tasks: Vec<Option<Task>>,
+    free: Vec<usize>,
+    /// The Lua function passed to `vim.schedule` that runs the scheduled closures and polls the
+    /// woken tasks
+    run: Option<LuaRef>,
+}
+
+thread_local! {
+    static EXECUTOR: RefCell<Executor> = RefCell::default();
+}
+
+/// Creates the async handle and the function that polls the tasks on first use
+fn init() {
+    if !ASYNC_HANDLE.load(Ordering::Acquire).is_null() {
+        return;
+    }
+
+    let run = Function::wrap(|()| {
+        handle::run_scheduled();
+        run_woken();
+        Ok::<_, Infallible>(())
+    })
+    .into_luaref();
+    EXECUTOR.with_borrow_mut(|ex| ex.run = Some(run));
+
+    // the executor lives as long as Neovim so the handle is never closed
+    let handle = unsafe {
+        crate::uv::Handle::new(HandleType::Async, (), |uv_loop, handle| {
+            uv_async_init(uv_loop, handle, on_async)
+        })
+    };
+    ASYNC_HANDLE.store(handle.as_ptr(), Ordering::Release);
+    std::mem::forget(handle);
+}
+
+/// Runs in a libuv callback, so the pending work is run later from `vim.schedule`
+extern "C" fn on_async(_: *mut uv_handle_t) {
+    unsafe {
+        thread_lock::scoped(
+            |_| {
+                let l = thread_lock::main_lua_ptr();
+                if mlua_sys::lua_checkstack(l, 3) == 0 {
+                    return;
+                }
+                mlua_sys::lua_getglobal(l, c"vim".as_ptr());
+                mlua_sys::lua_getfield(l, -1, c"schedule".as_ptr());
+                mlua_sys::lua_remove(l, -2);
+                EXECUTOR.with_borrow(|ex| {
+                    if let Some(run) = &ex.run {
+                        crate::nvim_types::IntoLua::push(run, l);
+                    }
+                });
+                // vim.schedule only fails if it is passed something other than a function
+                if mlua_sys::lua_pcall(l, 1, 0, 0) != 0 {
+                    mlua_sys::lua_pop(l, 1);
+                }
+            },
+            (),
+        );
+    }
+}
+
+/// Polls every task that was woken before the call
+fn run_woken() {
+    let woken = std::mem::take(&mut *WOKEN.lock().unwrap_or_else(|err| err.into_inner()));
+    for id in woken {
+        // the future is taken out while it is polled so that it can spawn other tasks
+        let Some((mut future, waker)) = EXECUTOR.with_borrow_mut(|ex| {
+            let task = ex.tasks.get_mut(id)?.as_mut()?;
+            Some((task.future.take()?, task.waker.clone()))
+        }) else {
+            continue;
+        };
+
+        let ready = future
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+            .is_ready();
+        EXECUTOR.with_borrow_mut(|ex| {
+            if ready {
+                ex.tasks[id] = None;
+                ex.free.push(id);
+            } else if let Some(task) = ex.tasks[id].as_mut() {
+                task.future = Some(future);
+            }
+        });
+    }
+}
+
+/// Spawns a future on the main thread and returns a handle to its output
+///
+/// The future is first polled after Neovim processes its event queue, not during this call.
+/// Dropping the returned [`JoinHandle`] does not cancel the task.
+///
+/// # Panics
+///
+/// If called from a thread that does not have access to Neovim.
+pub fn spawn_local<F: 'static + Future>(future: F) -> JoinHandle<F::Output> {
+    call_check();
+    init();
+
+    let state = Rc::new(JoinState {
+        output: Cell::new(None),
+        waker: Cell::new(None),
+    });
+    let task_state = state.clone();
+    let future: LocalFuture = Box::pin(async move {
+        let output = future.await;
+        task_state.output.set(Some(output));
+        if let Some(waker) = task_state.waker.take() {
+            waker.wake();
+        }
+    });
+
+    let waker = EXECUTOR.with_borrow_mut(|ex| {
+        let id = ex.free.pop().unwrap_or(ex.tasks.len());
+        let waker = Waker::from(Arc::new(TaskWaker(id)));
+        let task = Task {
+            future: Some(future),
+            waker: waker.clone(),
+        };
+        if id == ex.tasks.len() {
+            ex.tasks.push(Some(task));
+        } else {
+            ex.tasks[id] = Some(task);
+        }
+        waker
+    });
+    waker.wake();
+
+    JoinHandle { state }
+}
+
+struct JoinState<T> {
+    output: Cell<Option<T>>,
+    waker: Cell<Option<Waker>>,
+}
+
+/// A handle to a task spawned with [`spawn_local`]
+///
+/// Awaiting the handle returns the output of the task.
+pub struct JoinHandle<T> {
+    state: Rc<JoinState<T>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Returns true if the task has completed and its output was not taken yet
+    pub fn is_finished(&self) -> bool {
+        let output = self.state.output.take();
+        let finished = output.is_some();
+        self.state.output.set(output);
+        finished
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                self.state.waker.set(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Yields execution back to Neovim
+///
+/// The task is polled again after Neovim processes its pending events which allows it to redraw
+/// and handle input during a long running task.
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// The future returned by [`yield_now`]
+#[must_use = "futures do nothing unless polled"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(all(not(miri), feature = "testing"))]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use crate::{
+        self as nvimium,
+        nvim_funcs::{
+            global::{get_var, set_var},
+            vimscript::exec2,
+        },
+        nvim_types::Object,
+    };
+
+    #[nvim_test::nvim_test(no_exit)]
+    fn spawn_sleep_yield() {
+        let value = super::spawn_local(async { 5 });
+        super::spawn_local(async move {
+            assert_eq!(value.await, 5);
+            super::sleep(Duration::from_millis(10)).await;
+            super::yield_now().await;
+            let nested = super::spawn_local(async {
+                super::yield_now().await;
+                "nested"
+            });
+            assert_eq!(nested.await, "nested");
+            exec2(c":qall!", &Default::default()).unwrap();
+        });
+    }
+
+    #[nvim_test::nvim_test(no_exit)]
+    fn main_thread_handle() {
+        let handle = super::MainThreadHandle::new();
+        let rx = std::thread::spawn(move || {
+            handle.schedule(|| set_var(c"nvimium_scheduled", &Object::Integer(1)).unwrap());
+            handle.schedule_with_result(|| {
+                // Object is not Send so only the integer is sent back
+                match get_var(c"nvimium_scheduled").unwrap() {
+                    Object::Integer(i) => i,
+                    _ => panic!("variable is not an integer"),
+                }
+            })
+        })
+        .join()
+        .unwrap();
+
+        super::spawn_local(async move {
+            // closures run in the order they were scheduled
+            assert_eq!(rx.await, Ok(1));
+            exec2(c":qall!", &Default::default()).unwrap();
+        });
+    }
+
+    #[nvim_test::nvim_test(no_exit)]
+    fn timer_debounce() {
+        let debounced = Rc::new(Cell::new(0));
+        let counter = debounced.clone();
+        let mut debounce = super::Debounce::new(Duration::from_millis(5), move || {
+            counter.set(counter.get() + 1);
+        });
+        for _ in 0..3 {
+            debounce.call().unwrap();
+        }
+
+        let ticks = Rc::new(Cell::new(0));
+        let counter = ticks.clone();
+        let timer = super::Timer::start(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            move || counter.set(counter.get() + 1),
+        )
+        .unwrap();
+
+        // the future holds the debounce and timer so that they outlive the test function
+        super::spawn_local(async move {
+            super::sleep(Duration::from_millis(100)).await;
+            assert_eq!(debounced.get(), 1);
+            assert!(ticks.get() >= 3);
+            drop((debounce, timer));
+            exec2(c":qall!", &Default::default()).unwrap();
+        });
+    }
+
+    #[nvim_test::nvim_test(no_exit)]
+    fn timer_throttle() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let throttle = super::Throttle::new(Duration::from_millis(20), move || {
+            counter.set(counter.get() + 1);
+        });
+
+        // the leading call runs immediately
+        throttle.call().unwrap();
+        assert_eq!(calls.get(), 1);
+        // calls within the interval are merged into a single call at the end of it
+        for _ in 0..3 {
+            throttle.call().unwrap();
+        }
+        assert_eq!(calls.get(), 1);
+
+        super::spawn_local(async move {
+            super::sleep(Duration::from_millis(100)).await;
+            assert_eq!(calls.get(), 2);
+            // an interval without calls ends the window so the next call runs immediately
+            throttle.call().unwrap();
+            assert_eq!(calls.get(), 3);
+            exec2(c":qall!", &Default::default()).unwrap();
+        });
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use crate::uv::{Handle, HandleType, uv_handle_t, uv_timer_init, uv_timer_start};
+
+#[derive(Default)]
+struct TimerState {
+    fired: Cell<bool>,
+    waker: Cell<Option<Waker>>,
+}
+
+/// Waits until the duration has elapsed
+///
+/// The timer is driven by Neovim's event loop and starts when the future is first polled. The
+/// duration is rounded down to milliseconds.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        duration,
+        timer: None,
+    }
+}
+
+/// The future returned by [`sleep`]
+///
+/// Dropping the future before it completes stops the timer.
+#[must_use = "futures do nothing unless polled"]
+pub struct Sleep {
+    duration: Duration,
+    timer: Option<Handle<TimerState>>,
+}
+
+extern "C" fn on_timer(handle: *mut uv_handle_t) {
+    let state = unsafe { Handle::<TimerState>::data(handle) };
+    state.fired.set(true);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    /// # Panics
+    ///
+    /// If polled from a thread that does not have access to Neovim.
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let timeout = u64::try_from(self.duration.as_millis()).unwrap_or(u64::MAX);
+        let timer = self.timer.get_or_insert_with(|| unsafe {
+            let timer = Handle::new(
+                HandleType::Timer,
+                TimerState::default(),
+                |uv_loop, handle| uv_timer_init(uv_loop, handle),
+            );
+            uv_timer_start(timer.as_ptr(), on_timer, timeout, 0);
+            timer
+        });
+
+        let state = unsafe { Handle::<TimerState>::data(timer.as_ptr()) };
+        if state.fired.get() {
+            return Poll::Ready(());
+        }
+        state.waker.set(Some(cx.waker().clone()));
+        Poll::Pending
+    }
+}
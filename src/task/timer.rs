@@ -0,0 +1,233 @@
+use std::{
+    cell::{Cell, RefCell},
+    convert::Infallible,
+    ffi::CStr,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::nvim_types::{
+    Integer, LuaRef, Object,
+    lua::{CallError, Function, IntoLuaMany, utils::vim_function},
+};
+
+/// Calls `vim.uv.<name>`
+///
+/// luv functions return `nil` followed by the error message and name instead of raising an error.
+/// On success only the result is returned so the error is read as an [`Option`].
+fn uv_call<A: IntoLuaMany>(name: &CStr, args: A) -> Result<(), CallError> {
+    let (ret, err): (Object, Option<Object>) = vim_function(&[c"uv", name]).call(args)?;
+    match (ret, err) {
+        (Object::Null, Some(Object::String(err))) => Err(CallError::Runtime {
+            message: format!(
+                "vim.uv.{}: {}",
+                name.to_string_lossy(),
+                err.as_thinstr().to_str_lossy()
+            ),
+            traceback: String::new(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn millis(duration: Duration) -> Integer {
+    Integer::try_from(duration.as_millis()).unwrap_or(Integer::MAX)
+}
+
+/// A timer driven by Neovim's event loop, created with `vim.uv.new_timer`
+///
+/// The callback is wrapped with `vim.schedule_wrap` so that it can call any Neovim function. As a
+/// result it runs once Neovim processes its event queue which may be later than the timeout.
+///
+/// The timer is stopped and closed when dropped.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use nvimium::{nvim_funcs::vimscript::command, task::Timer};
+///
+/// // the timer must be kept alive for the callback to be called
+/// let timer = Timer::start(Duration::from_secs(1), Duration::ZERO, || {
+///     command(c"echo 'a second has passed'").unwrap();
+/// })
+/// .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Timer {
+    timer: LuaRef,
+    callback: LuaRef,
+}
+
+impl Timer {
+    /// Creates and starts a timer that calls `f` after `timeout` and then every `repeat`
+    ///
+    /// A zero `repeat` calls `f` once.
+    ///
+    /// # Panics
+    ///
+    /// If called from a thread that does not have access to Neovim.
+    pub fn start<F: 'static + Fn() + Unpin>(
+        timeout: Duration,
+        repeat: Duration,
+        f: F,
+    ) -> Result<Self, CallError> {
+        let timer: LuaRef = vim_function(&[c"uv", c"new_timer"]).call(())?;
+        let callback = Function::wrap(move |()| {
+            f();
+            Ok::<_, Infallible>(())
+        });
+        let callback: LuaRef = vim_function(&[c"schedule_wrap"]).call(callback.into_luaref())?;
+
+        let timer = Self { timer, callback };
+        timer.restart(timeout, repeat)?;
+        Ok(timer)
+    }
+
+    /// Starts the timer again with a new timeout and repeat, a running timer is reset
+    pub fn restart(&self, timeout: Duration, repeat: Duration) -> Result<(), CallError> {
+        uv_call(
+            c"timer_start",
+            (
+                self.timer.clone(),
+                millis(timeout),
+                millis(repeat),
+                self.callback.clone(),
+            ),
+        )
+    }
+
+    /// Stops the timer, it can be started again with [`Timer::again`] or [`Timer::restart`]
+    pub fn stop(&self) -> Result<(), CallError> {
+        uv_call(c"timer_stop", self.timer.clone())
+    }
+
+    /// Stops the timer and starts it again using the repeat value as the timeout
+    ///
+    /// Returns an error if the timer was never started with a non zero repeat.
+    pub fn again(&self) -> Result<(), CallError> {
+        uv_call(c"timer_again", self.timer.clone())
+    }
+
+    /// Sets the repeat interval, takes effect at the next timeout or call to [`Timer::again`]
+    pub fn set_repeat(&self, repeat: Duration) -> Result<(), CallError> {
+        uv_call(c"timer_set_repeat", (self.timer.clone(), millis(repeat)))
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        // `close` also stops the timer, errors are ignored as there is nothing to do about them
+        let _ = vim_function(&[c"uv", c"close"]).call::<_, ()>(self.timer.clone());
+    }
+}
+
+/// Delays calls to a function until no calls were made for a duration
+///
+/// Useful for reacting to a burst of events such as buffer changes only once they settle.
+pub struct Debounce {
+    delay: Duration,
+    f: Rc<dyn Fn()>,
+    timer: Option<Timer>,
+}
+
+impl Debounce {
+    pub fn new<F: 'static + Fn()>(delay: Duration, f: F) -> Self {
+        Self {
+            delay,
+            f: Rc::new(f),
+            timer: None,
+        }
+    }
+
+    /// Calls the function after the delay, a pending call is pushed back by the delay instead
+    pub fn call(&mut self) -> Result<(), CallError> {
+        match &self.timer {
+            Some(timer) => timer.restart(self.delay, Duration::ZERO),
+            None => {
+                let f = self.f.clone();
+                self.timer = Some(Timer::start(self.delay, Duration::ZERO, move || f())?);
+                Ok(())
+            }
+        }
+    }
+
+    /// Cancels a pending call
+    pub fn cancel(&self) -> Result<(), CallError> {
+        match &self.timer {
+            Some(timer) => timer.stop(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Limits calls to a function to at most one per interval
+///
+/// The first call runs the function immediately. Calls made during the interval are merged into a
+/// single call at the end of it.
+pub struct Throttle {
+    inner: Rc<ThrottleInner>,
+}
+
+struct ThrottleInner {
+    interval: Duration,
+    f: Box<dyn Fn()>,
+    cooling: Cell<bool>,
+    pending: Cell<bool>,
+    timer: RefCell<Option<Timer>>,
+}
+
+impl Throttle {
+    pub fn new<F: 'static + Fn()>(interval: Duration, f: F) -> Self {
+        Self {
+            inner: Rc::new(ThrottleInner {
+                interval,
+                f: Box::new(f),
+                cooling: Cell::new(false),
+                pending: Cell::new(false),
+                timer: RefCell::new(None),
+            }),
+        }
+    }
+
+    /// Calls the function now if the interval has passed since the last call, or at the end of
+    /// the interval otherwise
+    pub fn call(&self) -> Result<(), CallError> {
+        let inner = &self.inner;
+        if inner.cooling.get() {
+            inner.pending.set(true);
+            return Ok(());
+        }
+
+        (inner.f)();
+        inner.cooling.set(true);
+        if let Some(timer) = &*inner.timer.borrow() {
+            return timer.restart(inner.interval, inner.interval);
+        }
+
+        // the timer is owned by `inner` so the callback only holds a weak reference to it
+        let weak = Rc::downgrade(inner);
+        let timer = Timer::start(inner.interval, inner.interval, move || {
+            if let Some(inner) = weak.upgrade() {
+                inner.tick();
+            }
+        })?;
+        *inner.timer.borrow_mut() = Some(timer);
+        Ok(())
+    }
+}
+
+impl ThrottleInner {
+    /// Called at the end of each interval
+    fn tick(&self) {
+        if self.pending.replace(false) {
+            (self.f)();
+            return;
+        }
+
+        self.cooling.set(false);
+        if let Some(timer) = &*self.timer.borrow() {
+            let _ = timer.stop();
+        }
+    }
+}
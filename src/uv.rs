@@ -0,0 +1,137 @@
+//! Bindings to the parts of libuv used by nvimium
+//!
+//! Neovim statically links libuv and luv, so the symbols are resolved from the Neovim binary the
+//! same way as the API functions. Handle sizes differ between platforms, which is why handles are
+//! allocated with [`uv_handle_size`] instead of declaring their layout.
+#![allow(non_camel_case_types)]
+
+use std::{ffi::c_void, marker::PhantomData, ptr::NonNull};
+
+use libc::c_int;
+use mlua_sys::lua_State;
+
+#[repr(C)]
+pub(crate) struct uv_loop_t {
+    _unused: [u8; 0],
+}
+
+#[repr(C)]
+pub(crate) struct uv_handle_t {
+    _unused: [u8; 0],
+}
+
+/// The handle types from `uv_handle_type`, only the ones that are used are declared
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HandleType {
+    Async = 1,
+    Timer = 13,
+}
+
+pub(crate) type uv_close_cb = extern "C" fn(*mut uv_handle_t);
+pub(crate) type uv_async_cb = extern "C" fn(*mut uv_handle_t);
+pub(crate) type uv_timer_cb = extern "C" fn(*mut uv_handle_t);
+
+unsafe extern "C" {
+    /// Returns the loop used by luv, which is Neovim's main loop
+    pub fn luv_loop(l: *mut lua_State) -> *mut uv_loop_t;
+    pub fn uv_handle_size(kind: HandleType) -> usize;
+    pub fn uv_handle_get_data(handle: *const uv_handle_t) -> *mut c_void;
+    pub fn uv_handle_set_data(handle: *mut uv_handle_t, data: *mut c_void);
+    pub fn uv_close(handle: *mut uv_handle_t, cb: uv_close_cb);
+    pub fn uv_unref(handle: *mut uv_handle_t);
+
+    pub fn uv_async_init(l: *mut uv_loop_t, handle: *mut uv_handle_t, cb: uv_async_cb) -> c_int;
+    /// The only libuv function that is safe to call from any thread
+    pub fn uv_async_send(handle: *mut uv_handle_t) -> c_int;
+
+    pub fn uv_timer_init(l: *mut uv_loop_t, handle: *mut uv_handle_t) -> c_int;
+    pub fn uv_timer_start(
+        handle: *mut uv_handle_t,
+        cb: uv_timer_cb,
+        timeout: u64,
+        repeat: u64,
+    ) -> c_int;
+}
+
+/// Returns Neovim's main loop
+///
+/// # Panics
+///
+/// Calls [`thread_lock::call_check`] so any panics caused by it also apply here.
+pub(crate) fn main_loop() -> *mut uv_loop_t {
+    thread_lock::call_check();
+    unsafe { luv_loop(thread_lock::main_lua_ptr()) }
+}
+
+/// An initialized libuv handle that owns a value of `T`
+///
+/// The value is stored in the data field of the handle so that callbacks can access it with
+/// [`Handle::data`]. Dropping the handle closes it, the memory and the value are freed once libuv
+/// calls the close callback.
+pub(crate) struct Handle<T: 'static> {
+    ptr: NonNull<uv_handle_t>,
+    _data: PhantomData<T>,
+}
+
+impl<T: 'static> Handle<T> {
+    /// Allocates a handle of the provided type and initializes it with `init`
+    ///
+    /// # Panics
+    ///
+    /// If `init` returns an error or if the current thread does not have access to Neovim.
+    ///
+    /// # Safety
+    ///
+    /// `init` must initialize the handle with the provided loop as a handle of type `kind`.
+    pub(crate) unsafe fn new<F: FnOnce(*mut uv_loop_t, *mut uv_handle_t) -> c_int>(
+        kind: HandleType,
+        data: T,
+        init: F,
+    ) -> Self {
+        let uv_loop = main_loop();
+        unsafe {
+            let ptr = NonNull::new(libc::calloc(1, uv_handle_size(kind)) as *mut uv_handle_t)
+                .expect("unable to allocate libuv handle");
+            let status = init(uv_loop, ptr.as_ptr());
+            if status != 0 {
+                libc::free(ptr.as_ptr().cast());
+                panic!("unable to initialize libuv handle: error code {}", status);
+            }
+            uv_handle_set_data(ptr.as_ptr(), Box::into_raw(Box::new(data)).cast());
+            // a handle created by a plugin should never keep Neovim from exiting
+            uv_unref(ptr.as_ptr());
+            Self {
+                ptr,
+                _data: PhantomData,
+            }
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut uv_handle_t {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns the value stored in the handle
+    ///
+    /// # Safety
+    ///
+    /// The handle must have been created by [`Handle::new`] with the same `T` and must not be
+    /// closed yet.
+    pub(crate) unsafe fn data<'a>(handle: *mut uv_handle_t) -> &'a T {
+        unsafe { &*(uv_handle_get_data(handle) as *const T) }
+    }
+
+    extern "C" fn close(handle: *mut uv_handle_t) {
+        unsafe {
+            drop(Box::from_raw(uv_handle_get_data(handle) as *mut T));
+            libc::free(handle.cast());
+        }
+    }
+}
+
+impl<T: 'static> Drop for Handle<T> {
+    fn drop(&mut self) {
+        unsafe { uv_close(self.ptr.as_ptr(), Self::close) };
+    }
+}
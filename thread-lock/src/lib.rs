@@ -1,5 +1,6 @@
 // This works exactly how I want it to work but not a fan of the possible cost
-// TODO: no idea how this would work with LUV
+// LUV callbacks are not wrapped by nvimium, anything that runs in one (such as the executor in
+// nvimium) must call [`scoped`] itself and use [`main_lua_ptr`] as no callback Lua pointer is set.
 
 //! Various sync utilities for Neovim and Lua functions
 //!
@@ -219,6 +220,15 @@ pub unsafe fn init_lua_ptr(ptr: *mut lua_State) {
     LUA_PTR.set(NonNull::new(ptr));
 }
 
+/// Returns the main Lua pointer regardless of the Lua pointer of the current thread
+///
+/// Used for code that runs outside of a Lua callback, such as a libuv callback, where the
+/// thread's Lua pointer may be left over from a previous callback.
+#[inline(always)]
+pub fn main_lua_ptr() -> *mut lua_State {
+    MAIN_LUA.load(Ordering::Relaxed)
+}
+
 pub struct LuaPtr {
     ptr: *mut lua_State,
     should_unset: bool,